@@ -1,4 +1,5 @@
-use eureka::core::App;
+use eureka::asset::AssetServer;
+use eureka::core::{App, AppConfig};
 use eureka::math::color::ColorU;
 use eureka::math::transform::{Transform2d, Transform3d};
 use eureka::scene::{
@@ -6,7 +7,7 @@ use eureka::scene::{
     DirectionalLightComponent, GlobalTransform, LabelComponent, Name, PointLightComponent,
     SkyAssetPending,
 };
-use eureka::window::InputContent;
+use eureka::window::{InputContent, InputServer};
 use glam::{Quat, Vec2, Vec3};
 use winit::keyboard::KeyCode;
 
@@ -22,10 +23,15 @@ struct SunLogic {
 }
 
 fn main() {
-    let mut app = App::new();
+    let mut app = App::new(AppConfig::default());
 
     app.setup(|app| {
-        let singletons = app.singletons.as_mut().unwrap();
+        let asset_dir = app
+            .world
+            .resources
+            .resource::<AssetServer>()
+            .asset_dir
+            .clone();
         let world = &mut app.world;
 
         // 1. 添加 3D 摄像机和控制器
@@ -34,7 +40,7 @@ fn main() {
 
         world.ecs.spawn((
             Name("MainCamera".into()),
-            CTransform3d(Transform3d {
+            CTransform3d::new(Transform3d {
                 position: Vec3::new(-5.0, 2.0, 0.0),
                 ..Transform3d::default()
             }),
@@ -47,7 +53,7 @@ fn main() {
         // 2. 添加 UI 覆盖层 (2D 摄像机)
         world.ecs.spawn((
             Name("UICamera".into()),
-            CTransform2d(Transform2d::default()),
+            CTransform2d::new(Transform2d::default()),
             GlobalTransform::default(),
             eureka::scene::d2::camera2d::Camera2dComponent::default(),
             ActiveCamera,
@@ -57,7 +63,7 @@ fn main() {
         world.ecs.spawn((
             Name("Settings".into()),
             LabelComponent::new("SSAO (1): ON | AA (2): TAA | Volumetric (3): OFF | SSR (4): OFF | SSGI (5): OFF"),
-            CTransform2d(Transform2d {
+            CTransform2d::new(Transform2d {
                 position: Vec2::new(20.0, 20.0),
                 ..Transform2d::default()
             }),
@@ -65,7 +71,7 @@ fn main() {
         ));
 
         // 4. 添加环境
-        let skybox_path = singletons.asset_server.asset_dir.join("images/Panorama_Sky_21-512x512.png");
+        let skybox_path = asset_dir.join("images/Panorama_Sky_21-512x512.png");
         world
             .ecs
             .spawn((Name("Skybox".into()), SkyAssetPending(skybox_path)));
@@ -73,7 +79,7 @@ fn main() {
         // 5. 灯光
         world.ecs.spawn((
             "PointLight",
-            CTransform3d(Transform3d {
+            CTransform3d::new(Transform3d {
                 position: Vec3::new(0.0, 5.0, 0.0),
                 ..Transform3d::default()
             }),
@@ -88,7 +94,7 @@ fn main() {
 
         world.ecs.spawn((
             Name("DirLight".into()),
-            CTransform3d(Transform3d {
+            CTransform3d::new(Transform3d {
                 rotation: Quat::from_rotation_x(-135.0f32.to_radians()),
                 ..Transform3d::default()
             }),
@@ -104,12 +110,10 @@ fn main() {
         ));
 
         // 6. 模型
-        let asset_dir = singletons.asset_server.asset_dir.clone();
-
         // 螃蟹 (漂浮)
         world.ecs.spawn((
             Name("Ferris".into()),
-            CTransform3d(Transform3d {
+            CTransform3d::new(Transform3d {
                 position: Vec3::new(4.0, 0.1, 0.0),
                 ..Transform3d::default()
             }),
@@ -124,7 +128,7 @@ fn main() {
         // 旋转立方体
         world.ecs.spawn((
             Name("Cube".into()),
-            CTransform3d(Transform3d {
+            CTransform3d::new(Transform3d {
                 position: Vec3::new(2.0, 1.2, 2.0),
                 scale: Vec3::splat(0.5),
                 ..Transform3d::default()
@@ -137,7 +141,7 @@ fn main() {
         // 金属球 (MetalRoughSpheres)
         world.ecs.spawn((
             Name("Spheres".into()),
-            CTransform3d(Transform3d {
+            CTransform3d::new(Transform3d {
                 position: Vec3::new(0.0, 1.0, -0.5),
                 scale: Vec3::splat(0.1),
                 rotation: Quat::from_rotation_z(90.0f32.to_radians()),
@@ -157,7 +161,7 @@ fn main() {
         // 镜面立方体 (Mirror Cube)
         world.ecs.spawn((
             Name("MirrorCube".into()),
-            CTransform3d(Transform3d {
+            CTransform3d::new(Transform3d {
                 position: Vec3::new(-2.0, 1.5, 0.0),
                 ..Transform3d::default()
             }),
@@ -174,7 +178,7 @@ fn main() {
             .query_mut::<&mut CTransform3d>()
             .with::<&RotatingLogic>()
         {
-            transform.0.rotation *= Quat::from_rotation_y(dt);
+            transform.rotation *= Quat::from_rotation_y(dt);
         }
     });
 
@@ -186,7 +190,7 @@ fn main() {
             .query_mut::<(&mut CTransform3d, &mut FloatingLogic)>()
         {
             logic.timer += dt * logic.speed;
-            transform.0.position.y = 1.0 + logic.timer.sin() * 1.0;
+            transform.position.y = 1.0 + logic.timer.sin() * 1.0;
         }
     });
 
@@ -201,14 +205,14 @@ fn main() {
             logic.timer += dt * logic.speed;
 
             // 让太阳绕 X 轴旋转（模拟东升西落）
-            transform.0.rotation = Quat::from_rotation_x(logic.timer);
+            transform.rotation = Quat::from_rotation_x(logic.timer);
         }
     });
 
     // 添加自定义输入处理：设置控制
     app.add_update(|app, _dt| {
         let world = &mut app.world;
-        let input_server = &app.singletons.as_ref().unwrap().input_server;
+        let input_server = world.resources.resource::<InputServer>();
 
         for event in input_server.events() {
             if let InputContent::Key(e) = &event.content {
@@ -291,5 +295,30 @@ fn main() {
         }
     });
 
+    // 窗口设置：F11 切换全屏，V 切换垂直同步
+    app.add_update(|app, _dt| {
+        let pressed: Vec<KeyCode> = app
+            .world
+            .resources
+            .resource::<InputServer>()
+            .events()
+            .filter_map(|event| match &event.content {
+                InputContent::Key(e) if e.pressed => Some(e.key_code),
+                _ => None,
+            })
+            .collect();
+
+        for key_code in pressed {
+            match key_code {
+                KeyCode::F11 => app.toggle_fullscreen(),
+                KeyCode::KeyV => {
+                    let vsync = app.is_vsync();
+                    app.set_vsync(!vsync);
+                }
+                _ => {}
+            }
+        }
+    });
+
     app.run();
 }
@@ -1,5 +1,5 @@
 use eureka::animation::{AnimationClip, AnimationCurve, AnimationPlayer, Keyframe};
-use eureka::core::App;
+use eureka::core::{App, AppConfig};
 use eureka::math::transform::Transform2d;
 use eureka::scene::{
     ActiveCamera, CTransform2d, Camera2dComponent, GlobalTransform, LabelComponent, Name,
@@ -7,7 +7,7 @@ use eureka::scene::{
 use glam::Vec2;
 
 fn main() {
-    let mut app = App::new();
+    let mut app = App::new(AppConfig::default());
 
     app.setup(|app| {
         let world = &mut app.world;
@@ -15,7 +15,7 @@ fn main() {
         // 1. Add a 2D camera
         world.ecs.spawn((
             Name("MainCamera2D".into()),
-            CTransform2d(Transform2d::default()),
+            CTransform2d::new(Transform2d::default()),
             GlobalTransform::default(),
             Camera2dComponent::default(),
             ActiveCamera,
@@ -25,7 +25,7 @@ fn main() {
         let label_id = world.ecs.spawn((
             Name("AnimatedLabel".into()),
             LabelComponent::new("Animated Text!"),
-            CTransform2d(Transform2d {
+            CTransform2d::new(Transform2d {
                 position: Vec2::new(640.0, 360.0),
                 ..Transform2d::default()
             }),
@@ -69,7 +69,7 @@ fn main() {
         world.ecs.spawn((
             Name("Instructions".into()),
             LabelComponent::new("Animation Demo: Text bounces using ECS systems"),
-            CTransform2d(Transform2d {
+            CTransform2d::new(Transform2d {
                 position: Vec2::new(10.0, 30.0),
                 ..Transform2d::default()
             }),
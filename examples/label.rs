@@ -1,4 +1,5 @@
-use eureka::core::App;
+use eureka::asset::AssetServer;
+use eureka::core::{App, AppConfig};
 use eureka::math::transform::Transform2d;
 use eureka::scene::{
     ActiveCamera, CTransform2d, Camera2dComponent, GlobalTransform, LabelComponent, Name,
@@ -6,14 +7,14 @@ use eureka::scene::{
 use glam::Vec2;
 
 fn main() {
-    let mut app = App::new();
+    let mut app = App::new(AppConfig::default());
 
     app.setup(|app| {
-        let singletons = app.singletons.as_ref().unwrap();
         let world = &mut app.world;
 
-        let font_path = singletons
-            .asset_server
+        let font_path = world
+            .resources
+            .resource::<AssetServer>()
             .asset_dir
             .join("fonts/Arial Unicode MS Font.ttf")
             .into_os_string()
@@ -23,7 +24,7 @@ fn main() {
         // 1. 2D 摄像机
         world.ecs.spawn((
             Name("UICamera".into()),
-            CTransform2d(Transform2d::default()),
+            CTransform2d::new(Transform2d::default()),
             GlobalTransform::default(),
             Camera2dComponent::default(),
             ActiveCamera,
@@ -48,7 +49,7 @@ fn main() {
         world.ecs.spawn((
             Name("Label1".into()),
             label1,
-            CTransform2d(Transform2d {
+            CTransform2d::new(Transform2d {
                 position: Vec2::new(100.0, 100.0),
                 ..Transform2d::default()
             }),
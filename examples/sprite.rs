@@ -1,4 +1,5 @@
-use eureka::core::App;
+use eureka::asset::AssetServer;
+use eureka::core::{App, AppConfig, Time};
 use eureka::math::transform::Transform2d;
 use eureka::scene::{
     ActiveCamera, CTransform2d, Camera2dComponent, GlobalTransform, Name, Parent, Size,
@@ -10,22 +11,16 @@ use glam::Vec2;
 struct RotatingLogic;
 
 fn main() {
-    let mut app = App::new();
+    let mut app = App::new(AppConfig::default());
 
     app.setup(|app| {
         let world = &mut app.world;
-        let asset_dir = app
-            .singletons
-            .as_ref()
-            .unwrap()
-            .asset_server
-            .asset_dir
-            .clone();
+        let asset_dir = world.resources.resource::<AssetServer>().asset_dir.clone();
 
         // 1. 2D 摄像机
         world.ecs.spawn((
             Name("MainCamera2D".into()),
-            CTransform2d(Transform2d::default()),
+            CTransform2d::new(Transform2d::default()),
             GlobalTransform::default(),
             Camera2dComponent::default(),
             ActiveCamera,
@@ -35,7 +30,7 @@ fn main() {
         // 父节点 - 绕中心旋转的大圆
         let parent = world.ecs.spawn((
             Name("Parent_Circle".into()),
-            CTransform2d(Transform2d {
+            CTransform2d::new(Transform2d {
                 position: Vec2::new(640.0, 360.0), // 屏幕中心
                 scale: Vec2::splat(3.0),
                 ..Transform2d::default()
@@ -50,7 +45,7 @@ fn main() {
         // 子节点1 - 跟随父节点旋转的小圆 (在父节点内部)
         world.ecs.spawn((
             Name("Child_Circle_1".into()),
-            CTransform2d(Transform2d {
+            CTransform2d::new(Transform2d {
                 position: Vec2::new(150.0, 0.0), // 相对于父节点
                 scale: Vec2::splat(0.8),
                 ..Transform2d::default()
@@ -65,7 +60,7 @@ fn main() {
         // 子节点2 - 跟随父节点旋转的小圆
         world.ecs.spawn((
             Name("Child_Circle_2".into()),
-            CTransform2d(Transform2d {
+            CTransform2d::new(Transform2d {
                 position: Vec2::new(-150.0, 0.0), // 相对于父节点
                 scale: Vec2::splat(0.8),
                 ..Transform2d::default()
@@ -80,7 +75,7 @@ fn main() {
         // 3. 另一组父子关系 - 上下摆动的小精灵
         let swing_parent = world.ecs.spawn((
             Name("Swing_Parent".into()),
-            CTransform2d(Transform2d {
+            CTransform2d::new(Transform2d {
                 position: Vec2::new(200.0, 100.0),
                 scale: Vec2::splat(1.5),
                 ..Transform2d::default()
@@ -94,7 +89,7 @@ fn main() {
         // 挂在摆动父节点上的子节点
         world.ecs.spawn((
             Name("Swing_Child".into()),
-            CTransform2d(Transform2d {
+            CTransform2d::new(Transform2d {
                 position: Vec2::new(0.0, 120.0), // 相对于父节点，向下偏移
                 scale: Vec2::splat(0.6),
                 ..Transform2d::default()
@@ -110,7 +105,7 @@ fn main() {
     // 添加自定义更新逻辑
     app.add_update(|app, dt| {
         let world = &mut app.world;
-        let time = app.singletons.as_ref().unwrap().time.get_delta() as f32;
+        let time = world.resources.resource::<Time>().get_delta() as f32;
 
         // 1. 让标记 RotatingLogic 的精灵旋转
         for transform in world
@@ -118,7 +113,7 @@ fn main() {
             .query_mut::<&mut CTransform2d>()
             .with::<&RotatingLogic>()
         {
-            transform.0.rotation += dt;
+            transform.rotation += dt;
         }
 
         // 2. 让摆动父节点上下移动
@@ -142,7 +137,7 @@ fn main() {
 
         if let Some(id) = swing_parent_id {
             if let Ok(mut transform) = world.ecs.get::<&mut CTransform2d>(id) {
-                transform.0.position.y = 100.0 + (time * 2.0).sin() * 80.0;
+                transform.position.y = 100.0 + (time * 2.0).sin() * 80.0;
             }
         }
     });
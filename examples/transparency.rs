@@ -1,4 +1,5 @@
-use eureka::core::App;
+use eureka::core::{App, AppConfig};
+use eureka::render::RenderContext;
 use eureka::math::transform::{Transform2d, Transform3d};
 use eureka::scene::{
     ActiveCamera, CTransform2d, CTransform3d,
@@ -10,11 +11,11 @@ use glam::{Quat, Vec2, Vec3};
 use winit::keyboard::KeyCode;
 
 fn main() {
-    let mut app = App::new();
+    let mut app = App::new(AppConfig::default());
 
     app.setup(|app| {
-        let singletons = app.singletons.as_mut().unwrap();
         let world = &mut app.world;
+        let render_context = world.resources.resource::<RenderContext>();
         let render_world = app.render_world.as_ref().unwrap();
 
         // 资源缓存的快捷引用
@@ -29,7 +30,7 @@ fn main() {
 
         world.ecs.spawn((
             Name("MainCamera".into()),
-            CTransform3d(Transform3d {
+            CTransform3d::new(Transform3d {
                 position: Vec3::new(-5.0, 3.0, 0.0),
                 ..Transform3d::default()
             }),
@@ -42,7 +43,7 @@ fn main() {
         // 2. UI 覆盖层
         world.ecs.spawn((
             Name("UICamera".into()),
-            CTransform2d(Transform2d::default()),
+            CTransform2d::new(Transform2d::default()),
             GlobalTransform::default(),
             eureka::scene::d2::camera2d::Camera2dComponent::default(),
             ActiveCamera,
@@ -51,7 +52,7 @@ fn main() {
         world.ecs.spawn((
             Name("Settings".into()),
             LabelComponent::new("Testing Transparency: Opaque vs Transparent Primitives"),
-            CTransform2d(Transform2d {
+            CTransform2d::new(Transform2d {
                 position: Vec2::new(20.0, 20.0),
                 ..Transform2d::default()
             }),
@@ -63,7 +64,7 @@ fn main() {
 
         world.ecs.spawn((
             Name("DirLight".into()),
-            CTransform3d(Transform3d {
+            CTransform3d::new(Transform3d {
                 rotation: rot_x * rot_y,
                 ..Transform3d::default()
             }),
@@ -79,7 +80,7 @@ fn main() {
         // A. 地面 (不透明)
         let ground_model = Model::from_primitive(
             MeshPrimitive::Plane { size: 20.0 },
-            &singletons.render_context,
+            &render_context,
             &mut texture_cache,
             &mut material_cache,
             &mut mesh_cache,
@@ -87,7 +88,7 @@ fn main() {
         );
         world.ecs.spawn((
             Name("Ground".into()),
-            CTransform3d(Transform3d::default()),
+            CTransform3d::new(Transform3d::default()),
             GlobalTransform::default(),
             ground_model,
         ));
@@ -95,7 +96,7 @@ fn main() {
         // B. 不透明立方体 (红色)
         let mut opaque_cube = Model::from_primitive(
             MeshPrimitive::Cube,
-            &singletons.render_context,
+            &render_context,
             &mut texture_cache,
             &mut material_cache,
             &mut mesh_cache,
@@ -109,7 +110,7 @@ fn main() {
         }
         world.ecs.spawn((
             Name("OpaqueCube".into()),
-            CTransform3d(Transform3d {
+            CTransform3d::new(Transform3d {
                 position: Vec3::new(0.0, 0.5, 0.0),
                 ..Transform3d::default()
             }),
@@ -120,7 +121,7 @@ fn main() {
         // C. 透明球体 (蓝色，半透明)
         let mut transparent_sphere = Model::from_primitive(
             MeshPrimitive::Sphere { radius: 0.8, subdivisions: 32 },
-            &singletons.render_context,
+            &render_context,
             &mut texture_cache,
             &mut material_cache,
             &mut mesh_cache,
@@ -137,7 +138,7 @@ fn main() {
         }
         world.ecs.spawn((
             Name("TransparentSphere".into()),
-            CTransform3d(Transform3d {
+            CTransform3d::new(Transform3d {
                 position: Vec3::new(0.0, 1.0, 2.0), // 放在红色方块前面
                 ..Transform3d::default()
             }),
@@ -148,7 +149,7 @@ fn main() {
         // D. 另一个透明立方体 (绿色，放在更后面，测试深度排序)
         let mut transparent_cube = Model::from_primitive(
             MeshPrimitive::Cube,
-            &singletons.render_context,
+            &render_context,
             &mut texture_cache,
             &mut material_cache,
             &mut mesh_cache,
@@ -163,7 +164,7 @@ fn main() {
         }
         world.ecs.spawn((
             Name("TransparentCube".into()),
-            CTransform3d(Transform3d {
+            CTransform3d::new(Transform3d {
                 position: Vec3::new(0.0, 0.5, -2.0),
                 ..Transform3d::default()
             }),
@@ -1,16 +1,29 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 
 use crate::animation::curve::AnimationCurve;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnimationClip {
     pub name: String,
     pub duration: f32,
     pub loop_count: i32,
+    #[serde(serialize_with = "serialize_sorted")]
     pub curves: HashMap<String, AnimationCurve<f32>>,
 }
 
+/// 按名称排序，保存结果稳定
+fn serialize_sorted<S: Serializer>(
+    curves: &HashMap<String, AnimationCurve<f32>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    curves
+        .iter()
+        .collect::<BTreeMap<_, _>>()
+        .serialize(serializer)
+}
+
 impl AnimationClip {
     pub fn new(name: String) -> Self {
         Self {
@@ -1,13 +1,14 @@
 use glam::{Quat, Vec2, Vec3, Vec4};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Interpolation {
     Linear,
     Smooth,
     Step,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Keyframe<T> {
     pub time: f32,
     pub value: T,
@@ -29,7 +30,7 @@ impl<T> Keyframe<T> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnimationCurve<T> {
     pub keyframes: Vec<Keyframe<T>>,
 }
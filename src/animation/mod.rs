@@ -5,5 +5,5 @@ pub mod property;
 
 pub use clip::AnimationClip;
 pub use curve::{AnimationCurve, Interpolation, Keyframe};
-pub use player::AnimationPlayer;
+pub use player::{AnimationFinished, AnimationPlayer, PlayState};
 pub use property::{PropertyPath, PropertyProvider, PropertyValue};
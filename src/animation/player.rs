@@ -3,9 +3,12 @@ use std::sync::Arc;
 
 use crate::animation::property::PropertyChange;
 use crate::animation::{AnimationClip, PropertyPath, PropertyValue};
+use crate::scene::SceneEntities;
+use anyhow::{Context, Result};
 use hecs::Entity;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PlayState {
     Playing,
     Paused,
@@ -27,6 +30,14 @@ pub struct AnimationBinding {
     pub weight: f32,
 }
 
+/// Sent when a non-looping clip of an [`AnimationPlayer`] reaches its end.
+#[derive(Clone, Debug)]
+pub struct AnimationFinished {
+    /// The entity with the player.
+    pub entity: Entity,
+    pub clip: String,
+}
+
 pub struct AnimationPlayer {
     clips: HashMap<String, Arc<AnimationClip>>,
     active_clip: Option<String>,
@@ -102,6 +113,14 @@ impl AnimationPlayer {
         }
     }
 
+    pub fn play_state(&self) -> PlayState {
+        self.play_state
+    }
+
+    pub fn active_clip(&self) -> Option<&str> {
+        self.active_clip.as_deref()
+    }
+
     pub fn pause(&mut self) {
         self.play_state = PlayState::Paused;
     }
@@ -227,3 +246,87 @@ impl AnimationPlayer {
         self.apply_animation();
     }
 }
+
+/// Saved form of an [`AnimationPlayer`]: its clips, bindings and what was playing. The active
+/// clip restarts from the beginning on load.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct AnimationPlayerScene {
+    clips: Vec<AnimationClip>,
+    active_clip: Option<String>,
+    play_state: PlayState,
+    speed: f32,
+    loop_count: i32,
+    bindings: Vec<AnimationBindingScene>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AnimationBindingScene {
+    /// Scene id of the target entity.
+    target: u32,
+    property: String,
+    curve: String,
+    weight: f32,
+}
+
+impl AnimationPlayer {
+    /// Bindings to entities outside the scene are left out.
+    pub(crate) fn to_scene(&self, entities: &SceneEntities) -> AnimationPlayerScene {
+        let mut clips: Vec<AnimationClip> = self.clips.values().map(|c| (**c).clone()).collect();
+        clips.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let bindings = self
+            .bindings
+            .iter()
+            .filter_map(|b| {
+                Some(AnimationBindingScene {
+                    target: entities.id(b.target_entity)?,
+                    property: b.property_path.path().to_string(),
+                    curve: b.curve_name.clone(),
+                    weight: b.weight,
+                })
+            })
+            .collect();
+
+        AnimationPlayerScene {
+            clips,
+            active_clip: self.active_clip.clone(),
+            play_state: self.play_state,
+            speed: self.speed,
+            loop_count: self.loop_count,
+            bindings,
+        }
+    }
+
+    pub(crate) fn from_scene(
+        scene: AnimationPlayerScene,
+        entities: &SceneEntities,
+    ) -> Result<Self> {
+        let mut player = Self::new();
+        for clip in scene.clips {
+            player.add_clip(clip);
+        }
+
+        for binding in scene.bindings {
+            let target = entities
+                .entity(binding.target)
+                .with_context(|| format!("Animation bound to unknown entity {}", binding.target))?;
+            player.bindings.push(AnimationBinding {
+                target_entity: target,
+                property_path: PropertyPath::parse(&binding.property),
+                curve_name: binding.curve,
+                weight: binding.weight,
+            });
+        }
+
+        player.speed = scene.speed;
+        player.loop_count = scene.loop_count;
+        if let Some(clip) = &scene.active_clip {
+            if scene.play_state != PlayState::Stopped {
+                player.play(clip, scene.loop_count);
+                player.play_state = scene.play_state;
+            }
+        }
+
+        Ok(player)
+    }
+}
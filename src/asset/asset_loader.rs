@@ -0,0 +1,13 @@
+use std::any::Any;
+use std::path::Path;
+
+/// Loader for asset types the engine doesn't know about, registered on the [`crate::asset::AssetServer`].
+///
+/// Loading runs on the asset thread pool; the result is taken back on the logic thread with
+/// [`crate::asset::AssetServer::take_asset`].
+pub trait AssetLoader: Send + Sync + 'static {
+    /// File extensions handled by this loader, without the leading dot.
+    fn extensions(&self) -> &[&str];
+
+    fn load(&self, path: &Path) -> anyhow::Result<Box<dyn Any + Send>>;
+}
@@ -1,18 +1,30 @@
+use crate::asset::asset_loader::AssetLoader;
 use crate::asset::font_loader::find_system_font;
 use crate::render::{RawTextureData, Texture};
 use crate::scene::d3::{Model, RawModelData};
 use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::any::Any;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
 
 pub enum AssetMessage {
     Model(PathBuf, RawModelData),
     Texture(PathBuf, RawTextureData),
     Font(PathBuf, Vec<u8>),
+    /// Asset produced by a registered [`AssetLoader`].
+    Custom(PathBuf, Box<dyn Any + Send>),
     Error(PathBuf, String),
 }
 
+/// Sent when an asset finished loading in the background, see [`crate::core::Events`].
+#[derive(Clone, Debug)]
+pub enum AssetEvent {
+    Loaded(PathBuf),
+    Failed { path: PathBuf, error: String },
+}
+
 pub struct AssetServer {
     pub asset_dir: PathBuf,
     pool: ThreadPool,
@@ -24,9 +36,15 @@ pub struct AssetServer {
     loaded_raw_models: HashMap<PathBuf, RawModelData>,
     loaded_raw_textures: HashMap<PathBuf, RawTextureData>,
     loaded_raw_fonts: HashMap<PathBuf, Vec<u8>>,
+    loaded_custom_assets: HashMap<PathBuf, Box<dyn Any + Send>>,
+
+    loaders: Vec<Arc<dyn AssetLoader>>,
 
     loading_paths: HashMap<PathBuf, bool>,
     failed_paths: HashMap<PathBuf, String>,
+
+    /// Collected by [`AssetServer::update`] until the asset system sends them.
+    events: Vec<AssetEvent>,
 }
 
 impl AssetServer {
@@ -47,8 +65,11 @@ impl AssetServer {
             loaded_raw_models: HashMap::new(),
             loaded_raw_textures: HashMap::new(),
             loaded_raw_fonts: HashMap::new(),
+            loaded_custom_assets: HashMap::new(),
+            loaders: Vec::new(),
             loading_paths: HashMap::new(),
             failed_paths: HashMap::new(),
+            events: Vec::new(),
         }
     }
 
@@ -72,6 +93,27 @@ impl AssetServer {
         self.loaded_raw_fonts.remove(path.as_ref())
     }
 
+    /// Take a custom asset loaded by a registered [`AssetLoader`].
+    /// Returns `None` if it hasn't finished loading or isn't of type `T`.
+    pub fn take_asset<T: 'static, P: AsRef<Path>>(&mut self, path: P) -> Option<T> {
+        let path = path.as_ref();
+        let asset = self.loaded_custom_assets.remove(path)?;
+
+        match asset.downcast::<T>() {
+            Ok(asset) => Some(*asset),
+            Err(asset) => {
+                self.loaded_custom_assets.insert(path.to_path_buf(), asset);
+                None
+            }
+        }
+    }
+
+    /// Register a loader for custom asset types. Later loaders take precedence for the same
+    /// extension.
+    pub fn add_loader(&mut self, loader: Arc<dyn AssetLoader>) {
+        self.loaders.push(loader);
+    }
+
     pub fn get_fonts(&self) -> &HashMap<PathBuf, Vec<u8>> {
         &self.loaded_raw_fonts
     }
@@ -100,6 +142,53 @@ impl AssetServer {
         });
     }
 
+    /// Load a custom asset with the loader registered for its file extension.
+    pub fn request_asset<P: AsRef<Path>>(&mut self, path: P) {
+        let path_buf = path.as_ref().to_path_buf();
+        if self.loading_paths.contains_key(&path_buf)
+            || self.loaded_custom_assets.contains_key(&path_buf)
+            || self.failed_paths.contains_key(&path_buf)
+        {
+            return;
+        }
+
+        let extension = path_buf
+            .extension()
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let Some(loader) = self
+            .loaders
+            .iter()
+            .rev()
+            .find(|l| {
+                l.extensions()
+                    .iter()
+                    .any(|e| e.eq_ignore_ascii_case(&extension))
+            })
+            .cloned()
+        else {
+            let err_msg = format!("No asset loader registered for {:?}", path_buf);
+            log::error!("{}", err_msg);
+            self.failed_paths.insert(path_buf, err_msg);
+            return;
+        };
+
+        self.loading_paths.insert(path_buf.clone(), true);
+        let tx = self.tx.clone();
+
+        self.pool.spawn(move || match loader.load(&path_buf) {
+            Ok(asset) => {
+                let _ = tx.send(AssetMessage::Custom(path_buf, asset));
+            }
+            Err(e) => {
+                let err_msg = format!("Failed to load asset: {}", e);
+                log::error!("{}", err_msg);
+                let _ = tx.send(AssetMessage::Error(path_buf, err_msg));
+            }
+        });
+    }
+
     pub fn request_texture<P: AsRef<Path>>(&mut self, path: P) {
         let path_buf = path.as_ref().to_path_buf();
         if self.loading_paths.contains_key(&path_buf)
@@ -166,21 +255,37 @@ impl AssetServer {
             match msg {
                 AssetMessage::Model(path, raw) => {
                     self.loading_paths.remove(&path);
+                    self.events.push(AssetEvent::Loaded(path.clone()));
                     self.loaded_raw_models.insert(path, raw);
                 }
                 AssetMessage::Texture(path, raw) => {
                     self.loading_paths.remove(&path);
+                    self.events.push(AssetEvent::Loaded(path.clone()));
                     self.loaded_raw_textures.insert(path, raw);
                 }
                 AssetMessage::Font(path, buffer) => {
                     self.loading_paths.remove(&path);
+                    self.events.push(AssetEvent::Loaded(path.clone()));
                     self.loaded_raw_fonts.insert(path, buffer);
                 }
+                AssetMessage::Custom(path, asset) => {
+                    self.loading_paths.remove(&path);
+                    self.events.push(AssetEvent::Loaded(path.clone()));
+                    self.loaded_custom_assets.insert(path, asset);
+                }
                 AssetMessage::Error(path, err) => {
                     self.loading_paths.remove(&path);
+                    self.events.push(AssetEvent::Failed {
+                        path: path.clone(),
+                        error: err.clone(),
+                    });
                     self.failed_paths.insert(path, err);
                 }
             }
         }
     }
+
+    pub(crate) fn take_events(&mut self) -> Vec<AssetEvent> {
+        std::mem::take(&mut self.events)
+    }
 }
@@ -1,5 +1,7 @@
+pub(crate) mod asset_loader;
 pub(crate) mod asset_server;
 pub(crate) mod font_loader;
 
+pub use asset_loader::*;
 pub use asset_server::*;
 pub use font_loader::*;
@@ -1,3 +1,5 @@
+use std::any::TypeId;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::core::time::Time;
@@ -6,71 +8,300 @@ use winit::{
     application::ApplicationHandler,
     event::*,
     event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
-    window::{Window, WindowAttributes, WindowId},
+    window::{Fullscreen, Window, WindowAttributes},
 };
 
 // Import local crates.
-use crate::asset::AssetServer;
-use crate::core::singleton::Singletons;
-use crate::render::render_world::{RenderCommand, RenderWorld};
-use crate::render::RenderContext;
-use crate::scene::World;
+use crate::asset::{AssetLoader, AssetServer};
+use crate::core::{
+    apply_state_transition, AppConfig, DefaultPlugins, Plugin, State, StateHooks, States, Track,
+    WindowMode,
+};
+use crate::render::render_graph::RenderGraph;
+use crate::render::render_world::RenderGraphCallback;
+use crate::render::render_world::{RenderCommand, RenderWorld, WindowSurface};
+use crate::render::{InitError, RenderContext, RenderFeatures, RenderTier};
+use crate::scene::{Resources, SceneRegistry, Stage, SystemEntry, World};
 use crate::text::FontServer;
-use crate::window::InputServer;
+use crate::window::{InputRecording, InputServer, WindowId, WindowInfo, WindowResized, Windows};
+use glam::UVec2;
+
+/// Color format of the offscreen target used by headless apps.
+const HEADLESS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
 
-const INITIAL_WINDOW_WIDTH: u32 = 1280;
-const INITIAL_WINDOW_HEIGHT: u32 = 720;
+type ExitCallback = Box<dyn FnOnce(&mut App)>;
 
 pub struct App {
+    /// The primary window.
     window: Option<Arc<Window>>,
+    /// Windows spawned through [`Windows::spawn`].
+    secondary_windows: HashMap<winit::window::WindowId, (WindowId, Arc<Window>)>,
     window_size: LogicalSize<u32>,
     scale_factor: f64,
     pub world: World,
     pub render_world: Option<RenderWorld>,
-    pub singletons: Option<Singletons>,
     initialized: bool,
+    config: AppConfig,
+    /// Headless apps have no window or event loop and render into an offscreen texture.
+    headless: bool,
+    /// Mode used by [`App::toggle_fullscreen`].
+    fullscreen_mode: WindowMode,
+    /// Only use the fallback (software) adapter. Gives reproducible output across machines.
+    force_fallback_adapter: bool,
+    /// Set if initialization failed inside the event loop, returned by [`App::try_run`].
+    init_error: Option<InitError>,
     /// We keep the event loop in an option to take it when running.
     event_loop: Option<EventLoop<()>>,
     /// Callback for user setup logic after initialization.
     setup_callback: Option<Box<dyn FnOnce(&mut App)>>,
     /// Callback for user-defined update logic, called every frame after the world update.
     update_callbacks: Vec<Box<dyn FnMut(&mut App, f32)>>,
+    /// Callbacks run once before the render thread is shut down.
+    exit_callbacks: Vec<ExitCallback>,
+    /// Callbacks modifying the standard render graph, applied when the render thread starts.
+    render_graph_callbacks: Vec<RenderGraphCallback>,
+    /// Names of the plugins added so far, with the type that was built under each name.
+    plugins: HashMap<String, TypeId>,
+    /// Asset loaders registered before initialization.
+    pending_asset_loaders: Vec<Arc<dyn AssetLoader>>,
+    /// Applies the queued transition of each state type added with [`App::add_state`].
+    state_transitions: Vec<fn(&mut World, &mut RenderWorld)>,
     /// 逻辑更新累加器，用于固定步长更新
     accumulator: f64,
     last_tick_time: std::time::Instant,
 }
 
 impl App {
-    pub fn new() -> Self {
+    pub fn new(config: AppConfig) -> Self {
+        Self::with_plugins(config, DefaultPlugins::new())
+    }
+
+    /// Like [`App::new`], with `plugins` instead of the [`DefaultPlugins`].
+    pub fn with_plugins<P: Plugin>(config: AppConfig, plugins: P) -> Self {
+        Self::init_logger();
+
+        // New winit event loop
+        let event_loop = EventLoop::new().unwrap();
+
+        let window_size = LogicalSize::new(config.width.max(1), config.height.max(1));
+
+        let mut app = Self::with_event_loop(Some(event_loop), window_size);
+        app.set_config(config);
+        app.add_plugin(plugins);
+        app
+    }
+
+    /// Create an app without a window. Rendering goes to an offscreen texture of the given size
+    /// and the fixed-step loop is driven manually through [`App::step`].
+    ///
+    /// This works on machines without a display (CI, batch jobs, render farms), and will fall back
+    /// to a software adapter if no hardware adapter is available.
+    pub fn new_headless(width: u32, height: u32) -> Self {
+        Self::new_headless_with_plugins(width, height, DefaultPlugins::new())
+    }
+
+    /// Like [`App::new_headless`], with `plugins` instead of the [`DefaultPlugins`].
+    pub fn new_headless_with_plugins<P: Plugin>(width: u32, height: u32, plugins: P) -> Self {
+        Self::init_logger();
+
+        let mut app = Self::with_event_loop(None, LogicalSize::new(width.max(1), height.max(1)));
+        app.headless = true;
+        app.add_plugin(plugins);
+        app
+    }
+
+    fn init_logger() {
         // Config logger
         let env = env_logger::Env::default()
             .filter_or("EUREKA_LOG_LEVEL", "info")
             .write_style_or("EUREKA_LOG_STYLE", "always");
         let _ = env_logger::try_init_from_env(env);
+    }
 
-        // New winit event loop
-        let event_loop = EventLoop::new().unwrap();
-
-        let window_size = LogicalSize::new(INITIAL_WINDOW_WIDTH, INITIAL_WINDOW_HEIGHT);
-
+    fn with_event_loop(event_loop: Option<EventLoop<()>>, window_size: LogicalSize<u32>) -> Self {
         let world = World::new();
 
         Self {
             window: None,
+            secondary_windows: HashMap::new(),
             window_size,
             scale_factor: 1.0,
             world,
             render_world: None,
-            singletons: None,
             initialized: false,
-            event_loop: Some(event_loop),
+            config: AppConfig::default(),
+            headless: false,
+            fullscreen_mode: WindowMode::BorderlessFullscreen,
+            force_fallback_adapter: false,
+            init_error: None,
+            event_loop,
             setup_callback: None,
             update_callbacks: Vec::new(),
+            exit_callbacks: Vec::new(),
+            render_graph_callbacks: Vec::new(),
+            plugins: HashMap::new(),
+            pending_asset_loaders: Vec::new(),
+            state_transitions: Vec::new(),
             accumulator: 0.0,
             last_tick_time: std::time::Instant::now(),
         }
     }
 
+    pub fn config(&self) -> &AppConfig {
+        &self.config
+    }
+
+    /// Must be called before initialization. Tick rate and catch-up can be changed later
+    /// through [`Time`], present mode and window mode through [`App::set_present_mode`] and
+    /// [`App::set_window_mode`]. Headless apps keep the size they were created with.
+    pub fn set_config(&mut self, config: AppConfig) {
+        if self.initialized {
+            log::warn!("App config can't be changed after initialization");
+            return;
+        }
+        if !self.headless {
+            self.window_size = LogicalSize::new(config.width.max(1), config.height.max(1));
+        }
+        if config.window_mode != WindowMode::Windowed {
+            self.fullscreen_mode = config.window_mode;
+        }
+        self.config = config;
+    }
+
+    pub fn set_window_mode(&mut self, mode: WindowMode) {
+        self.config.window_mode = mode;
+        if mode != WindowMode::Windowed {
+            self.fullscreen_mode = mode;
+        }
+
+        // 窗口尺寸变化后会收到 Resized 事件，由它重新配置 surface
+        if let Some(window) = &self.window {
+            window.set_fullscreen(Self::fullscreen(mode, window.current_monitor()));
+        }
+    }
+
+    /// Switch between windowed and the configured fullscreen mode (borderless if windowed
+    /// was configured).
+    pub fn toggle_fullscreen(&mut self) {
+        let mode = if self.config.window_mode == WindowMode::Windowed {
+            self.fullscreen_mode
+        } else {
+            WindowMode::Windowed
+        };
+        self.set_window_mode(mode);
+    }
+
+    /// Change the present mode at runtime. Unsupported modes fall back to the lowest latency
+    /// mode available.
+    pub fn set_present_mode(&mut self, present_mode: Option<wgpu::PresentMode>) {
+        self.config.present_mode = present_mode;
+
+        let (Some(mut render_context), Some(render_world)) = (
+            self.world.resources.get_mut::<RenderContext>(),
+            &self.render_world,
+        ) else {
+            return;
+        };
+
+        let present_mode =
+            Self::choose_present_mode(present_mode, &render_context.supported_present_modes);
+        if render_context.surface_config.present_mode == present_mode {
+            return;
+        }
+
+        render_context.surface_config.present_mode = present_mode;
+        let _ = render_world
+            .sender
+            .send(RenderCommand::SetPresentMode(present_mode));
+
+        log::info!("Present mode changed to {:?}", present_mode);
+    }
+
+    /// Fifo when enabled, otherwise the lowest latency mode available.
+    pub fn set_vsync(&mut self, enabled: bool) {
+        let present_mode = enabled.then_some(wgpu::PresentMode::Fifo);
+        self.set_present_mode(present_mode);
+    }
+
+    pub fn is_vsync(&self) -> bool {
+        self.world
+            .resources
+            .get::<RenderContext>()
+            .is_some_and(|render_context| {
+                matches!(
+                    render_context.surface_config.present_mode,
+                    wgpu::PresentMode::Fifo | wgpu::PresentMode::AutoVsync
+                )
+            })
+    }
+
+    fn choose_present_mode(
+        preferred: Option<wgpu::PresentMode>,
+        supported: &[wgpu::PresentMode],
+    ) -> wgpu::PresentMode {
+        if let Some(preferred) = preferred {
+            // Auto 模式总是可用
+            if supported.contains(&preferred)
+                || matches!(
+                    preferred,
+                    wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync
+                )
+            {
+                return preferred;
+            }
+            log::warn!("Present mode {:?} not supported, falling back", preferred);
+        }
+
+        // 为了看到真实的 release 性能，我们优先尝试使用 Mailbox 或 Immediate (不锁帧)
+        if supported.contains(&wgpu::PresentMode::Mailbox) {
+            wgpu::PresentMode::Mailbox
+        } else if supported.contains(&wgpu::PresentMode::Immediate) {
+            wgpu::PresentMode::Immediate
+        } else {
+            wgpu::PresentMode::Fifo
+        }
+    }
+
+    fn fullscreen(
+        mode: WindowMode,
+        monitor: Option<winit::monitor::MonitorHandle>,
+    ) -> Option<Fullscreen> {
+        match mode {
+            WindowMode::Windowed => None,
+            WindowMode::BorderlessFullscreen => Some(Fullscreen::Borderless(monitor)),
+            WindowMode::Fullscreen => {
+                let video_mode = monitor.as_ref().and_then(|monitor| {
+                    monitor.video_modes().max_by_key(|mode| {
+                        let size = mode.size();
+                        (size.width * size.height, mode.refresh_rate_millihertz())
+                    })
+                });
+
+                match video_mode {
+                    Some(video_mode) => Some(Fullscreen::Exclusive(video_mode)),
+                    None => Some(Fullscreen::Borderless(monitor)),
+                }
+            }
+        }
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.headless
+    }
+
+    /// Only use the fallback (software) adapter. Must be called before initialization.
+    pub fn set_force_fallback_adapter(&mut self, force: bool) {
+        self.force_fallback_adapter = force;
+    }
+
+    /// Tier and optional GPU features the renderer runs with, once initialized.
+    pub fn render_features(&self) -> Option<RenderFeatures> {
+        self.world
+            .resources
+            .get::<RenderContext>()
+            .map(|render_context| render_context.features)
+    }
+
     pub fn setup<F>(&mut self, f: F)
     where
         F: FnOnce(&mut App) + 'static,
@@ -79,7 +310,7 @@ impl App {
     }
 
     /// Register a callback that will be invoked every frame after the world update.
-    /// The callback receives a mutable reference to the app and the delta time in seconds.
+    /// The callback receives a mutable reference to the app and the virtual delta time in seconds.
     pub fn add_update<F>(&mut self, f: F)
     where
         F: FnMut(&mut App, f32) + 'static,
@@ -87,162 +318,720 @@ impl App {
         self.update_callbacks.push(Box::new(f));
     }
 
+    /// Register a callback run when the app exits, while the render thread is still alive.
+    pub fn on_exit<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut App) + 'static,
+    {
+        self.exit_callbacks.push(Box::new(f));
+    }
+
+    /// Run the exit callbacks, then flush the GPU work and stop the render thread. Called when
+    /// the event loop exits; headless apps may call it when done.
+    pub fn shutdown(&mut self) {
+        self.save_input_recording();
+
+        for callback in std::mem::take(&mut self.exit_callbacks) {
+            callback(self);
+        }
+
+        if let Some(render_world) = &mut self.render_world {
+            render_world.shutdown();
+        }
+    }
+
+    /// The ticks have been written while recording, this only finishes the file.
+    fn save_input_recording(&mut self) {
+        let Some(path) = &self.config.record_input else {
+            return;
+        };
+        let Some(recording) = self
+            .world
+            .resources
+            .get_mut::<InputServer>()
+            .and_then(|mut input_server| input_server.stop_recording())
+        else {
+            return;
+        };
+
+        log::info!(
+            "Input of {} ticks recorded to {}",
+            recording.len(),
+            path.display()
+        );
+    }
+
+    /// Build a plugin. Adding a plugin with the same name again does nothing.
+    pub fn add_plugin<P: Plugin>(&mut self, plugin: P) -> &mut Self {
+        if self.plugins.contains_key(plugin.name()) {
+            log::warn!("Plugin {} already added", plugin.name());
+            return self;
+        }
+        self.plugins
+            .insert(plugin.name().to_string(), TypeId::of::<P>());
+
+        plugin.build(self);
+        self
+    }
+
+    /// Whether a plugin of type `P` was added, under its own or a custom [`Plugin::name`].
+    pub fn is_plugin_added<P: Plugin>(&self) -> bool {
+        self.plugins.values().any(|id| *id == TypeId::of::<P>())
+    }
+
+    /// Register a logic system under a unique label, see [`crate::scene::Schedule::add_system`].
+    ///
+    /// ```ignore
+    /// app.add_system(Stage::PostUpdate, "ik", solve_ik)
+    ///     .after(system_labels::ANIMATION)
+    ///     .before(system_labels::PROPAGATE_TRANSFORMS);
+    /// ```
+    pub fn add_system<F>(
+        &mut self,
+        stage: Stage,
+        label: impl Into<String>,
+        system: F,
+    ) -> &mut SystemEntry
+    where
+        F: FnMut(&mut hecs::World, &mut Resources, &mut RenderWorld, f32) + 'static,
+    {
+        self.world.schedule.add_system(stage, label, system)
+    }
+
+    /// Add a resource to the world, see [`Resources::insert`].
+    pub fn insert_resource<T: 'static>(&mut self, value: T) -> &mut Self {
+        self.world.resources.insert(value);
+        self
+    }
+
+    /// Register an event type, see [`Resources::add_event`].
+    pub fn add_event<T: 'static>(&mut self) -> &mut Self {
+        self.world.resources.add_event::<T>();
+        self
+    }
+
+    /// Add a state machine starting in `initial`, see [`States`]. Transitions are queued through
+    /// [`State::set`] and applied between fixed ticks.
+    pub fn add_state<S: States>(&mut self, initial: S) -> &mut Self {
+        if self.world.resources.contains::<State<S>>() {
+            log::warn!("State {} already added", std::any::type_name::<S>());
+            return self;
+        }
+
+        self.world.resources.insert(State::new(initial));
+        self.world.resources.insert(StateHooks::<S>::default());
+        self.state_transitions.push(apply_state_transition::<S>);
+        self
+    }
+
+    /// Queue a transition, see [`State::set`].
+    pub fn set_state<S: States>(&mut self, next: S) {
+        match self.world.resources.get_mut::<State<S>>() {
+            Some(mut state) => state.set(next),
+            None => log::warn!("State {} not added", std::any::type_name::<S>()),
+        }
+    }
+
+    /// Register a system run once when `state` is entered, including the initial state.
+    pub fn on_state_enter<S, F>(&mut self, state: S, system: F) -> &mut Self
+    where
+        S: States,
+        F: FnMut(&mut hecs::World, &mut Resources, &mut RenderWorld) + 'static,
+    {
+        match self.world.resources.get_mut::<StateHooks<S>>() {
+            Some(mut hooks) => hooks.on_enter.push((state, Box::new(system))),
+            None => log::warn!("State {} not added", std::any::type_name::<S>()),
+        }
+        self
+    }
+
+    /// Register a system run once when `state` is exited, before its [`StateScoped`] entities
+    /// are despawned.
+    pub fn on_state_exit<S, F>(&mut self, state: S, system: F) -> &mut Self
+    where
+        S: States,
+        F: FnMut(&mut hecs::World, &mut Resources, &mut RenderWorld) + 'static,
+    {
+        match self.world.resources.get_mut::<StateHooks<S>>() {
+            Some(mut hooks) => hooks.on_exit.push((state, Box::new(system))),
+            None => log::warn!("State {} not added", std::any::type_name::<S>()),
+        }
+        self
+    }
+
+    /// Like [`App::add_system`], but the system only runs while the app is in `state`.
+    pub fn add_state_system<S, F>(
+        &mut self,
+        stage: Stage,
+        state: S,
+        label: impl Into<String>,
+        mut system: F,
+    ) -> &mut SystemEntry
+    where
+        S: States,
+        F: FnMut(&mut hecs::World, &mut Resources, &mut RenderWorld, f32) + 'static,
+    {
+        self.add_system(stage, label, move |ecs, resources, render_world, dt| {
+            let active = resources
+                .get::<State<S>>()
+                .is_some_and(|current| *current.get() == state);
+            if active {
+                system(ecs, resources, render_world, dt);
+            }
+        })
+    }
+
+    /// Save and load `T` with scenes under `name`, see [`SceneRegistry`].
+    pub fn register_scene_component<T>(&mut self, name: &str) -> &mut Self
+    where
+        T: hecs::Component + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        match self.world.resources.get_mut::<SceneRegistry>() {
+            Some(mut registry) => {
+                registry.register::<T>(name);
+            }
+            None => log::warn!("No SceneRegistry resource, is ScenePlugin added?"),
+        }
+        self
+    }
+
+    pub fn add_asset_loader<L: AssetLoader>(&mut self, loader: L) -> &mut Self {
+        let loader = Arc::new(loader);
+        match self.world.resources.get_mut::<AssetServer>() {
+            Some(mut asset_server) => asset_server.add_loader(loader),
+            None => self.pending_asset_loaders.push(loader),
+        }
+        self
+    }
+
+    /// Register a callback that modifies the render graph (e.g. adding or removing nodes and
+    /// edges) once it has been set up with the standard nodes. It is applied to the graph of
+    /// every window. Must be called before initialization, i.e. from plugins or before
+    /// [`App::run`].
+    pub fn configure_render_graph<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&mut RenderGraph) + Send + 'static,
+    {
+        if self.initialized {
+            log::warn!("Render graph can't be configured after initialization");
+            return self;
+        }
+
+        self.render_graph_callbacks.push(Box::new(f));
+        self
+    }
+
     /// Creating some of the wgpu types requires async code.
+    ///
+    /// Without a window, no surface is created and the returned surface config only describes
+    /// the offscreen target.
     async fn init_render(
-        window: Arc<Window>,
-        render_cpu_time: Arc<std::sync::atomic::AtomicU64>,
-        gpu_time: Arc<std::sync::atomic::AtomicU64>,
-    ) -> (RenderContext, wgpu::Surface<'static>) {
+        window: Option<Arc<Window>>,
+        size: PhysicalSize<u32>,
+        config: &AppConfig,
+        force_fallback_adapter: bool,
+        time: &Time,
+    ) -> Result<(RenderContext, Option<wgpu::Surface<'static>>), InitError> {
         // Context for all other wgpu objects.
         let instance = wgpu::Instance::default();
 
         // Handle to a presentable surface.
-        let surface = instance.create_surface(window.clone()).unwrap();
+        let surface = window
+            .map(|window| instance.create_surface(window))
+            .transpose()
+            .map_err(InitError::CreateSurface)?;
 
         // Handle to a physical graphics and/or compute device.
-        let adapter = instance
+        let mut adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance, // 强制请求高性能显卡
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
+                power_preference: config.power_preference,
+                compatible_surface: surface.as_ref(),
+                force_fallback_adapter,
             })
-            .await
-            .expect("Failed to find an appropriate adapter!");
+            .await;
+
+        // 无显示环境（CI、渲染农场）下允许退回到软件适配器
+        if adapter.is_err() && surface.is_none() && !force_fallback_adapter {
+            adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: config.power_preference,
+                    compatible_surface: None,
+                    force_fallback_adapter: true,
+                })
+                .await;
+        }
 
-        let mut features = wgpu::Features::TEXTURE_BINDING_ARRAY
-            | wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING
-            | wgpu::Features::INDIRECT_FIRST_INSTANCE;
+        let adapter = adapter.map_err(InitError::NoAdapter)?;
 
-        if adapter.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
-            features |= wgpu::Features::TIMESTAMP_QUERY;
+        // 按适配器支持的特性选择档位，不支持 bindless 时退回到简化路径
+        let features = RenderFeatures::probe(&adapter);
+        let missing = features.wgpu_features() - adapter.features();
+        if !missing.is_empty() {
+            return Err(InitError::MissingFeatures(missing));
         }
 
-        if adapter
-            .features()
-            .contains(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS)
-        {
-            features |= wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS;
+        if features.tier != RenderTier::Full {
+            log::warn!("Bindless textures unsupported, using the reduced render tier");
         }
 
         // Use the adapter to create a device and a queue.
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
                 label: None,
-                required_features: features,
+                required_features: features.wgpu_features(),
                 // 移除了 STORAGE_RESOURCE_BINDING_ARRAY，因为目前没用到
-                required_limits: wgpu::Limits {
-                    max_binding_array_elements_per_shader_stage: 1024,
-                    ..Default::default()
-                },
+                required_limits: features.limits(),
                 memory_hints: Default::default(),
                 experimental_features: Default::default(),
                 trace: Default::default(),
             })
             .await
-            .expect("Failed to create device!");
-
-        // Get the window's inner size.
-        let size = window.inner_size();
+            .map_err(InitError::RequestDevice)?;
+
+        let Some(surface) = surface else {
+            // Offscreen target, copyable so that frames can be read back.
+            let surface_config = wgpu::SurfaceConfiguration {
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                format: HEADLESS_FORMAT,
+                width: size.width,
+                height: size.height,
+                present_mode: wgpu::PresentMode::Fifo,
+                desired_maximum_frame_latency: 2,
+                alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+                view_formats: vec![],
+            };
+
+            let frames_in_flight = config.frames_in_flight.unwrap_or(2).max(1);
+
+            return Ok((
+                RenderContext::new(
+                    surface_config,
+                    instance,
+                    adapter,
+                    device,
+                    queue,
+                    frames_in_flight,
+                    time,
+                ),
+                None,
+            ));
+        };
 
         let mut surface_config = surface
             .get_default_config(&adapter, size.width, size.height)
-            .expect("Surface unsupported by adapter!");
+            .ok_or(InitError::SurfaceUnsupported)?;
 
         let surface_capabilities = surface.get_capabilities(&adapter);
 
+        let present_mode =
+            Self::choose_present_mode(config.present_mode, &surface_capabilities.present_modes);
+
         // 如果无法从 capabilities 获取，通常根据显示模式推断：
         // Fifo (V-Sync) 通常需要 2 张图，Mailbox (Triple Buffering) 通常需要 3 张。
-        // 为了看到真实的 release 性能，我们优先尝试使用 Mailbox 或 Immediate (不锁帧)
-        let present_mode = if surface_capabilities
-            .present_modes
-            .contains(&wgpu::PresentMode::Mailbox)
-        {
-            wgpu::PresentMode::Mailbox
-        } else if surface_capabilities
-            .present_modes
-            .contains(&wgpu::PresentMode::Immediate)
-        {
-            wgpu::PresentMode::Immediate
-        } else {
-            wgpu::PresentMode::Fifo
-        };
-
-        let frames_in_flight = if present_mode == wgpu::PresentMode::Mailbox {
-            3
-        } else {
-            2
-        };
+        let frames_in_flight = config
+            .frames_in_flight
+            .unwrap_or(if present_mode == wgpu::PresentMode::Mailbox {
+                3
+            } else {
+                2
+            })
+            .max(1);
 
         surface_config.present_mode = present_mode;
 
+        // 允许从交换链纹理回读（截图）
+        if surface_capabilities
+            .usages
+            .contains(wgpu::TextureUsages::COPY_SRC)
+        {
+            surface_config.usage |= wgpu::TextureUsages::COPY_SRC;
+        }
+
         surface.configure(&device, &surface_config);
 
         // Create a render server.
-        (
-            RenderContext::new(
-                surface_config,
-                device,
-                queue,
-                frames_in_flight,
-                render_cpu_time,
-                gpu_time,
-            ),
-            surface,
-        )
+        let mut render_context = RenderContext::new(
+            surface_config,
+            instance,
+            adapter,
+            device,
+            queue,
+            frames_in_flight,
+            time,
+        );
+        render_context.supported_present_modes = surface_capabilities.present_modes;
+
+        Ok((render_context, Some(surface)))
     }
 
+    /// Run the event loop. Panics if the renderer can't be initialized, see [`App::try_run`].
     pub fn run(&mut self) {
+        if let Err(err) = self.try_run() {
+            panic!("Failed to initialize renderer: {}", err);
+        }
+    }
+
+    /// Like [`App::run`], but returns initialization errors (e.g. no GPU adapter) so that the
+    /// app can show a message instead.
+    pub fn try_run(&mut self) -> Result<(), InitError> {
+        assert!(!self.headless, "Headless apps are driven by App::step");
+
         let event_loop = self.event_loop.take().expect("Event loop already taken");
         event_loop.set_control_flow(ControlFlow::Poll);
         event_loop.run_app(self).expect("Failed to run event loop");
+
+        match self.init_error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Run `n_ticks` fixed logic ticks, rendering a frame after each one.
+    ///
+    /// Unlike the event loop, this doesn't depend on wall-clock time, so the same calls always
+    /// produce the same simulation. The first call initializes a headless app and panics if
+    /// that fails, see [`App::try_step`].
+    pub fn step(&mut self, n_ticks: u32) {
+        if let Err(err) = self.try_step(n_ticks) {
+            panic!("Failed to initialize renderer: {}", err);
+        }
+    }
+
+    /// Like [`App::step`], but returns initialization errors.
+    pub fn try_step(&mut self, n_ticks: u32) -> Result<(), InitError> {
+        if !self.initialized {
+            self.init(None)?;
+        }
+
+        let mut windows = self.world.resources.resource_mut::<Windows>();
+        if !windows.pending_spawns.is_empty() {
+            log::warn!("Headless apps can't open windows");
+            windows.pending_spawns.clear();
+        }
+        drop(windows);
+
+        for _ in 0..n_ticks {
+            let fixed_dt = self.world.resources.resource::<Time>().get_fixed_delta();
+
+            self.fixed_update(fixed_dt);
+
+            self.world
+                .resources
+                .resource_mut::<InputServer>()
+                .clear_events();
+            let mut time = self.world.resources.resource_mut::<Time>();
+            time.tick();
+            // 每个 tick 都渲染，不需要插值
+            time.set_interpolation_alpha(1.0);
+            drop(time);
+
+            self.render();
+        }
+
+        Ok(())
+    }
+
+    /// Set up the render thread and engine resources, then run the user setup callback.
+    fn init(&mut self, window: Option<Arc<Window>>) -> Result<(), InitError> {
+        if let Some(window) = &window {
+            self.scale_factor = window.scale_factor();
+        }
+        let size = match &window {
+            Some(window) => window.inner_size(),
+            None => self.window_size.to_physical(self.scale_factor),
+        };
+
+        let mut time = Time::new();
+        time.set_tick_rate(self.config.tick_rate);
+        time.set_max_catch_up(self.config.max_catch_up);
+
+        // App::init_render uses async code, so we're going to wait for it to finish.
+        let (render_context, surface) = pollster::block_on(Self::init_render(
+            window.clone(),
+            size,
+            &self.config,
+            self.force_fallback_adapter,
+            &time,
+        ))?;
+
+        let mut asset_server = AssetServer::new();
+        for loader in self.pending_asset_loaders.drain(..) {
+            asset_server.add_loader(loader);
+        }
+
+        let surface = window
+            .zip(surface)
+            .map(|(window, surface)| WindowSurface { window, surface });
+
+        let render_world = RenderWorld::new(
+            render_context.clone(),
+            surface,
+            std::mem::take(&mut self.render_graph_callbacks),
+            self.config.retain_asset_data,
+        );
+        let font_server = FontServer::new(&mut asset_server);
+
+        let mut input_server = InputServer::new();
+        if let Some(path) = &self.config.replay_input {
+            match InputRecording::load(path) {
+                // 不同的 tick 频率下回放无法复现原来的会话
+                Ok(recording) if recording.tick_rate != self.config.tick_rate => log::error!(
+                    "Input recording {} was made at {} ticks per second, not replaying it at {}",
+                    path.display(),
+                    recording.tick_rate,
+                    self.config.tick_rate
+                ),
+                Ok(recording) => {
+                    log::info!("Replaying input from {}", path.display());
+                    input_server.start_replay(recording);
+                }
+                Err(err) => log::error!("{:#}", err),
+            }
+        }
+        if let Some(path) = &self.config.record_input {
+            if let Err(err) = input_server.start_recording_to(self.config.tick_rate, path) {
+                log::error!("{:#}", err);
+            }
+        }
+
+        let resources = &mut self.world.resources;
+        resources.insert(time);
+        resources.insert(render_context);
+        resources.insert(input_server);
+        resources.insert(Windows::new(
+            UVec2::new(size.width, size.height),
+            self.scale_factor,
+        ));
+        resources.insert(font_server);
+        resources.insert(asset_server);
+        resources.add_event::<WindowResized>();
+
+        self.render_world = Some(render_world);
+        self.initialized = true;
+
+        // Run user setup callback if provided.
+        if let Some(setup) = self.setup_callback.take() {
+            setup(self);
+        }
+
+        Ok(())
+    }
+
+    /// One fixed logic tick: input, then world and user updates.
+    fn fixed_update(&mut self, fixed_dt: f64) {
+        let logic_tick_start = std::time::Instant::now();
+
+        self.restore_gpu_resources();
+
+        // 2. 处理输入 (在逻辑更新前)，光标捕获作用于焦点窗口
+        if let Some(mut input_server) = self.world.resources.get_mut::<InputServer>() {
+            let focused = input_server.get_focused_window();
+            let window = self
+                .secondary_windows
+                .values()
+                .find(|(id, _)| *id == focused)
+                .map(|(_, window)| window)
+                .or(self.window.as_ref());
+            if let Some(window) = window {
+                input_server.update(window);
+            }
+            input_server.begin_tick();
+        }
+
+        // 状态切换在两个 tick 之间生效，上一个 tick 排队的切换在此应用
+        if let Some(render_world) = &mut self.render_world {
+            for apply in &self.state_transitions {
+                apply(&mut self.world, render_world);
+            }
+        }
+
+        // 3. 执行固定步长逻辑更新，系统使用缩放/暂停后的虚拟时间
+        let dt = match self.world.resources.get_mut::<Time>() {
+            Some(mut time) => time.advance_virtual(fixed_dt),
+            None => fixed_dt,
+        };
+        self.update(dt as f32);
+
+        // 事件保留两个 tick：本 tick 发送的事件在下一个 tick 仍可读取
+        self.world.resources.update_events();
+
+        // 4. 记录逻辑耗时 (仅包含真正的逻辑 Tick)
+        if let Some(time) = self.world.resources.get::<Time>() {
+            time.logic_time.store(
+                logic_tick_start.elapsed().as_nanos() as u64,
+                std::sync::atomic::Ordering::Relaxed,
+            );
+        }
+    }
+
+    /// After a device loss, switch to the device recreated by the render thread and upload the
+    /// shared textures and meshes to it again. Without [`AppConfig::retain_asset_data`] their
+    /// data is gone, so the assets are requested again instead.
+    fn restore_gpu_resources(&mut self) {
+        let Some(render_world) = &self.render_world else {
+            return;
+        };
+        let Some(mut render_context) = self.world.resources.get_mut::<RenderContext>() else {
+            return;
+        };
+        let Ok(recovered) = render_world.device_recovered.try_recv() else {
+            return;
+        };
+
+        render_context.adapter = recovered.adapter;
+        render_context.device = recovered.device;
+        render_context.queue = recovered.queue;
+
+        render_world
+            .imported_texture_cache
+            .write()
+            .unwrap()
+            .restore(&render_context.device, &render_context.queue);
+        render_world
+            .imported_mesh_allocator
+            .write()
+            .unwrap()
+            .restore(&render_context.device, &render_context.queue);
+        drop(render_context);
+        if !self.config.retain_asset_data {
+            // 网格和材质引用的 GPU 数据已丢失，随模型一起重新加载
+            render_world.imported_mesh_cache.write().unwrap().storage.clear();
+            render_world.imported_material_cache.write().unwrap().storage.clear();
+            crate::scene::systems::reload_assets(&mut self.world.ecs);
+        }
+        if let Some(mut font_server) = self.world.resources.get_mut::<FontServer>() {
+            font_server.invalidate_atlases();
+        }
+
+        let _ = render_world.sender.send(RenderCommand::ResourcesRestored);
+        log::info!("GPU resources restored");
     }
 
     /// Resize window.
-    fn resize(&mut self, new_size: PhysicalSize<u32>) {
-        self.window_size = new_size.to_logical(self.scale_factor);
+    fn resize(&mut self, id: WindowId, new_size: PhysicalSize<u32>) {
+        if id == WindowId::PRIMARY {
+            self.window_size = new_size.to_logical(self.scale_factor);
+        }
+
+        if self.initialized {
+            let resources = &mut self.world.resources;
+            if let Some(info) = resources.resource_mut::<Windows>().get_mut(id) {
+                info.size = UVec2::new(new_size.width, new_size.height);
+            }
+
+            resources.send_event(WindowResized {
+                window: id,
+                size: UVec2::new(new_size.width, new_size.height),
+            });
 
-        if let Some(singletons) = &mut self.singletons {
             if new_size.width > 0 && new_size.height > 0 {
                 // 1. 更新 wgpu surface 配置 (逻辑层记录)
-                singletons.render_context.surface_config.width = new_size.width;
-                singletons.render_context.surface_config.height = new_size.height;
+                if id == WindowId::PRIMARY {
+                    let mut render_context = resources.resource_mut::<RenderContext>();
+                    render_context.surface_config.width = new_size.width;
+                    render_context.surface_config.height = new_size.height;
+                }
 
                 // 3. 通知渲染线程执行真正的配置和资源清理
                 if let Some(render_world) = &self.render_world {
-                    let _ = render_world
-                        .sender
-                        .send(RenderCommand::Resize(new_size.width, new_size.height));
+                    let _ = render_world.sender.send(RenderCommand::Resize(
+                        id,
+                        new_size.width,
+                        new_size.height,
+                    ));
                 }
             }
         }
     }
 
     /// Handle input events.
-    fn input(&mut self, event: &WindowEvent) -> bool {
-        if let (Some(window), Some(singletons)) = (&self.window, &mut self.singletons) {
+    fn input(&mut self, id: WindowId, event: &WindowEvent) -> bool {
+        if let Some(mut input_server) = self.world.resources.get_mut::<InputServer>() {
             // Convert to our own input events.
-            singletons.input_server.prepare_input_event(window, event);
+            input_server.prepare_input_event(id, event);
 
             return true;
         }
         false
     }
 
-    fn update(&mut self, dt: f32) {
-        if let (Some(singletons), Some(render_world)) =
-            (&mut self.singletons, &mut self.render_world)
-        {
-            // Reconcile fonts.
-            singletons.font_server.update(
-                &singletons.render_context,
-                &mut render_world.imported_texture_cache.write().unwrap(),
-                &mut singletons.asset_server,
+    /// Create and destroy the windows requested through [`Windows`].
+    fn update_windows(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(render_world) = &self.render_world else {
+            return;
+        };
+        let Some(mut windows) = self.world.resources.get_mut::<Windows>() else {
+            return;
+        };
+        let render_context = self.world.resources.resource::<RenderContext>();
+
+        for id in std::mem::take(&mut windows.pending_closes) {
+            if id == WindowId::PRIMARY {
+                event_loop.exit();
+                continue;
+            }
+
+            self.secondary_windows
+                .retain(|_, (window_id, _)| *window_id != id);
+            windows.remove(id);
+            let _ = render_world.sender.send(RenderCommand::RemoveWindow(id));
+        }
+
+        for (id, descriptor) in std::mem::take(&mut windows.pending_spawns) {
+            let mut attributes = WindowAttributes::default();
+            attributes.title = descriptor.title;
+            attributes.inner_size = Some(Size::from(LogicalSize::new(
+                descriptor.width.max(1),
+                descriptor.height.max(1),
+            )));
+            attributes.resizable = descriptor.resizable;
+
+            let window = match event_loop.create_window(attributes) {
+                Ok(window) => Arc::new(window),
+                Err(err) => {
+                    log::error!("Failed to create window {:?}: {}", id, err);
+                    continue;
+                }
+            };
+
+            let surface = match render_context.instance.create_surface(window.clone()) {
+                Ok(surface) => surface,
+                Err(err) => {
+                    log::error!("Failed to create surface for window {:?}: {}", id, err);
+                    continue;
+                }
+            };
+
+            let size = window.inner_size();
+            windows.insert(
+                id,
+                WindowInfo {
+                    size: UVec2::new(size.width, size.height),
+                    scale_factor: window.scale_factor(),
+                    focused: false,
+                },
             );
 
-            // Note: we don't call singletons.time.tick() here anymore,
+            let _ = render_world.sender.send(RenderCommand::AddWindow(
+                id,
+                WindowSurface {
+                    window: window.clone(),
+                    surface,
+                },
+                size.width.max(1),
+                size.height.max(1),
+            ));
+
+            self.secondary_windows.insert(window.id(), (id, window));
+        }
+    }
+
+    fn update(&mut self, dt: f32) {
+        if let (true, Some(render_world)) = (self.initialized, &mut self.render_world) {
+            // Note: we don't call time.tick() here anymore,
             // as it's called once per main loop iteration in about_to_wait.
 
-            self.world.update(dt, singletons, render_world);
+            self.world.update(dt, render_world);
 
             // Run user-defined update callbacks.
             let callbacks = std::mem::take(&mut self.update_callbacks);
@@ -254,25 +1043,46 @@ impl App {
     }
 
     fn render(&mut self) {
-        let (Some(singletons), Some(render_world)) = (&mut self.singletons, &mut self.render_world)
+        self.restore_gpu_resources();
+
+        let Some(render_world) = &mut self.render_world else {
+            return;
+        };
+        let Some((frame_dt, alpha)) = self
+            .world
+            .resources
+            .get::<Time>()
+            .map(|time| (time.get_delta() as f32, time.get_interpolation_alpha()))
         else {
             return;
         };
 
+        // Per-frame systems that prepare the world for extraction.
+        self.world.run_stage(Stage::Extract, frame_dt, render_world);
+
         // Extract render entities from the draw commands.
-        let extracted = self.world.extract_render_objects();
+        let extract_start = std::time::Instant::now();
+        let extracted = self.world.extract_render_objects(alpha);
+        if let Some(time) = self.world.resources.get::<Time>() {
+            time.profiler.record(Track::Logic, "extract", extract_start);
+        }
 
         // Update server GPU resources (text).
-        singletons.font_server.prepare(
-            &singletons.render_context,
+        self.world.resources.resource_mut::<FontServer>().prepare(
+            &self.world.resources.resource::<RenderContext>(),
             &mut render_world.imported_texture_cache.write().unwrap(),
         );
 
         // Send to render thread using try_send to avoid blocking the logic thread.
         // If the render thread is too far behind, we just skip this frame's extraction.
-        let _ = render_world
-            .sender
-            .try_send(RenderCommand::Render(extracted));
+        // Headless apps block instead, so that every tick is rendered deterministically.
+        if self.headless {
+            let _ = render_world.sender.send(RenderCommand::Render(extracted));
+        } else {
+            let _ = render_world
+                .sender
+                .try_send(RenderCommand::Render(extracted));
+        }
     }
 }
 
@@ -282,70 +1092,68 @@ impl ApplicationHandler for App {
             return;
         }
 
-        // Use cargo package name as the window title.
-        let title = env!("CARGO_PKG_NAME");
-
         let mut attributes = WindowAttributes::default();
-        attributes.title = title.to_string();
+        attributes.title = self.config.title.clone();
         attributes.inner_size = Some(Size::from(self.window_size));
+        attributes.resizable = self.config.resizable;
+        attributes.fullscreen =
+            Self::fullscreen(self.config.window_mode, event_loop.primary_monitor());
 
         let window = Arc::new(event_loop.create_window(attributes).unwrap());
         self.window = Some(window.clone());
 
-        let time = Time::new();
-
-        // App::init_render uses async code, so we're going to wait for it to finish.
-        let (render_context, surface) = pollster::block_on(Self::init_render(
-            window.clone(),
-            time.render_cpu_time.clone(),
-            time.gpu_time.clone(),
-        ));
-
-        let mut asset_server = AssetServer::new();
-        let render_world = RenderWorld::new(render_context.clone(), surface);
-        let font_server = FontServer::new(&mut asset_server);
-
-        self.singletons = Some(Singletons {
-            time,
-            render_context,
-            input_server: InputServer::new(),
-            font_server,
-            asset_server,
-        });
-
-        self.render_world = Some(render_world);
-        self.initialized = true;
-
-        // Run user setup callback if provided.
-        if let Some(setup) = self.setup_callback.take() {
-            setup(self);
+        if let Err(err) = self.init(Some(window)) {
+            log::error!("Failed to initialize renderer: {}", err);
+            self.init_error = Some(err);
+            event_loop.exit();
         }
     }
 
     fn window_event(
         &mut self,
         event_loop: &ActiveEventLoop,
-        window_id: WindowId,
+        window_id: winit::window::WindowId,
         event: WindowEvent,
     ) {
         // Clone the Arc to release the borrow on self and satisfy the borrow checker.
-        let window = match &self.window {
-            Some(w) if w.id() == window_id => w.clone(),
-            _ => return,
+        let (id, window) = match &self.window {
+            Some(w) if w.id() == window_id => (WindowId::PRIMARY, w.clone()),
+            _ => match self.secondary_windows.get(&window_id) {
+                Some((id, w)) => (*id, w.clone()),
+                None => return,
+            },
         };
 
         match event {
-            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::CloseRequested if id == WindowId::PRIMARY => event_loop.exit(),
+            WindowEvent::CloseRequested => {
+                if let Some(mut windows) = self.world.resources.get_mut::<Windows>() {
+                    windows.close(id);
+                }
+            }
             WindowEvent::Resized(physical_size) => {
-                self.resize(physical_size);
-                log::info!("Window resized to {:?}", physical_size);
+                self.resize(id, physical_size);
+                log::info!("Window {:?} resized to {:?}", id, physical_size);
+            }
+            // 附加窗口的 Resized 事件随后会到达
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } if id != WindowId::PRIMARY => {
+                if let Some(mut windows) = self.world.resources.get_mut::<Windows>() {
+                    if let Some(info) = windows.get_mut(id) {
+                        info.scale_factor = scale_factor;
+                    }
+                }
             }
             // Scale factor changed.
             WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
                 self.scale_factor = scale_factor;
+                if let Some(mut windows) = self.world.resources.get_mut::<Windows>() {
+                    if let Some(info) = windows.get_mut(id) {
+                        info.scale_factor = scale_factor;
+                    }
+                }
 
                 let new_physical_size = self.window_size.to_physical(scale_factor);
-                self.resize(new_physical_size);
+                self.resize(id, new_physical_size);
 
                 let _ = window.request_inner_size(new_physical_size);
 
@@ -354,32 +1162,44 @@ impl ApplicationHandler for App {
                     new_physical_size
                 );
             }
-            // Redraw request.
+            // Redraw request. A frame renders all windows, so only the primary one triggers it.
             WindowEvent::RedrawRequested => {
-                if self.singletons.is_some() {
+                if id == WindowId::PRIMARY && self.initialized {
                     self.render();
                 }
             }
             _ => {
+                if let WindowEvent::Focused(focused) = event {
+                    if let Some(mut windows) = self.world.resources.get_mut::<Windows>() {
+                        if let Some(info) = windows.get_mut(id) {
+                            info.focused = focused;
+                        }
+                    }
+                }
+
                 // Other input events should be handled by the input server.
-                self.input(&event);
+                self.input(id, &event);
             }
         }
     }
 
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        self.shutdown();
+    }
+
     fn device_event(
         &mut self,
         _event_loop: &ActiveEventLoop,
         _device_id: DeviceId,
         event: DeviceEvent,
     ) {
-        if let Some(singletons) = &mut self.singletons {
-            singletons.input_server.handle_device_event(&event);
+        if let Some(mut input_server) = self.world.resources.get_mut::<InputServer>() {
+            input_server.handle_device_event(&event);
         }
     }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
-        if self.singletons.is_none() || self.window.is_none() {
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if !self.initialized || self.window.is_none() {
             return;
         }
 
@@ -388,48 +1208,54 @@ impl ApplicationHandler for App {
         let elapsed = now.duration_since(self.last_tick_time).as_secs_f64();
         self.last_tick_time = now;
 
+        let Some((fixed_dt, max_catch_up)) = self
+            .world
+            .resources
+            .get::<Time>()
+            .map(|time| (time.get_fixed_delta(), time.get_max_catch_up()))
+        else {
+            return;
+        };
+
         // 防止“死亡螺旋”
-        let elapsed = elapsed.min(0.1);
+        let elapsed = elapsed.min(max_catch_up);
         self.accumulator += elapsed;
 
-        let fixed_dt = 1.0 / 120.0;
         let mut updated = false;
 
         while self.accumulator >= fixed_dt {
-            let logic_tick_start = std::time::Instant::now();
-
-            // 2. 处理输入 (在逻辑更新前)
-            {
-                let window = self.window.as_ref().unwrap();
-                let singletons = self.singletons.as_mut().unwrap();
-                singletons.input_server.update(window);
-                self.world.input(&mut singletons.input_server);
-            }
-
-            // 3. 执行固定步长逻辑更新
-            self.update(fixed_dt as f32);
-
-            // 4. 记录逻辑耗时 (仅包含真正的逻辑 Tick)
-            if let Some(s) = &mut self.singletons {
-                s.time.logic_time.store(
-                    logic_tick_start.elapsed().as_nanos() as u64,
-                    std::sync::atomic::Ordering::Relaxed,
-                );
-            }
+            self.fixed_update(fixed_dt);
 
             self.accumulator -= fixed_dt;
             updated = true;
         }
 
-        if updated {
-            if let Some(s) = &mut self.singletons {
-                s.input_server.clear_events();
+        let interpolate = self.config.interpolate_transforms;
+
+        if let Some(mut time) = self.world.resources.get_mut::<Time>() {
+            if updated {
+                self.world
+                    .resources
+                    .resource_mut::<InputServer>()
+                    .clear_events();
                 // 在这里调用 tick，这样 Time 里的 FPS 统计的就是真正的“逻辑更新频率”
-                s.time.tick();
+                time.tick();
             }
 
-            // 只有当逻辑确实更新了，才请求重绘画面
-            // 这能防止 TAA 在物体静止时进行重复渲染导致的拖影
+            let alpha = if interpolate {
+                (self.accumulator / fixed_dt) as f32
+            } else {
+                1.0
+            };
+            time.set_interpolation_alpha(alpha);
+        }
+
+        // 在渲染之前创建/销毁逻辑层请求的窗口
+        self.update_windows(event_loop);
+
+        // 不插值时，只有当逻辑确实更新了，才请求重绘画面
+        // 这能防止 TAA 在物体静止时进行重复渲染导致的拖影
+        if updated || interpolate {
             if let Some(window) = &self.window {
                 window.request_redraw();
             }
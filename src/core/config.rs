@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+/// How the window covers the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowMode {
+    Windowed,
+    /// Borderless window covering the current monitor.
+    BorderlessFullscreen,
+    /// Exclusive fullscreen using the monitor's highest resolution video mode. Falls back to
+    /// borderless if the platform has no video modes.
+    Fullscreen,
+}
+
+/// App settings, applied when the app is initialized.
+pub struct AppConfig {
+    /// Window title.
+    pub title: String,
+    /// Initial window size in logical pixels.
+    pub width: u32,
+    pub height: u32,
+    pub resizable: bool,
+    pub window_mode: WindowMode,
+    /// Preferred present mode. If `None` or unsupported, the lowest latency mode available is
+    /// picked (Mailbox > Immediate > Fifo).
+    pub present_mode: Option<wgpu::PresentMode>,
+    pub power_preference: wgpu::PowerPreference,
+    /// Frames the logic thread may queue ahead of the GPU. If `None`, derived from the present
+    /// mode (3 for Mailbox, otherwise 2).
+    pub frames_in_flight: Option<u32>,
+    /// Fixed logic ticks per second.
+    pub tick_rate: f64,
+    /// Most real time (in seconds) simulated in one frame. Anything beyond is dropped, so that a
+    /// slow frame doesn't cause an ever-growing backlog of ticks.
+    pub max_catch_up: f64,
+    /// Render transforms interpolated between the last two ticks, and redraw every frame
+    /// instead of only after a tick.
+    pub interpolate_transforms: bool,
+    /// Record the input of every fixed tick to this file, written as it is recorded, see
+    /// [`crate::window::InputRecording`].
+    pub record_input: Option<PathBuf>,
+    /// Replay the input recorded in this file instead of reading window input. The recording
+    /// has to be made at the same `tick_rate`.
+    pub replay_input: Option<PathBuf>,
+    /// Keep a CPU copy of all imported meshes and textures, so they can be uploaded again after
+    /// a GPU device loss. Without it, models, skies and sprites are loaded again from their files,
+    /// and models are picked and turned into convex hulls by their bounding boxes.
+    pub retain_asset_data: bool,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            // Use cargo package name as the window title.
+            title: env!("CARGO_PKG_NAME").to_string(),
+            width: 1280,
+            height: 720,
+            resizable: true,
+            window_mode: WindowMode::Windowed,
+            present_mode: None,
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            frames_in_flight: None,
+            tick_rate: 120.0,
+            max_catch_up: 0.1,
+            interpolate_transforms: true,
+            record_input: None,
+            replay_input: None,
+            retain_asset_data: true,
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use crate::animation::AnimationFinished;
+use crate::asset::{AssetEvent, AssetServer};
+use crate::core::{App, Plugin};
+use crate::physics::{CollisionEvent, Physics};
+use crate::render::RenderContext;
+use crate::scene::components::TimerFinished;
+use crate::scene::system_labels::*;
+use crate::scene::systems::*;
+use crate::scene::{ChangeTick, SceneRegistry, SpatialIndex, Stage};
+use crate::text::FontServer;
+use crate::window::{InputServer, Windows};
+use std::any::TypeId;
+
+/// Ticks `Timer` and `Stopwatch` components with virtual time and sends [`TimerFinished`] events.
+pub struct TimePlugin;
+
+impl Plugin for TimePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TimerFinished>();
+
+        app.add_system(
+            Stage::PreUpdate,
+            TIMERS,
+            |ecs, resources, _render_world, dt| {
+                update_timers(ecs, &mut resources.event_writer(), dt)
+            },
+        );
+    }
+}
+
+/// Polls the asset server, sends [`AssetEvent`]s and finalizes loaded models, skies and sprites.
+pub struct AssetPlugin;
+
+impl Plugin for AssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AssetEvent>();
+
+        app.add_system(Stage::PreUpdate, ASSETS, |ecs, resources, render_world, _dt| {
+            let mut asset_server = resources.resource_mut::<AssetServer>();
+
+            // 先更新资产服务器，从后台线程接收已加载的资产
+            asset_server.update();
+            let events = asset_server.take_events();
+
+            update_assets(
+                ecs,
+                &mut asset_server,
+                &resources.resource::<RenderContext>(),
+                render_world,
+            );
+
+            drop(asset_server);
+            resources.event_writer().send_batch(events);
+        });
+    }
+}
+
+/// Keeps camera viewports in sync with the render target size.
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(Stage::Update, CAMERAS, |ecs, resources, _render_world, _dt| {
+            update_cameras(ecs, &resources.resource::<Windows>())
+        });
+    }
+}
+
+/// Advances animation players.
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AnimationFinished>();
+
+        app.add_system(Stage::Update, ANIMATION, |ecs, resources, _render_world, dt| {
+            update_animations(ecs, &mut resources.event_writer(), dt)
+        });
+    }
+}
+
+/// Moves cameras driven by a `Camera3dController`.
+pub struct CameraControllerPlugin;
+
+impl Plugin for CameraControllerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            Stage::PreUpdate,
+            CAMERA_CONTROLLER_INPUT,
+            |ecs, resources, _render_world, _dt| {
+                handle_input(ecs, &mut resources.resource_mut::<InputServer>())
+            },
+        )
+        .after(ASSETS);
+
+        app.add_system(
+            Stage::Update,
+            CAMERA_CONTROLLER,
+            |ecs, _resources, _render_world, dt| update_example_logic(ecs, dt),
+        );
+    }
+}
+
+/// Steps the [`Physics`] simulation and sends [`CollisionEvent`]s.
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CollisionEvent>();
+        app.world.resources.insert(Physics::new());
+
+        app.add_system(
+            Stage::PostUpdate,
+            PHYSICS,
+            |ecs, resources, _render_world, dt| {
+                let events = resources.resource_mut::<Physics>().step(ecs, dt);
+                resources.event_writer().send_batch(events);
+            },
+        )
+        .before(PROPAGATE_TRANSFORMS);
+    }
+}
+
+/// Propagates local transforms to `GlobalTransform`.
+pub struct TransformPlugin;
+
+impl Plugin for TransformPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            Stage::PostUpdate,
+            PROPAGATE_TRANSFORMS,
+            |ecs, resources, _render_world, _dt| {
+                propagate_transforms(ecs, resources.resource::<ChangeTick>().0)
+            },
+        );
+    }
+}
+
+/// Keeps the [`SpatialIndex`] resource in sync with the propagated transforms.
+pub struct SpatialIndexPlugin;
+
+impl Plugin for SpatialIndexPlugin {
+    fn build(&self, app: &mut App) {
+        app.world.resources.insert(SpatialIndex::new());
+
+        app.add_system(
+            Stage::PostUpdate,
+            SPATIAL_INDEX,
+            |ecs, resources, _render_world, _dt| {
+                resources.resource_mut::<SpatialIndex>().update(ecs)
+            },
+        )
+        .after(PROPAGATE_TRANSFORMS);
+    }
+}
+
+/// Reconciles loaded fonts and lays out labels.
+pub struct TextPlugin;
+
+impl Plugin for TextPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(Stage::PostUpdate, TEXT, |ecs, resources, render_world, _dt| {
+            let mut font_server = resources.resource_mut::<FontServer>();
+            let mut asset_server = resources.resource_mut::<AssetServer>();
+
+            font_server.update(
+                &resources.resource::<RenderContext>(),
+                &mut render_world.imported_texture_cache.write().unwrap(),
+                &mut asset_server,
+            );
+
+            update_labels(ecs, &mut asset_server, &mut font_server);
+        })
+        .after(PROPAGATE_TRANSFORMS);
+    }
+}
+
+/// Adds the [`SceneRegistry`] used by `World::save_scene` and `World::load_scene`.
+pub struct ScenePlugin;
+
+impl Plugin for ScenePlugin {
+    fn build(&self, app: &mut App) {
+        app.world.resources.insert(SceneRegistry::new());
+    }
+}
+
+/// The engine's own plugins, added by [`App::new`].
+///
+/// Individual plugins can be left out to replace them:
+/// ```ignore
+/// let mut app = App::with_plugins(
+///     AppConfig::default(),
+///     DefaultPlugins::new().disable::<AnimationPlugin>(),
+/// );
+/// app.add_plugin(MyAnimationPlugin);
+/// ```
+#[derive(Default)]
+pub struct DefaultPlugins {
+    disabled: Vec<TypeId>,
+}
+
+impl DefaultPlugins {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn disable<P: Plugin>(mut self) -> Self {
+        self.disabled.push(TypeId::of::<P>());
+        self
+    }
+
+    fn add<P: Plugin>(&self, app: &mut App, plugin: P) {
+        if !self.disabled.contains(&TypeId::of::<P>()) {
+            app.add_plugin(plugin);
+        }
+    }
+}
+
+impl Plugin for DefaultPlugins {
+    fn build(&self, app: &mut App) {
+        self.add(app, TimePlugin);
+        self.add(app, AssetPlugin);
+        self.add(app, CameraPlugin);
+        self.add(app, AnimationPlugin);
+        self.add(app, CameraControllerPlugin);
+        self.add(app, PhysicsPlugin);
+        self.add(app, TransformPlugin);
+        self.add(app, SpatialIndexPlugin);
+        self.add(app, TextPlugin);
+        self.add(app, ScenePlugin);
+    }
+}
@@ -0,0 +1,157 @@
+use std::cell::RefMut;
+use std::marker::PhantomData;
+
+struct EventInstance<T> {
+    id: usize,
+    event: T,
+}
+
+/// Event queue of one type, see [`crate::scene::Resources::add_event`].
+///
+/// Double-buffered: the queue is updated after every fixed tick, so an event stays readable
+/// during the tick it was sent in and the next one, then it is dropped.
+pub struct Events<T> {
+    previous: Vec<EventInstance<T>>,
+    current: Vec<EventInstance<T>>,
+    event_count: usize,
+}
+
+impl<T> Default for Events<T> {
+    fn default() -> Self {
+        Self {
+            previous: Vec::new(),
+            current: Vec::new(),
+            event_count: 0,
+        }
+    }
+}
+
+impl<T> Events<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn send(&mut self, event: T) {
+        self.current.push(EventInstance {
+            id: self.event_count,
+            event,
+        });
+        self.event_count += 1;
+    }
+
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = T>) {
+        for event in events {
+            self.send(event);
+        }
+    }
+
+    /// Drop the events of the previous tick and start a new one.
+    pub fn update(&mut self) {
+        std::mem::swap(&mut self.previous, &mut self.current);
+        self.current.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.previous.clear();
+        self.current.clear();
+    }
+
+    /// Number of buffered events.
+    pub fn len(&self) -> usize {
+        self.previous.len() + self.current.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// All buffered events, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.previous
+            .iter()
+            .chain(&self.current)
+            .map(|instance| &instance.event)
+    }
+}
+
+/// Sends events of one type, see [`crate::scene::Resources::event_writer`].
+pub struct EventWriter<'a, T> {
+    events: RefMut<'a, Events<T>>,
+}
+
+impl<'a, T> EventWriter<'a, T> {
+    pub(crate) fn new(events: RefMut<'a, Events<T>>) -> Self {
+        Self { events }
+    }
+
+    pub fn send(&mut self, event: T) {
+        self.events.send(event);
+    }
+
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = T>) {
+        self.events.send_batch(events);
+    }
+}
+
+/// Remembers which events of one type have been read. Systems keep one per event type, e.g.
+/// captured by the system closure:
+/// ```ignore
+/// let mut reader = EventReader::<WindowResized>::new();
+/// app.add_system(Stage::Update, "resize_ui", move |_ecs, resources, _render_world, _dt| {
+///     for event in reader.read(&resources.events().unwrap()) {
+///         log::info!("{:?}", event);
+///     }
+/// });
+/// ```
+pub struct EventReader<T> {
+    last_event_count: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for EventReader<T> {
+    fn default() -> Self {
+        Self {
+            last_event_count: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> EventReader<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Events not read by this reader yet, oldest first. Events dropped before being read are
+    /// skipped.
+    pub fn read<'a>(&mut self, events: &'a Events<T>) -> impl Iterator<Item = &'a T> {
+        let start = self.last_event_count;
+        self.last_event_count = events.event_count;
+
+        events
+            .previous
+            .iter()
+            .chain(&events.current)
+            .filter(move |instance| instance.id >= start)
+            .map(|instance| &instance.event)
+    }
+
+    /// Number of unread events.
+    pub fn len(&self, events: &Events<T>) -> usize {
+        events
+            .previous
+            .iter()
+            .chain(&events.current)
+            .filter(|instance| instance.id >= self.last_event_count)
+            .count()
+    }
+
+    pub fn is_empty(&self, events: &Events<T>) -> bool {
+        self.len(events) == 0
+    }
+
+    /// Mark all events as read.
+    pub fn clear(&mut self, events: &Events<T>) {
+        self.last_event_count = events.event_count;
+    }
+}
@@ -1,7 +1,17 @@
 pub mod app;
-pub(crate) mod singleton;
+pub(crate) mod config;
+pub(crate) mod default_plugins;
+pub(crate) mod events;
+pub(crate) mod plugin;
+pub(crate) mod profiler;
+pub(crate) mod state;
 pub(crate) mod time;
 
 pub use app::*;
-pub use singleton::*;
+pub use config::*;
+pub use default_plugins::*;
+pub use events::*;
+pub use plugin::*;
+pub use profiler::*;
+pub use state::*;
 pub use time::*;
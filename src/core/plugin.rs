@@ -0,0 +1,29 @@
+use crate::core::App;
+
+/// A reusable piece of app functionality: logic systems, render graph nodes, resources and
+/// asset loaders.
+///
+/// ```ignore
+/// struct PhysicsPlugin;
+///
+/// impl Plugin for PhysicsPlugin {
+///     fn build(&self, app: &mut App) {
+///         app.insert_resource(PhysicsSettings::default());
+///         app.add_system(Stage::PostUpdate, "physics", |ecs, resources, _render_world, dt| {
+///             step_physics(ecs, &resources.resource::<PhysicsSettings>(), dt)
+///         })
+///         .before(system_labels::PROPAGATE_TRANSFORMS);
+///     }
+/// }
+///
+/// app.add_plugin(PhysicsPlugin);
+/// ```
+pub trait Plugin: 'static {
+    /// Register everything this plugin provides. Called once, when the plugin is added.
+    fn build(&self, app: &mut App);
+
+    /// Plugins with the same name are only added once.
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
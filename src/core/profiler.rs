@@ -0,0 +1,310 @@
+use anyhow::Result;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Number of samples in a rolling average.
+const AVERAGE_WINDOW: usize = 120;
+
+/// Timeline a span is recorded on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Track {
+    /// Logic systems on the main thread.
+    Logic,
+    /// Render preparation and command recording on the render thread.
+    Render,
+    /// Render graph nodes on the GPU, primary window only.
+    Gpu,
+}
+
+impl Track {
+    fn name(self) -> &'static str {
+        match self {
+            Track::Logic => "Logic",
+            Track::Render => "Render",
+            Track::Gpu => "GPU",
+        }
+    }
+}
+
+struct Span {
+    track: Track,
+    name: String,
+    /// Since the profiler was created.
+    start: Duration,
+    duration: Duration,
+}
+
+#[derive(Default)]
+struct RollingAverage {
+    samples: VecDeque<Duration>,
+    sum: Duration,
+}
+
+impl RollingAverage {
+    fn push(&mut self, sample: Duration) {
+        self.samples.push_back(sample);
+        self.sum += sample;
+        if self.samples.len() > AVERAGE_WINDOW {
+            self.sum -= self.samples.pop_front().unwrap();
+        }
+    }
+
+    fn average(&self) -> Duration {
+        self.sum / self.samples.len().max(1) as u32
+    }
+}
+
+#[derive(Default)]
+struct ProfilerData {
+    averages: HashMap<Track, HashMap<String, RollingAverage>>,
+    captured: Vec<Span>,
+    /// Index of the frame being rendered.
+    frame: u64,
+    /// Frames whose spans are captured.
+    capture: Range<u64>,
+    /// Last frame whose GPU spans were read back, they arrive a few frames late.
+    gpu_frame: Option<u64>,
+}
+
+impl ProfilerData {
+    fn record(
+        &mut self,
+        track: Track,
+        name: &str,
+        start: Duration,
+        duration: Duration,
+        frame: u64,
+    ) {
+        let averages = self.averages.entry(track).or_default();
+        match averages.get_mut(name) {
+            Some(average) => average.push(duration),
+            None => averages.entry(name.to_string()).or_default().push(duration),
+        }
+
+        if self.capture.contains(&frame) {
+            self.captured.push(Span {
+                track,
+                name: name.to_string(),
+                start,
+                duration,
+            });
+        }
+    }
+
+    /// The capture waits for the GPU spans of its last frame, if GPU timing is on.
+    fn is_capturing(&self) -> bool {
+        let last = self.capture.end.saturating_sub(1);
+        !self.capture.is_empty()
+            && (self.frame <= last || self.gpu_frame.is_some_and(|frame| frame < last))
+    }
+}
+
+/// CPU and GPU timings of logic systems, render preparation and render graph nodes, shared by
+/// the logic and render threads. Available as [`crate::core::Time::profiler`].
+///
+/// Every span feeds a rolling average over the last samples. Spans of a few frames can be
+/// captured and exported for `chrome://tracing` or Perfetto:
+/// ```ignore
+/// time.profiler.capture_frames(10);
+/// // ... ten frames later
+/// time.profiler.export_chrome_trace("trace.json")?;
+/// ```
+#[derive(Clone)]
+pub struct Profiler {
+    epoch: Instant,
+    data: Arc<Mutex<ProfilerData>>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self {
+            epoch: Instant::now(),
+            data: Default::default(),
+        }
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a span that started at `start` and ends now.
+    pub fn record(&self, track: Track, name: &str, start: Instant) {
+        self.record_span(track, name, start, start.elapsed());
+    }
+
+    pub fn record_span(&self, track: Track, name: &str, start: Instant, duration: Duration) {
+        let mut data = self.data.lock().unwrap();
+        let frame = data.frame;
+        let start = start.saturating_duration_since(self.epoch);
+        data.record(track, name, start, duration, frame);
+    }
+
+    /// Record a GPU span of an earlier frame, see [`Profiler::frame`].
+    pub(crate) fn record_gpu_span(
+        &self,
+        name: &str,
+        start: Instant,
+        duration: Duration,
+        frame: u64,
+    ) {
+        let mut data = self.data.lock().unwrap();
+        let start = start.saturating_duration_since(self.epoch);
+        data.record(Track::Gpu, name, start, duration, frame);
+    }
+
+    /// All GPU spans of `frame` have been recorded.
+    pub(crate) fn gpu_frame_done(&self, frame: u64) {
+        let mut data = self.data.lock().unwrap();
+        let was_capturing = data.is_capturing();
+        data.gpu_frame = Some(data.gpu_frame.map_or(frame, |last| last.max(frame)));
+        if was_capturing && !data.is_capturing() {
+            log::info!("Profiler capture done, {} spans", data.captured.len());
+        }
+    }
+
+    /// Index of the frame being rendered.
+    pub(crate) fn frame(&self) -> u64 {
+        self.data.lock().unwrap().frame
+    }
+
+    /// Time a scope, the span is recorded when the guard is dropped.
+    pub fn scope<'a>(&'a self, track: Track, name: &'a str) -> ProfileScope<'a> {
+        ProfileScope {
+            profiler: self,
+            track,
+            name,
+            start: Instant::now(),
+        }
+    }
+
+    /// Rolling average of a span.
+    pub fn average(&self, track: Track, name: &str) -> Option<Duration> {
+        let data = self.data.lock().unwrap();
+        data.averages
+            .get(&track)?
+            .get(name)
+            .map(RollingAverage::average)
+    }
+
+    /// Rolling averages of all spans, sorted by track and name.
+    pub fn averages(&self) -> Vec<(Track, String, Duration)> {
+        let data = self.data.lock().unwrap();
+        let mut averages: Vec<_> = data
+            .averages
+            .iter()
+            .flat_map(|(track, averages)| {
+                averages
+                    .iter()
+                    .map(|(name, average)| (*track, name.clone(), average.average()))
+            })
+            .collect();
+        averages.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+        averages
+    }
+
+    /// Capture all spans of the next `frames` rendered frames, discarding the previous capture.
+    pub fn capture_frames(&self, frames: u32) {
+        let mut data = self.data.lock().unwrap();
+        data.captured.clear();
+        data.capture = data.frame..data.frame + frames as u64;
+    }
+
+    /// A capture is running, or waiting for the GPU spans of its frames.
+    pub fn is_capturing(&self) -> bool {
+        self.data.lock().unwrap().is_capturing()
+    }
+
+    /// Called by the render thread after each frame.
+    pub(crate) fn end_frame(&self) {
+        let mut data = self.data.lock().unwrap();
+        let was_capturing = data.is_capturing();
+        data.frame += 1;
+        if was_capturing && !data.is_capturing() {
+            log::info!("Profiler capture done, {} spans", data.captured.len());
+        }
+    }
+
+    /// The captured spans in the Chrome trace event format.
+    pub fn chrome_trace(&self) -> String {
+        let data = self.data.lock().unwrap();
+
+        let mut events = Vec::new();
+        for track in [Track::Logic, Track::Render, Track::Gpu] {
+            events.push(format!(
+                r#"{{"name":"thread_name","ph":"M","pid":0,"tid":{},"args":{{"name":"{}"}}}}"#,
+                track as u32,
+                track.name()
+            ));
+        }
+
+        for span in &data.captured {
+            // 时间单位为微秒
+            events.push(format!(
+                r#"{{"name":"{}","cat":"{}","ph":"X","ts":{:.3},"dur":{:.3},"pid":0,"tid":{}}}"#,
+                escape_json(&span.name),
+                track_category(span.track),
+                span.start.as_secs_f64() * 1e6,
+                span.duration.as_secs_f64() * 1e6,
+                span.track as u32
+            ));
+        }
+
+        format!(
+            "{{\"displayTimeUnit\":\"ms\",\"traceEvents\":[\n{}\n]}}\n",
+            events.join(",\n")
+        )
+    }
+
+    /// Write the captured spans to a Chrome trace JSON file.
+    pub fn export_chrome_trace(&self, path: impl AsRef<Path>) -> Result<()> {
+        if self.is_capturing() {
+            log::warn!("Exporting an unfinished profiler capture");
+        }
+
+        std::fs::write(path.as_ref(), self.chrome_trace())?;
+        log::info!("Chrome trace written to {}", path.as_ref().display());
+        Ok(())
+    }
+}
+
+fn track_category(track: Track) -> &'static str {
+    match track {
+        Track::Logic | Track::Render => "cpu",
+        Track::Gpu => "gpu",
+    }
+}
+
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Records a span when dropped, see [`Profiler::scope`].
+pub struct ProfileScope<'a> {
+    profiler: &'a Profiler,
+    track: Track,
+    name: &'a str,
+    start: Instant,
+}
+
+impl Drop for ProfileScope<'_> {
+    fn drop(&mut self) {
+        self.profiler.record(self.track, self.name, self.start);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::render::render_world::RenderWorld;
+use crate::scene::{despawn_recursive, Resources, World};
+use hecs::World as EcsWorld;
+use std::fmt::Debug;
+
+/// Types usable as app states, usually a fieldless enum:
+/// ```ignore
+/// #[derive(Clone, Debug, PartialEq, Eq)]
+/// enum GameState {
+///     Loading,
+///     Menu,
+///     Playing,
+/// }
+///
+/// app.add_state(GameState::Loading)
+///     .on_state_enter(GameState::Menu, spawn_menu)
+///     .on_state_exit(GameState::Playing, save_progress);
+/// ```
+pub trait States: Clone + Debug + Eq + Send + Sync + 'static {}
+
+impl<T: Clone + Debug + Eq + Send + Sync + 'static> States for T {}
+
+/// Called once when a state is entered or exited.
+pub type StateSystem = Box<dyn FnMut(&mut EcsWorld, &mut Resources, &mut RenderWorld)>;
+
+/// Current state of type `S`, stored as a resource by [`crate::core::App::add_state`].
+pub struct State<S: States> {
+    current: S,
+    next: Option<S>,
+    /// The enter systems of the initial state have run.
+    entered: bool,
+}
+
+impl<S: States> State<S> {
+    pub fn new(initial: S) -> Self {
+        Self {
+            current: initial,
+            next: None,
+            entered: false,
+        }
+    }
+
+    pub fn get(&self) -> &S {
+        &self.current
+    }
+
+    /// Queue a transition. It is applied after the current fixed tick, a later call in the same
+    /// tick replaces it.
+    pub fn set(&mut self, next: S) {
+        self.next = Some(next);
+    }
+
+    /// The transition queued by [`State::set`], if any.
+    pub fn queued(&self) -> Option<&S> {
+        self.next.as_ref()
+    }
+}
+
+/// Entities with this component are despawned with their descendants when their state is
+/// exited.
+pub struct StateScoped<S: States>(pub S);
+
+/// Enter and exit systems of the states of type `S`.
+pub(crate) struct StateHooks<S: States> {
+    pub(crate) on_enter: Vec<(S, StateSystem)>,
+    pub(crate) on_exit: Vec<(S, StateSystem)>,
+}
+
+impl<S: States> Default for StateHooks<S> {
+    fn default() -> Self {
+        Self {
+            on_enter: Vec::new(),
+            on_exit: Vec::new(),
+        }
+    }
+}
+
+/// Apply the queued transition of `S`: run the exit systems of the old state, despawn its scoped
+/// entities, then run the enter systems of the new one. Setting the current state again does
+/// nothing.
+pub(crate) fn apply_state_transition<S: States>(world: &mut World, render_world: &mut RenderWorld) {
+    let Some(mut state) = world.resources.get_mut::<State<S>>() else {
+        return;
+    };
+
+    let entered = std::mem::replace(&mut state.entered, true);
+    let transition = match state.next.take() {
+        Some(next) if next != state.current => {
+            let previous = std::mem::replace(&mut state.current, next.clone());
+            Some((previous, next))
+        }
+        _ => None,
+    };
+    let current = state.current.clone();
+    drop(state);
+
+    if entered && transition.is_none() {
+        return;
+    }
+
+    // 钩子运行时可能访问 Resources，先取出
+    let Some(mut hooks) = world.resources.remove::<StateHooks<S>>() else {
+        return;
+    };
+
+    if !entered {
+        run_hooks(&mut hooks.on_enter, &current, world, render_world);
+    }
+
+    if let Some((previous, next)) = transition {
+        log::info!("State transition: {:?} -> {:?}", previous, next);
+
+        run_hooks(&mut hooks.on_exit, &previous, world, render_world);
+
+        let scoped: Vec<hecs::Entity> = world
+            .ecs
+            .query::<(hecs::Entity, &StateScoped<S>)>()
+            .iter()
+            .filter(|(_, scoped)| scoped.0 == previous)
+            .map(|(entity, _)| entity)
+            .collect();
+        for entity in scoped {
+            despawn_recursive(&mut world.ecs, entity);
+        }
+
+        run_hooks(&mut hooks.on_enter, &next, world, render_world);
+    }
+
+    world.resources.insert(hooks);
+}
+
+fn run_hooks<S: States>(
+    hooks: &mut [(S, StateSystem)],
+    state: &S,
+    world: &mut World,
+    render_world: &mut RenderWorld,
+) {
+    for (_, system) in hooks.iter_mut().filter(|(s, _)| s == state) {
+        system(&mut world.ecs, &mut world.resources, render_world);
+    }
+}
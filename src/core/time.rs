@@ -1,7 +1,9 @@
+use crate::core::Profiler;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::SystemTime;
 
+/// Real (wall-clock) time for UI and profiling, and virtual time for gameplay.
 pub struct Time {
     startup_time: SystemTime,
 
@@ -13,10 +15,25 @@ pub struct Time {
 
     last_time_updated_fps: SystemTime,
 
+    /// Length of a fixed logic tick.
+    fixed_delta: f64,
+    /// See [`crate::core::AppConfig::max_catch_up`].
+    max_catch_up: f64,
+    /// How far the rendered frame is between the previous (0) and the current (1) tick.
+    interpolation_alpha: f32,
+
+    // Virtual (game) time, scaled and pausable. Drives the logic systems.
+    scale: f64,
+    paused: bool,
+    virtual_delta: f64,
+    virtual_elapsed: f64,
+
     // Profiling stats in nanoseconds
     pub logic_time: Arc<AtomicU64>,
     pub render_cpu_time: Arc<AtomicU64>,
     pub gpu_time: Arc<AtomicU64>,
+    /// Per-system and per-node timings, shared with the render thread.
+    pub profiler: Profiler,
 }
 
 impl Time {
@@ -27,9 +44,17 @@ impl Time {
             delta: 0.0,
             fps: 0.0,
             last_time_updated_fps: SystemTime::now(),
+            fixed_delta: 1.0 / 120.0,
+            max_catch_up: 0.1,
+            interpolation_alpha: 1.0,
+            scale: 1.0,
+            paused: false,
+            virtual_delta: 0.0,
+            virtual_elapsed: 0.0,
             logic_time: Arc::new(AtomicU64::new(0)),
             render_cpu_time: Arc::new(AtomicU64::new(0)),
             gpu_time: Arc::new(AtomicU64::new(0)),
+            profiler: Profiler::new(),
         }
     }
 
@@ -53,19 +78,24 @@ impl Time {
             let render_cpu_ms = self.render_cpu_time.load(Ordering::Relaxed) as f64 / 1_000_000.0;
             let gpu_ms = self.gpu_time.load(Ordering::Relaxed) as f64 / 1_000_000.0;
 
-            println!(
+            log::debug!(
                 "FPS: {:>6.2} | CPU Logic: {:>6.2}ms | CPU Render: {:>6.2}ms | GPU: {:>6.2}ms",
-                self.fps, logic_ms, render_cpu_ms, gpu_ms
+                self.fps,
+                logic_ms,
+                render_cpu_ms,
+                gpu_ms
             );
         }
 
         self.last_frame_time = now;
     }
 
+    /// Real time between the last two ticks, unaffected by scale and pause.
     pub fn get_delta(&self) -> f64 {
         return self.delta;
     }
 
+    /// Real time since startup.
     pub fn get_elapsed(&self) -> f64 {
         match self.startup_time.elapsed() {
             Ok(elapsed) => elapsed.as_secs_f64(),
@@ -79,4 +109,71 @@ impl Time {
     pub fn get_fps(&self) -> f32 {
         self.fps
     }
+
+    pub fn get_fixed_delta(&self) -> f64 {
+        self.fixed_delta
+    }
+
+    /// Set the number of fixed logic ticks per second.
+    pub fn set_tick_rate(&mut self, tick_rate: f64) {
+        self.fixed_delta = 1.0 / tick_rate.max(1.0);
+    }
+
+    pub fn get_max_catch_up(&self) -> f64 {
+        self.max_catch_up
+    }
+
+    pub fn set_max_catch_up(&mut self, max_catch_up: f64) {
+        self.max_catch_up = max_catch_up.max(self.fixed_delta);
+    }
+
+    /// Advance virtual time by one fixed tick of real time, returning the virtual delta.
+    pub(crate) fn advance_virtual(&mut self, real_delta: f64) -> f64 {
+        self.virtual_delta = if self.paused {
+            0.0
+        } else {
+            real_delta * self.scale
+        };
+        self.virtual_elapsed += self.virtual_delta;
+        self.virtual_delta
+    }
+
+    /// Virtual time passed during the last logic tick. Zero while paused.
+    pub fn get_virtual_delta(&self) -> f64 {
+        self.virtual_delta
+    }
+
+    /// Virtual time passed since startup, excluding pauses.
+    pub fn get_virtual_elapsed(&self) -> f64 {
+        self.virtual_elapsed
+    }
+
+    pub fn get_scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Speed of virtual time relative to real time, e.g. 0.5 for slow motion.
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = scale.max(0.0);
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn get_interpolation_alpha(&self) -> f32 {
+        self.interpolation_alpha
+    }
+
+    pub(crate) fn set_interpolation_alpha(&mut self, alpha: f32) {
+        self.interpolation_alpha = alpha.clamp(0.0, 1.0);
+    }
 }
@@ -3,7 +3,9 @@ pub mod animation;
 pub mod asset;
 pub mod core;
 pub mod math;
+pub mod physics;
 pub mod render;
 pub mod scene;
+pub mod testing;
 pub mod text;
 pub mod window;
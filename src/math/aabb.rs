@@ -1,6 +1,6 @@
 use glam::Vec3;
 
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
 pub struct Aabb {
     pub min: Vec3,
     pub max: Vec3,
@@ -48,8 +48,8 @@ impl Aabb {
         self.transform_by_matrix(&transform.matrix())
     }
 
-    pub fn transform_by_matrix(&self, matrix: &glam::Mat4) -> Self {
-        let corners = [
+    pub fn corners(&self) -> [Vec3; 8] {
+        [
             Vec3::new(self.min.x, self.min.y, self.min.z),
             Vec3::new(self.min.x, self.min.y, self.max.z),
             Vec3::new(self.min.x, self.max.y, self.min.z),
@@ -58,12 +58,14 @@ impl Aabb {
             Vec3::new(self.max.x, self.min.y, self.max.z),
             Vec3::new(self.max.x, self.max.y, self.min.z),
             Vec3::new(self.max.x, self.max.y, self.max.z),
-        ];
+        ]
+    }
 
+    pub fn transform_by_matrix(&self, matrix: &glam::Mat4) -> Self {
         let mut new_min = Vec3::splat(f32::INFINITY);
         let mut new_max = Vec3::splat(f32::NEG_INFINITY);
 
-        for &c in &corners {
+        for c in self.corners() {
             let transformed = matrix.transform_point3(c);
             new_min = new_min.min(transformed);
             new_max = new_max.max(transformed);
@@ -85,4 +87,21 @@ impl Aabb {
     pub fn center(&self) -> Vec3 {
         (self.min + self.max) * 0.5
     }
+
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    pub fn intersects_aabb(&self, other: &Self) -> bool {
+        self.min.cmple(other.max).all() && self.max.cmpge(other.min).all()
+    }
+
+    /// The point in the box nearest to `point`, `point` itself if it is inside.
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        point.clamp(self.min, self.max)
+    }
 }
@@ -1,20 +1,21 @@
 use crate::math::aabb::Aabb;
 use crate::math::frustum::Frustum;
+use crate::math::ray::Ray;
 
 #[derive(Clone)]
-pub enum BvhNode {
+pub enum BvhNode<T = usize> {
     Internal {
         aabb: Aabb,
-        left: Box<BvhNode>,
-        right: Box<BvhNode>,
+        left: Box<BvhNode<T>>,
+        right: Box<BvhNode<T>>,
     },
     Leaf {
         aabb: Aabb,
-        object_indices: Vec<usize>,
+        objects: Vec<T>,
     },
 }
 
-impl BvhNode {
+impl<T> BvhNode<T> {
     pub fn aabb(&self) -> &Aabb {
         match self {
             BvhNode::Internal { aabb, .. } => aabb,
@@ -23,13 +24,21 @@ impl BvhNode {
     }
 }
 
-#[derive(Clone, Default)]
-pub struct Bvh {
-    pub root: Option<BvhNode>,
+/// Bounding volume hierarchy over objects of type `T` (e.g. indices or entities), each with an
+/// AABB.
+#[derive(Clone)]
+pub struct Bvh<T = usize> {
+    pub root: Option<BvhNode<T>>,
+}
+
+impl<T> Default for Bvh<T> {
+    fn default() -> Self {
+        Self { root: None }
+    }
 }
 
-impl Bvh {
-    pub fn build(objects: Vec<(Aabb, usize)>) -> Self {
+impl<T: Clone> Bvh<T> {
+    pub fn build(objects: Vec<(Aabb, T)>) -> Self {
         if objects.is_empty() {
             return Self { root: None };
         }
@@ -37,16 +46,16 @@ impl Bvh {
         Self { root: Some(root) }
     }
 
-    fn build_recursive(mut objects: Vec<(Aabb, usize)>) -> BvhNode {
+    fn build_recursive(mut objects: Vec<(Aabb, T)>) -> BvhNode<T> {
         let mut aabb = objects[0].0;
-        for i in 1..objects.len() {
-            aabb = aabb.union(&objects[i].0);
+        for object in &objects[1..] {
+            aabb = aabb.union(&object.0);
         }
 
         if objects.len() <= 2 {
             return BvhNode::Leaf {
                 aabb,
-                object_indices: objects.into_iter().map(|(_, idx)| idx).collect(),
+                objects: objects.into_iter().map(|(_, object)| object).collect(),
             };
         }
 
@@ -76,25 +85,241 @@ impl Bvh {
         }
     }
 
-    pub fn query(&self, frustum: &Frustum, result: &mut Vec<usize>) {
+    /// Update the node AABBs after objects moved, keeping the tree. Cheaper than a rebuild, but
+    /// queries get slower as objects drift away from where the tree was built.
+    pub fn refit(&mut self, aabb: impl Fn(&T) -> Aabb) {
+        if let Some(root) = &mut self.root {
+            Self::refit_recursive(root, &aabb);
+        }
+    }
+
+    fn refit_recursive(node: &mut BvhNode<T>, object_aabb: &impl Fn(&T) -> Aabb) -> Aabb {
+        let refitted = match node {
+            BvhNode::Internal { aabb, left, right } => {
+                *aabb = Self::refit_recursive(left, object_aabb)
+                    .union(&Self::refit_recursive(right, object_aabb));
+                aabb
+            }
+            BvhNode::Leaf { aabb, objects } => {
+                if let Some((first, rest)) = objects.split_first() {
+                    *aabb = rest.iter().fold(object_aabb(first), |aabb, object| {
+                        aabb.union(&object_aabb(object))
+                    });
+                }
+                aabb
+            }
+        };
+        *refitted
+    }
+
+    /// Objects whose AABB is (at least partly) inside the frustum.
+    pub fn query(&self, frustum: &Frustum, result: &mut Vec<T>) {
+        self.query_with(|aabb| frustum.intersects_aabb(aabb), result);
+    }
+
+    /// Objects whose AABB overlaps `aabb`.
+    pub fn query_aabb(&self, aabb: &Aabb, result: &mut Vec<T>) {
+        self.query_with(|node| node.intersects_aabb(aabb), result);
+    }
+
+    /// Objects whose AABB is hit by the ray.
+    pub fn query_ray(&self, ray: &Ray, result: &mut Vec<T>) {
+        self.query_with(|aabb| ray.intersect_aabb(aabb).is_some(), result);
+    }
+
+    /// Objects in the leaves reached by descending into every node for which `overlaps` returns
+    /// true. `overlaps` must be true for any AABB that contains one for which it is true, e.g.
+    /// `|aabb| sphere.intersects_aabb(aabb)`.
+    pub fn query_with(&self, overlaps: impl Fn(&Aabb) -> bool, result: &mut Vec<T>) {
         if let Some(root) = &self.root {
-            Self::query_recursive(root, frustum, result);
+            Self::query_recursive(root, &overlaps, result);
         }
     }
 
-    fn query_recursive(node: &BvhNode, frustum: &Frustum, result: &mut Vec<usize>) {
-        if !frustum.intersects_aabb(node.aabb()) {
+    fn query_recursive(node: &BvhNode<T>, overlaps: &impl Fn(&Aabb) -> bool, result: &mut Vec<T>) {
+        if !overlaps(node.aabb()) {
             return;
         }
 
         match node {
             BvhNode::Internal { left, right, .. } => {
-                Self::query_recursive(left, frustum, result);
-                Self::query_recursive(right, frustum, result);
+                Self::query_recursive(left, overlaps, result);
+                Self::query_recursive(right, overlaps, result);
             }
-            BvhNode::Leaf { object_indices, .. } => {
-                result.extend(object_indices);
+            BvhNode::Leaf { objects, .. } => {
+                result.extend(objects.iter().cloned());
             }
         }
     }
+
+    /// The nearest object hit by the ray within `max_distance`. `hit` returns the distance at
+    /// which an object is hit, nodes farther than the nearest hit so far are skipped.
+    pub fn ray_cast(
+        &self,
+        ray: &Ray,
+        max_distance: f32,
+        mut hit: impl FnMut(&T) -> Option<f32>,
+    ) -> Option<(T, f32)> {
+        let mut nearest = None;
+        if let Some(root) = &self.root {
+            Self::ray_cast_recursive(root, ray, max_distance, &mut hit, &mut nearest);
+        }
+        nearest
+    }
+
+    fn ray_cast_recursive(
+        node: &BvhNode<T>,
+        ray: &Ray,
+        max_distance: f32,
+        hit: &mut impl FnMut(&T) -> Option<f32>,
+        nearest: &mut Option<(T, f32)>,
+    ) {
+        let limit = nearest.as_ref().map_or(max_distance, |(_, d)| *d);
+        if ray.intersect_aabb(node.aabb()).is_none_or(|d| d > limit) {
+            return;
+        }
+
+        match node {
+            BvhNode::Internal { left, right, .. } => {
+                // 先进入较近的子节点，找到命中后可跳过较远的子节点
+                let left_distance = ray.intersect_aabb(left.aabb()).unwrap_or(f32::INFINITY);
+                let right_distance = ray.intersect_aabb(right.aabb()).unwrap_or(f32::INFINITY);
+                let (first, second) = if left_distance <= right_distance {
+                    (left, right)
+                } else {
+                    (right, left)
+                };
+                Self::ray_cast_recursive(first, ray, max_distance, hit, nearest);
+                Self::ray_cast_recursive(second, ray, max_distance, hit, nearest);
+            }
+            BvhNode::Leaf { objects, .. } => {
+                for object in objects {
+                    let Some(distance) = hit(object) else {
+                        continue;
+                    };
+                    let closer = nearest.as_ref().is_none_or(|(_, d)| distance < *d);
+                    if closer && distance <= max_distance {
+                        *nearest = Some((object.clone(), distance));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec3;
+
+    /// Unit cubes centered on the X axis at the given positions.
+    fn cubes(positions: &[f32]) -> Vec<Aabb> {
+        positions
+            .iter()
+            .map(|x| Aabb::new(Vec3::new(x - 0.5, -0.5, -0.5), Vec3::new(x + 0.5, 0.5, 0.5)))
+            .collect()
+    }
+
+    fn build(aabbs: &[Aabb]) -> Bvh {
+        Bvh::build(
+            aabbs
+                .iter()
+                .copied()
+                .enumerate()
+                .map(|(i, aabb)| (aabb, i))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn ray_cast_finds_the_nearest_object() {
+        let aabbs = cubes(&[0.0, 3.0, 6.0, 9.0, 12.0]);
+        let bvh = build(&aabbs);
+        let hit =
+            |i: &usize| Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X).intersect_aabb(&aabbs[*i]);
+
+        let ray = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X);
+        assert_eq!(bvh.ray_cast(&ray, f32::INFINITY, hit), Some((0, 4.5)));
+
+        let back = Ray::new(Vec3::new(20.0, 0.0, 0.0), -Vec3::X);
+        let result = bvh.ray_cast(&back, f32::INFINITY, |i| back.intersect_aabb(&aabbs[*i]));
+        assert_eq!(result, Some((4, 7.5)));
+    }
+
+    #[test]
+    fn ray_cast_skips_objects_the_callback_misses() {
+        let aabbs = cubes(&[0.0, 3.0, 6.0]);
+        let bvh = build(&aabbs);
+        let ray = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X);
+
+        // 包围盒被击中，但物体本身没有，例如三角形之间的空隙
+        let result = bvh.ray_cast(&ray, f32::INFINITY, |i| {
+            (*i != 0).then(|| ray.intersect_aabb(&aabbs[*i])).flatten()
+        });
+        assert_eq!(result, Some((1, 7.5)));
+    }
+
+    #[test]
+    fn ray_cast_misses_past_max_distance_and_beside_the_objects() {
+        let aabbs = cubes(&[0.0, 3.0]);
+        let bvh = build(&aabbs);
+        let ray = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X);
+        assert_eq!(
+            bvh.ray_cast(&ray, 4.0, |i| ray.intersect_aabb(&aabbs[*i])),
+            None
+        );
+
+        let beside = Ray::new(Vec3::new(-5.0, 2.0, 0.0), Vec3::X);
+        assert_eq!(
+            bvh.ray_cast(&beside, f32::INFINITY, |i| beside
+                .intersect_aabb(&aabbs[*i])),
+            None
+        );
+
+        let empty = Bvh::<usize>::default();
+        assert_eq!(empty.ray_cast(&ray, f32::INFINITY, |_| Some(0.0)), None);
+    }
+
+    #[test]
+    fn ray_cast_starting_inside_an_object() {
+        let aabbs = cubes(&[0.0, 3.0, 6.0]);
+        let bvh = build(&aabbs);
+        let ray = Ray::new(Vec3::new(3.0, 0.0, 0.0), Vec3::X);
+        let result = bvh.ray_cast(&ray, f32::INFINITY, |i| ray.intersect_aabb(&aabbs[*i]));
+        assert_eq!(result, Some((1, 0.0)));
+    }
+
+    #[test]
+    fn refit_follows_moved_objects() {
+        let mut aabbs = cubes(&[0.0, 3.0, 6.0, 9.0]);
+        let mut bvh = build(&aabbs);
+
+        aabbs[0] = cubes(&[20.0])[0];
+        bvh.refit(|i| aabbs[*i]);
+
+        let root = *bvh.root.as_ref().unwrap().aabb();
+        assert_eq!(root.max.x, 20.5);
+        assert_eq!(root.min.x, 2.5);
+
+        let ray = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X);
+        let result = bvh.ray_cast(&ray, f32::INFINITY, |i| ray.intersect_aabb(&aabbs[*i]));
+        assert_eq!(result, Some((1, 7.5)));
+
+        let back = Ray::new(Vec3::new(30.0, 0.0, 0.0), -Vec3::X);
+        let result = bvh.ray_cast(&back, f32::INFINITY, |i| back.intersect_aabb(&aabbs[*i]));
+        assert_eq!(result, Some((0, 9.5)));
+
+        let mut found = Vec::new();
+        bvh.query_aabb(&cubes(&[20.0])[0], &mut found);
+        // 返回整个叶子中的物体，另一个叶子不再被访问
+        assert!(found.contains(&0));
+        assert!(!found.contains(&2) && !found.contains(&3));
+    }
+
+    #[test]
+    fn refit_of_an_empty_tree_does_nothing() {
+        let mut bvh = Bvh::<usize>::default();
+        bvh.refit(|_| unreachable!());
+        assert!(bvh.root.is_none());
+    }
 }
@@ -0,0 +1,297 @@
+use crate::math::aabb::Aabb;
+use crate::math::obb::Obb;
+use crate::math::sphere::Sphere;
+use glam::Vec3;
+
+/// Points within `radius` of the segment from `start` to `end`.
+#[derive(Debug, Copy, Clone)]
+pub struct Capsule {
+    pub start: Vec3,
+    pub end: Vec3,
+    pub radius: f32,
+}
+
+impl Capsule {
+    pub fn new(start: Vec3, end: Vec3, radius: f32) -> Self {
+        Self { start, end, radius }
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        Aabb::new(
+            self.start.min(self.end) - Vec3::splat(self.radius),
+            self.start.max(self.end) + Vec3::splat(self.radius),
+        )
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        let on_segment = closest_point_on_segment(self.start, self.end, point);
+        on_segment.distance_squared(point) <= self.radius * self.radius
+    }
+
+    /// The point in the capsule nearest to `point`, `point` itself if it is inside.
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        let on_segment = closest_point_on_segment(self.start, self.end, point);
+        Sphere::new(on_segment, self.radius).closest_point(point)
+    }
+
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+        let on_segment = closest_point_on_segment(self.start, self.end, sphere.center);
+        Sphere::new(on_segment, self.radius).intersects_sphere(sphere)
+    }
+
+    pub fn intersects_capsule(&self, other: &Capsule) -> bool {
+        let (a, b) = closest_points_between_segments(self.start, self.end, other.start, other.end);
+        let radius = self.radius + other.radius;
+        a.distance_squared(b) <= radius * radius
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        let center = aabb.center();
+        let distance_squared = segment_box_distance_squared(
+            self.start - center,
+            self.end - center,
+            aabb.half_extents(),
+        );
+        distance_squared <= self.radius * self.radius
+    }
+
+    pub fn intersects_obb(&self, obb: &Obb) -> bool {
+        // 在盒子的局部空间中计算
+        let inv_rotation = obb.rotation.inverse();
+        let start = inv_rotation * (self.start - obb.center);
+        let end = inv_rotation * (self.end - obb.center);
+        segment_box_distance_squared(start, end, obb.half_extents) <= self.radius * self.radius
+    }
+}
+
+/// Squared distance between the segment `a`-`b` and a box centered at the origin.
+fn segment_box_distance_squared(a: Vec3, b: Vec3, half_extents: Vec3) -> f32 {
+    // 线段穿过 slab 平面的位置把它分成几段，每段上距离的平方是二次函数，分别求最小值
+    let d = b - a;
+    let mut breaks = vec![0.0, 1.0];
+    for axis in 0..3 {
+        if d[axis] != 0.0 {
+            for plane in [-half_extents[axis], half_extents[axis]] {
+                let t = (plane - a[axis]) / d[axis];
+                if t > 0.0 && t < 1.0 {
+                    breaks.push(t);
+                }
+            }
+        }
+    }
+    breaks.sort_by(|x, y| x.total_cmp(y));
+
+    let distance_squared = |t: f32| {
+        let point = a + d * t;
+        point.distance_squared(point.clamp(-half_extents, half_extents))
+    };
+
+    let mut nearest = distance_squared(0.0).min(distance_squared(1.0));
+    for window in breaks.windows(2) {
+        let (t0, t1) = (window[0], window[1]);
+        let middle = a + d * ((t0 + t1) * 0.5);
+
+        // 该段上在盒子外的轴固定，距离为这些轴上超出部分的平方和
+        let mut numerator = 0.0;
+        let mut denominator = 0.0;
+        for axis in 0..3 {
+            let plane = if middle[axis] > half_extents[axis] {
+                half_extents[axis]
+            } else if middle[axis] < -half_extents[axis] {
+                -half_extents[axis]
+            } else {
+                continue;
+            };
+            numerator -= (a[axis] - plane) * d[axis];
+            denominator += d[axis] * d[axis];
+        }
+
+        if denominator > 0.0 {
+            let t = (numerator / denominator).clamp(t0, t1);
+            nearest = nearest.min(distance_squared(t));
+        }
+    }
+    nearest
+}
+
+/// The point on the segment `a`-`b` nearest to `point`.
+pub fn closest_point_on_segment(a: Vec3, b: Vec3, point: Vec3) -> Vec3 {
+    let ab = b - a;
+    let length_squared = ab.length_squared();
+    if length_squared <= f32::EPSILON {
+        return a;
+    }
+    let t = ((point - a).dot(ab) / length_squared).clamp(0.0, 1.0);
+    a + ab * t
+}
+
+/// The nearest pair of points on the segments `p1`-`q1` and `p2`-`q2`, one on each.
+pub fn closest_points_between_segments(p1: Vec3, q1: Vec3, p2: Vec3, q2: Vec3) -> (Vec3, Vec3) {
+    // Ericson, Real-Time Collision Detection 5.1.9
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = d1.length_squared();
+    let e = d2.length_squared();
+    let f = d2.dot(r);
+
+    if a <= f32::EPSILON && e <= f32::EPSILON {
+        return (p1, p2);
+    }
+
+    let (s, t) = if a <= f32::EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(r);
+        if e <= f32::EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(d2);
+            let denom = a * e - b * b;
+            // 平行时任取一点
+            let mut s = if denom > f32::EPSILON {
+                ((b * f - c * e) / denom).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let mut t = (b * s + f) / e;
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / a).clamp(0.0, 1.0);
+            }
+            (s, t)
+        }
+    };
+
+    (p1 + d1 * s, p2 + d2 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Quat;
+    use std::f32::consts::FRAC_PI_4;
+
+    fn vertical(x: f32, z: f32, radius: f32) -> Capsule {
+        Capsule::new(Vec3::new(x, -1.0, z), Vec3::new(x, 1.0, z), radius)
+    }
+
+    fn unit_box() -> Aabb {
+        Aabb::new(Vec3::splat(-1.0), Vec3::splat(1.0))
+    }
+
+    #[test]
+    fn contains_point_along_the_segment_and_around_the_ends() {
+        let capsule = vertical(0.0, 0.0, 0.5);
+        assert!(capsule.contains_point(Vec3::new(0.4, 0.9, 0.0)));
+        assert!(capsule.contains_point(Vec3::new(0.0, 1.5, 0.0)));
+        assert!(!capsule.contains_point(Vec3::new(0.4, 1.4, 0.0)));
+        assert_eq!(
+            capsule.closest_point(Vec3::new(2.0, 0.0, 0.0)),
+            Vec3::new(0.5, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn capsules_parallel_crossing_and_apart() {
+        let capsule = vertical(0.0, 0.0, 0.5);
+        // 平行的线段
+        assert!(capsule.intersects_capsule(&vertical(1.0, 0.0, 0.5)));
+        assert!(!capsule.intersects_capsule(&vertical(1.0, 0.0, 0.4)));
+
+        // 异面交叉的线段相距 1
+        let crossing =
+            |radius| Capsule::new(Vec3::new(-1.0, 0.0, 1.0), Vec3::new(1.0, 0.0, 1.0), radius);
+        assert!(capsule.intersects_capsule(&crossing(0.5)));
+        assert!(!capsule.intersects_capsule(&crossing(0.4)));
+
+        // 端点之间
+        let above = Capsule::new(Vec3::new(0.0, 2.0, 0.0), Vec3::new(0.0, 3.0, 0.0), 0.5);
+        assert!(capsule.intersects_capsule(&above));
+        assert!(!vertical(0.0, 0.0, 0.4).intersects_capsule(&above));
+    }
+
+    #[test]
+    fn capsules_with_equal_ends_are_spheres() {
+        let point =
+            |x, radius| Capsule::new(Vec3::new(x, 0.0, 0.0), Vec3::new(x, 0.0, 0.0), radius);
+        assert!(point(0.0, 0.6).intersects_capsule(&point(1.0, 0.4)));
+        assert!(!point(0.0, 0.5).intersects_capsule(&point(1.0, 0.4)));
+        assert!(point(0.0, 0.6).intersects_sphere(&Sphere::new(Vec3::X, 0.4)));
+        assert_eq!(
+            closest_point_on_segment(Vec3::X, Vec3::X, Vec3::ZERO),
+            Vec3::X
+        );
+    }
+
+    #[test]
+    fn capsule_against_sphere() {
+        let capsule = vertical(0.0, 0.0, 0.5);
+        assert!(capsule.intersects_sphere(&Sphere::new(Vec3::new(0.0, 2.0, 0.0), 0.5)));
+        assert!(!capsule.intersects_sphere(&Sphere::new(Vec3::new(0.0, 2.1, 0.0), 0.5)));
+        assert!(capsule.intersects_sphere(&Sphere::new(Vec3::new(0.9, 0.0, 0.0), 0.5)));
+    }
+
+    #[test]
+    fn capsule_against_aabb() {
+        // 线段穿过盒子
+        let through = Capsule::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(5.0, 0.0, 0.0), 0.0);
+        assert!(through.intersects_aabb(&unit_box()));
+
+        // 与面平行
+        let beside =
+            |radius| Capsule::new(Vec3::new(-5.0, 1.5, 0.0), Vec3::new(5.0, 1.5, 0.0), radius);
+        assert!(beside(0.6).intersects_aabb(&unit_box()));
+        assert!(!beside(0.4).intersects_aabb(&unit_box()));
+
+        // 与棱平行，相距 √2
+        let along_edge =
+            |radius| Capsule::new(Vec3::new(2.0, 2.0, -5.0), Vec3::new(2.0, 2.0, 5.0), radius);
+        assert!(along_edge(1.5).intersects_aabb(&unit_box()));
+        assert!(!along_edge(1.3).intersects_aabb(&unit_box()));
+
+        // 斜着经过角，最近点在线段中间
+        let past_corner =
+            |radius| Capsule::new(Vec3::new(3.0, 1.0, 0.0), Vec3::new(1.0, 3.0, 0.0), radius);
+        assert!(past_corner(1.5).intersects_aabb(&unit_box()));
+        assert!(!past_corner(1.3).intersects_aabb(&unit_box()));
+
+        // 端点
+        let end = |radius| Capsule::new(Vec3::new(3.0, 0.0, 0.0), Vec3::new(5.0, 0.0, 0.0), radius);
+        assert!(end(2.0).intersects_aabb(&unit_box()));
+        assert!(!end(1.9).intersects_aabb(&unit_box()));
+    }
+
+    #[test]
+    fn capsule_against_obb() {
+        let obb = Obb::new(Vec3::ZERO, Vec3::ONE, Quat::from_rotation_z(FRAC_PI_4));
+        let line =
+            |radius| Capsule::new(Vec3::new(1.5, 0.0, -5.0), Vec3::new(1.5, 0.0, 5.0), radius);
+        assert!(line(0.2).intersects_obb(&obb));
+        assert!(!line(0.05).intersects_obb(&obb));
+        // 不旋转时距离为 0.5
+        assert!(!line(0.2).intersects_aabb(&unit_box()));
+    }
+
+    #[test]
+    fn closest_points_between_parallel_and_degenerate_segments() {
+        let (a, b) = closest_points_between_segments(
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+        );
+        assert!((a.distance(b) - 1.0).abs() < 1e-5);
+
+        let (a, b) = closest_points_between_segments(
+            Vec3::ZERO,
+            Vec3::ZERO,
+            Vec3::new(-1.0, 1.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+        );
+        assert_eq!((a, b), (Vec3::ZERO, Vec3::Y));
+    }
+}
@@ -1,6 +1,7 @@
 use glam::Vec3;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ColorU {
     pub r: u8,
     pub g: u8,
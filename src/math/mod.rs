@@ -1,8 +1,13 @@
 pub mod aabb;
-pub(crate) mod bvh;
+pub mod bvh;
+pub mod capsule;
 pub mod color;
 pub mod frustum;
+pub mod obb;
+pub mod ray;
+pub mod sphere;
 pub mod transform;
+pub mod triangle;
 
 use allsorts::pathfinder_geometry::rect::RectF;
 use glam::Vec4;
@@ -0,0 +1,214 @@
+use crate::math::aabb::Aabb;
+use crate::math::sphere::Sphere;
+use crate::math::transform::Transform3d;
+use glam::{Mat3, Quat, Vec3};
+
+/// Oriented bounding box.
+#[derive(Debug, Copy, Clone)]
+pub struct Obb {
+    pub center: Vec3,
+    pub half_extents: Vec3,
+    pub rotation: Quat,
+}
+
+impl Obb {
+    pub fn new(center: Vec3, half_extents: Vec3, rotation: Quat) -> Self {
+        Self {
+            center,
+            half_extents,
+            rotation,
+        }
+    }
+
+    /// A local space box placed by `transform`. Unlike [`Aabb::transform`] the result stays
+    /// tight under rotation.
+    pub fn from_aabb(aabb: &Aabb, transform: &Transform3d) -> Self {
+        Self {
+            center: transform.matrix().transform_point3(aabb.center()),
+            half_extents: aabb.half_extents() * transform.scale.abs(),
+            rotation: transform.rotation,
+        }
+    }
+
+    /// Local X, Y and Z axes in world space.
+    pub fn axes(&self) -> [Vec3; 3] {
+        let m = Mat3::from_quat(self.rotation);
+        [m.x_axis, m.y_axis, m.z_axis]
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        let [x, y, z] = self.axes();
+        let extent = x.abs() * self.half_extents.x
+            + y.abs() * self.half_extents.y
+            + z.abs() * self.half_extents.z;
+        Aabb::new(self.center - extent, self.center + extent)
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        let local = self.rotation.inverse() * (point - self.center);
+        local.abs().cmple(self.half_extents).all()
+    }
+
+    /// The point in the box nearest to `point`, `point` itself if it is inside.
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        let local = self.rotation.inverse() * (point - self.center);
+        self.center + self.rotation * local.clamp(-self.half_extents, self.half_extents)
+    }
+
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+        sphere.contains_point(self.closest_point(sphere.center))
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.intersects_obb(&Obb::new(
+            aabb.center(),
+            aabb.half_extents(),
+            Quat::IDENTITY,
+        ))
+    }
+
+    /// Separating axis test over the face axes of both boxes and their cross products.
+    pub fn intersects_obb(&self, other: &Obb) -> bool {
+        let a = self.axes();
+        let b = other.axes();
+        let ea = self.half_extents.to_array();
+        let eb = other.half_extents.to_array();
+
+        // other 的轴在 self 局部空间中的表示
+        let mut r = [[0.0; 3]; 3];
+        let mut abs_r = [[0.0; 3]; 3];
+        for i in 0..3 {
+            for j in 0..3 {
+                r[i][j] = a[i].dot(b[j]);
+                // 加上 epsilon，防止两轴平行时叉积接近 0 导致误判
+                abs_r[i][j] = r[i][j].abs() + 1e-6;
+            }
+        }
+
+        let offset = other.center - self.center;
+        let t = [offset.dot(a[0]), offset.dot(a[1]), offset.dot(a[2])];
+
+        for i in 0..3 {
+            let rb = eb[0] * abs_r[i][0] + eb[1] * abs_r[i][1] + eb[2] * abs_r[i][2];
+            if t[i].abs() > ea[i] + rb {
+                return false;
+            }
+        }
+
+        for j in 0..3 {
+            let ra = ea[0] * abs_r[0][j] + ea[1] * abs_r[1][j] + ea[2] * abs_r[2][j];
+            let t_b = t[0] * r[0][j] + t[1] * r[1][j] + t[2] * r[2][j];
+            if t_b.abs() > ra + eb[j] {
+                return false;
+            }
+        }
+
+        for i in 0..3 {
+            let (i1, i2) = ((i + 1) % 3, (i + 2) % 3);
+            for j in 0..3 {
+                let (j1, j2) = ((j + 1) % 3, (j + 2) % 3);
+                let ra = ea[i1] * abs_r[i2][j] + ea[i2] * abs_r[i1][j];
+                let rb = eb[j1] * abs_r[i][j2] + eb[j2] * abs_r[i][j1];
+                let t_axis = t[i2] * r[i1][j] - t[i1] * r[i2][j];
+                if t_axis.abs() > ra + rb {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_4, SQRT_2};
+
+    fn diamond() -> Obb {
+        Obb::new(Vec3::ZERO, Vec3::ONE, Quat::from_rotation_z(FRAC_PI_4))
+    }
+
+    #[test]
+    fn contains_point_in_local_space() {
+        let obb = diamond();
+        assert!(obb.contains_point(Vec3::new(1.3, 0.0, 0.0)));
+        assert!(!obb.contains_point(Vec3::new(0.9, 0.9, 0.0)));
+    }
+
+    #[test]
+    fn aabb_encloses_the_rotated_box() {
+        let aabb = diamond().aabb();
+        assert!(aabb.max.abs_diff_eq(Vec3::new(SQRT_2, SQRT_2, 1.0), 1e-5));
+        assert!(aabb.min.abs_diff_eq(-Vec3::new(SQRT_2, SQRT_2, 1.0), 1e-5));
+    }
+
+    #[test]
+    fn from_aabb_applies_the_transform() {
+        let aabb = Aabb::new(Vec3::ZERO, Vec3::new(2.0, 2.0, 2.0));
+        let transform = Transform3d {
+            position: Vec3::new(1.0, 0.0, 0.0),
+            rotation: Quat::IDENTITY,
+            scale: Vec3::new(2.0, -1.0, 1.0),
+        };
+        let obb = Obb::from_aabb(&aabb, &transform);
+        assert!(obb.center.abs_diff_eq(Vec3::new(3.0, -1.0, 1.0), 1e-5));
+        assert_eq!(obb.half_extents, Vec3::new(2.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn boxes_touching_overlapping_and_apart() {
+        let obb = Obb::new(Vec3::ZERO, Vec3::ONE, Quat::IDENTITY);
+        // 平行的轴，叉积为 0
+        assert!(obb.intersects_obb(&obb));
+        assert!(obb.intersects_obb(&Obb::new(
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::ONE,
+            Quat::IDENTITY
+        )));
+        assert!(!obb.intersects_obb(&Obb::new(
+            Vec3::new(2.1, 0.0, 0.0),
+            Vec3::ONE,
+            Quat::IDENTITY
+        )));
+
+        // 旋转后的盒子伸出 √2
+        let rotated = |x| Obb::new(Vec3::new(x, 0.0, 0.0), Vec3::ONE, diamond().rotation);
+        assert!(obb.intersects_obb(&rotated(2.3)));
+        assert!(!obb.intersects_obb(&rotated(2.5)));
+    }
+
+    #[test]
+    fn boxes_separated_only_by_an_edge_axis() {
+        // 两条棱交叉，棱相距 2.9 - 2√2，6 个面轴上的投影都重叠
+        let a = Obb::new(Vec3::ZERO, Vec3::ONE, Quat::from_rotation_x(FRAC_PI_4));
+        let b = |z| {
+            Obb::new(
+                Vec3::new(0.0, 0.0, z),
+                Vec3::ONE,
+                Quat::from_rotation_y(FRAC_PI_4),
+            )
+        };
+        assert!(!a.intersects_obb(&b(2.9)));
+        assert!(a.intersects_obb(&b(2.7)));
+    }
+
+    #[test]
+    fn box_with_zero_extents_is_a_point() {
+        let point = Obb::new(Vec3::new(0.5, 0.5, 0.5), Vec3::ZERO, diamond().rotation);
+        assert!(point.contains_point(Vec3::new(0.5, 0.5, 0.5)));
+        assert!(point.intersects_obb(&Obb::new(Vec3::ZERO, Vec3::ONE, Quat::IDENTITY)));
+        assert!(!point.intersects_obb(&Obb::new(Vec3::splat(2.0), Vec3::ONE, Quat::IDENTITY)));
+    }
+
+    #[test]
+    fn box_against_sphere_and_aabb() {
+        let obb = diamond();
+        assert!(obb.intersects_sphere(&Sphere::new(Vec3::new(1.8, 0.0, 0.0), 0.5)));
+        assert!(!obb.intersects_sphere(&Sphere::new(Vec3::new(1.8, 0.0, 0.0), 0.3)));
+
+        let aabb = |x| Aabb::new(Vec3::new(x, -0.1, -0.1), Vec3::new(x + 1.0, 0.1, 0.1));
+        assert!(obb.intersects_aabb(&aabb(1.3)));
+        assert!(!obb.intersects_aabb(&aabb(1.5)));
+    }
+}
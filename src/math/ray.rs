@@ -0,0 +1,338 @@
+use crate::math::aabb::Aabb;
+use crate::math::capsule::Capsule;
+use crate::math::obb::Obb;
+use crate::math::sphere::Sphere;
+use crate::math::triangle::Triangle;
+use glam::{Mat4, Vec3};
+
+#[derive(Debug, Copy, Clone)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl Ray {
+    /// `direction` is normalized, so hit distances are in world units.
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    /// The point on the ray nearest to `point`.
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        let t = (point - self.origin).dot(self.direction) / self.direction.length_squared();
+        self.at(t.max(0.0))
+    }
+
+    /// The direction is not normalized again, so distances along the transformed ray match the
+    /// ones along this ray. Used to test against geometry in its local space.
+    pub fn transform_by_matrix(&self, matrix: &Mat4) -> Self {
+        Self {
+            origin: matrix.transform_point3(self.origin),
+            direction: matrix.transform_vector3(self.direction),
+        }
+    }
+
+    /// Distance to where the ray enters the box, 0 if it starts inside.
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        // Slab 测试
+        let mut t_near = f32::NEG_INFINITY;
+        let mut t_far = f32::INFINITY;
+        for axis in 0..3 {
+            let (origin, direction) = (self.origin[axis], self.direction[axis]);
+            // 与 slab 平行时起点必须在 slab 内，起点在 slab 平面上时 0 × ∞ 会得到 NaN
+            if direction == 0.0 {
+                if origin < aabb.min[axis] || origin > aabb.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+
+            let inv_dir = direction.recip();
+            let t0 = (aabb.min[axis] - origin) * inv_dir;
+            let t1 = (aabb.max[axis] - origin) * inv_dir;
+            t_near = t_near.max(t0.min(t1));
+            t_far = t_far.min(t0.max(t1));
+        }
+
+        (t_near <= t_far && t_far >= 0.0).then_some(t_near.max(0.0))
+    }
+
+    /// Distance to where the ray enters the box, 0 if it starts inside.
+    pub fn intersect_obb(&self, obb: &Obb) -> Option<f32> {
+        // 在盒子的局部空间中做 slab 测试，旋转不改变距离
+        let inv_rotation = obb.rotation.inverse();
+        let local = Self {
+            origin: inv_rotation * (self.origin - obb.center),
+            direction: inv_rotation * self.direction,
+        };
+        local.intersect_aabb(&Aabb::new(-obb.half_extents, obb.half_extents))
+    }
+
+    /// Distance to where the ray enters the sphere, 0 if it starts inside.
+    pub fn intersect_sphere(&self, sphere: &Sphere) -> Option<f32> {
+        let m = self.origin - sphere.center;
+        let a = self.direction.length_squared();
+        let b = m.dot(self.direction);
+        let c = m.length_squared() - sphere.radius * sphere.radius;
+        if c <= 0.0 {
+            return Some(0.0);
+        }
+        // 起点在球外且背离球心
+        if b > 0.0 {
+            return None;
+        }
+
+        let discriminant = b * b - a * c;
+        (discriminant >= 0.0).then(|| (-b - discriminant.sqrt()) / a)
+    }
+
+    /// Distance to where the ray enters the capsule, 0 if it starts inside.
+    pub fn intersect_capsule(&self, capsule: &Capsule) -> Option<f32> {
+        if capsule.contains_point(self.origin) {
+            return Some(0.0);
+        }
+
+        // 两端的球
+        let caps = [capsule.start, capsule.end]
+            .into_iter()
+            .filter_map(|center| self.intersect_sphere(&Sphere::new(center, capsule.radius)));
+
+        // 中间的圆柱面，Ericson 5.3.7
+        let d = capsule.end - capsule.start;
+        let m = self.origin - capsule.start;
+        let n = self.direction;
+        let dd = d.length_squared();
+        let md = m.dot(d);
+        let nd = n.dot(d);
+        let a = dd * n.length_squared() - nd * nd;
+        let b = dd * m.dot(n) - nd * md;
+        let c = dd * (m.length_squared() - capsule.radius * capsule.radius) - md * md;
+
+        let discriminant = b * b - a * c;
+        // 与轴平行时只会碰到两端的球
+        let side = (a.abs() > f32::EPSILON && discriminant >= 0.0)
+            .then(|| (-b - discriminant.sqrt()) / a)
+            .filter(|t| *t >= 0.0 && (0.0..=dd).contains(&(md + t * nd)));
+
+        caps.chain(side).min_by(|a, b| a.total_cmp(b))
+    }
+
+    /// Möller–Trumbore, both sides of the triangle are hit.
+    pub fn intersect_triangle(&self, triangle: &Triangle) -> Option<f32> {
+        let Triangle { a, b, c } = *triangle;
+        let edge1 = b - a;
+        let edge2 = c - a;
+        let p = self.direction.cross(edge2);
+        let det = edge1.dot(p);
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let inv_det = 1.0 / det;
+        let s = self.origin - a;
+        let u = s.dot(p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = s.cross(edge1);
+        let v = self.direction.dot(q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = edge2.dot(q) * inv_det;
+        (t >= 0.0).then_some(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Quat;
+    use std::f32::consts::{FRAC_PI_4, SQRT_2};
+
+    fn assert_distance(actual: Option<f32>, expected: f32) {
+        let actual = actual.expect("expected a hit");
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "hit at {actual}, expected {expected}"
+        );
+    }
+
+    fn unit_box() -> Aabb {
+        Aabb::new(Vec3::splat(-1.0), Vec3::splat(1.0))
+    }
+
+    #[test]
+    fn aabb_hit_miss_and_inside() {
+        let ray = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X);
+        assert_distance(ray.intersect_aabb(&unit_box()), 4.0);
+
+        let diagonal = Ray::new(Vec3::new(-5.0, -5.0, 0.0), Vec3::new(1.0, 1.0, 0.0));
+        assert_distance(diagonal.intersect_aabb(&unit_box()), 4.0 * SQRT_2);
+
+        let away = Ray::new(Vec3::new(-5.0, 0.0, 0.0), -Vec3::X);
+        assert_eq!(away.intersect_aabb(&unit_box()), None);
+
+        let inside = Ray::new(Vec3::new(0.5, 0.0, 0.0), Vec3::Y);
+        assert_eq!(inside.intersect_aabb(&unit_box()), Some(0.0));
+    }
+
+    #[test]
+    fn aabb_parallel_to_a_slab() {
+        // 与 y slab 平行，起点在 slab 外
+        let outside = Ray::new(Vec3::new(-5.0, 2.0, 0.0), Vec3::X);
+        assert_eq!(outside.intersect_aabb(&unit_box()), None);
+
+        // 起点正好在 slab 平面上，擦过盒子的面
+        let grazing = Ray::new(Vec3::new(-5.0, 1.0, 0.0), Vec3::X);
+        assert_distance(grazing.intersect_aabb(&unit_box()), 4.0);
+    }
+
+    #[test]
+    fn obb_rotation_changes_the_hit() {
+        let obb = Obb::new(Vec3::ZERO, Vec3::ONE, Quat::from_rotation_z(FRAC_PI_4));
+
+        let ray = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X);
+        assert_distance(ray.intersect_obb(&obb), 5.0 - SQRT_2);
+
+        // 不旋转的盒子在 y = 1.3 处不会被击中
+        let above = Ray::new(Vec3::new(-5.0, 1.3, 0.0), Vec3::X);
+        assert_eq!(above.intersect_aabb(&unit_box()), None);
+        assert_distance(above.intersect_obb(&obb), 5.0 - (SQRT_2 - 1.3));
+
+        let miss = Ray::new(Vec3::new(-5.0, 1.5, 0.0), Vec3::X);
+        assert_eq!(miss.intersect_obb(&obb), None);
+
+        let inside = Ray::new(Vec3::new(0.2, 0.2, 0.0), Vec3::Z);
+        assert_eq!(inside.intersect_obb(&obb), Some(0.0));
+    }
+
+    #[test]
+    fn sphere_hit_tangent_miss_and_inside() {
+        let sphere = Sphere::new(Vec3::ZERO, 1.0);
+
+        let ray = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X);
+        assert_distance(ray.intersect_sphere(&sphere), 4.0);
+
+        let tangent = Ray::new(Vec3::new(-5.0, 1.0, 0.0), Vec3::X);
+        assert_distance(tangent.intersect_sphere(&sphere), 5.0);
+
+        let miss = Ray::new(Vec3::new(-5.0, 1.5, 0.0), Vec3::X);
+        assert_eq!(miss.intersect_sphere(&sphere), None);
+
+        let behind = Ray::new(Vec3::new(5.0, 0.0, 0.0), Vec3::X);
+        assert_eq!(behind.intersect_sphere(&sphere), None);
+
+        let inside = Ray::new(Vec3::new(0.5, 0.0, 0.0), Vec3::X);
+        assert_eq!(inside.intersect_sphere(&sphere), Some(0.0));
+    }
+
+    #[test]
+    fn sphere_with_zero_radius_is_hit_through_its_center() {
+        let point = Sphere::new(Vec3::ZERO, 0.0);
+
+        let ray = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X);
+        assert_distance(ray.intersect_sphere(&point), 5.0);
+
+        let beside = Ray::new(Vec3::new(-5.0, 0.1, 0.0), Vec3::X);
+        assert_eq!(beside.intersect_sphere(&point), None);
+    }
+
+    #[test]
+    fn capsule_side_caps_miss_and_inside() {
+        let capsule = Capsule::new(Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0), 0.5);
+
+        let side = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X);
+        assert_distance(side.intersect_capsule(&capsule), 4.5);
+
+        // 在线段范围外，只会击中端部的球
+        let cap = Ray::new(Vec3::new(-5.0, 1.3, 0.0), Vec3::X);
+        assert_distance(cap.intersect_capsule(&capsule), 4.6);
+
+        let miss = Ray::new(Vec3::new(-5.0, 0.0, 1.0), Vec3::X);
+        assert_eq!(miss.intersect_capsule(&capsule), None);
+
+        let past_end = Ray::new(Vec3::new(-5.0, 2.0, 0.0), Vec3::X);
+        assert_eq!(past_end.intersect_capsule(&capsule), None);
+
+        let inside = Ray::new(Vec3::new(0.0, 0.5, 0.2), Vec3::X);
+        assert_eq!(inside.intersect_capsule(&capsule), Some(0.0));
+    }
+
+    #[test]
+    fn capsule_parallel_to_its_axis_hits_the_nearer_cap() {
+        let capsule = Capsule::new(Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0), 0.5);
+
+        let down = Ray::new(Vec3::new(0.0, 5.0, 0.0), -Vec3::Y);
+        assert_distance(down.intersect_capsule(&capsule), 3.5);
+
+        let beside = Ray::new(Vec3::new(1.0, 5.0, 0.0), -Vec3::Y);
+        assert_eq!(beside.intersect_capsule(&capsule), None);
+    }
+
+    #[test]
+    fn capsule_with_equal_ends_is_a_sphere() {
+        let capsule = Capsule::new(Vec3::ZERO, Vec3::ZERO, 0.5);
+
+        let ray = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X);
+        assert_distance(ray.intersect_capsule(&capsule), 4.5);
+
+        let miss = Ray::new(Vec3::new(-5.0, 1.0, 0.0), Vec3::X);
+        assert_eq!(miss.intersect_capsule(&capsule), None);
+    }
+
+    #[test]
+    fn triangle_hit_from_both_sides_and_miss() {
+        let triangle = Triangle::new(
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+
+        let front = Ray::new(Vec3::new(0.0, 0.0, 5.0), -Vec3::Z);
+        assert_distance(front.intersect_triangle(&triangle), 5.0);
+
+        let back = Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::Z);
+        assert_distance(back.intersect_triangle(&triangle), 5.0);
+
+        let outside = Ray::new(Vec3::new(2.0, 0.0, 5.0), -Vec3::Z);
+        assert_eq!(outside.intersect_triangle(&triangle), None);
+
+        let away = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::Z);
+        assert_eq!(away.intersect_triangle(&triangle), None);
+    }
+
+    #[test]
+    fn triangle_parallel_or_degenerate_is_not_hit() {
+        let triangle = Triangle::new(
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let in_plane = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X);
+        assert_eq!(in_plane.intersect_triangle(&triangle), None);
+
+        let line = Triangle::new(Vec3::ZERO, Vec3::X, Vec3::X * 2.0);
+        let ray = Ray::new(Vec3::new(1.0, 0.0, 5.0), -Vec3::Z);
+        assert_eq!(ray.intersect_triangle(&line), None);
+    }
+
+    #[test]
+    fn transformed_ray_keeps_distances() {
+        let ray = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X);
+        let matrix = Mat4::from_scale(Vec3::splat(2.0)).inverse();
+        let local = ray.transform_by_matrix(&matrix);
+
+        // 世界空间中 2 倍大小的盒子，在局部空间中仍返回世界空间的距离
+        assert_distance(local.intersect_aabb(&unit_box()), 3.0);
+    }
+}
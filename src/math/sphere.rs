@@ -0,0 +1,88 @@
+use crate::math::aabb::Aabb;
+use glam::Vec3;
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Sphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl Sphere {
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        Aabb::new(
+            self.center - Vec3::splat(self.radius),
+            self.center + Vec3::splat(self.radius),
+        )
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.center.distance_squared(point) <= self.radius * self.radius
+    }
+
+    /// The point in the sphere nearest to `point`, `point` itself if it is inside.
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        let offset = point - self.center;
+        if offset.length_squared() <= self.radius * self.radius {
+            point
+        } else {
+            self.center + offset.normalize() * self.radius
+        }
+    }
+
+    pub fn intersects_sphere(&self, other: &Sphere) -> bool {
+        let radius = self.radius + other.radius;
+        self.center.distance_squared(other.center) <= radius * radius
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.contains_point(aabb.closest_point(self.center))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spheres_touching_overlapping_and_apart() {
+        let sphere = Sphere::new(Vec3::ZERO, 1.0);
+        assert!(sphere.intersects_sphere(&Sphere::new(Vec3::new(2.0, 0.0, 0.0), 1.0)));
+        assert!(sphere.intersects_sphere(&Sphere::new(Vec3::new(0.2, 0.0, 0.0), 0.1)));
+        assert!(!sphere.intersects_sphere(&Sphere::new(Vec3::new(2.1, 0.0, 0.0), 1.0)));
+    }
+
+    #[test]
+    fn spheres_with_zero_radius_are_points() {
+        let point = Sphere::new(Vec3::ONE, 0.0);
+        assert!(point.contains_point(Vec3::ONE));
+        assert!(!point.contains_point(Vec3::ZERO));
+        assert!(point.intersects_sphere(&Sphere::new(Vec3::ONE, 0.0)));
+        assert!(!point.intersects_sphere(&Sphere::new(Vec3::ZERO, 1.0)));
+        assert_eq!(point.closest_point(Vec3::ZERO), Vec3::ONE);
+    }
+
+    #[test]
+    fn sphere_against_aabb() {
+        let aabb = Aabb::new(Vec3::splat(-1.0), Vec3::splat(1.0));
+        assert!(Sphere::new(Vec3::new(2.0, 0.0, 0.0), 1.0).intersects_aabb(&aabb));
+        assert!(Sphere::new(Vec3::ZERO, 0.1).intersects_aabb(&aabb));
+        // 包围盒相交，但离盒子的角太远
+        let near_corner = Sphere::new(Vec3::new(2.0, 2.0, 0.0), 1.0);
+        assert!(near_corner.aabb().intersects_aabb(&aabb));
+        assert!(!near_corner.intersects_aabb(&aabb));
+    }
+
+    #[test]
+    fn closest_point_is_on_the_surface_or_the_point_itself() {
+        let sphere = Sphere::new(Vec3::ZERO, 1.0);
+        assert_eq!(sphere.closest_point(Vec3::new(3.0, 0.0, 0.0)), Vec3::X);
+        assert_eq!(
+            sphere.closest_point(Vec3::new(0.5, 0.0, 0.0)),
+            Vec3::new(0.5, 0.0, 0.0)
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use glam::{Quat, Vec2, Vec3};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct Transform2d {
     pub position: Vec2,
     pub rotation: f32,
@@ -47,7 +48,7 @@ impl Transform2d {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct Transform3d {
     pub position: Vec3,
     pub rotation: Quat,
@@ -0,0 +1,348 @@
+use crate::math::aabb::Aabb;
+use crate::math::capsule::{closest_points_between_segments, Capsule};
+use crate::math::obb::Obb;
+use crate::math::ray::Ray;
+use crate::math::sphere::Sphere;
+use glam::Vec3;
+
+#[derive(Debug, Copy, Clone)]
+pub struct Triangle {
+    pub a: Vec3,
+    pub b: Vec3,
+    pub c: Vec3,
+}
+
+impl Triangle {
+    pub fn new(a: Vec3, b: Vec3, c: Vec3) -> Self {
+        Self { a, b, c }
+    }
+
+    /// Unit normal, facing the side from which the vertices are counter-clockwise.
+    pub fn normal(&self) -> Vec3 {
+        (self.b - self.a).cross(self.c - self.a).normalize_or_zero()
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        Aabb::new(
+            self.a.min(self.b).min(self.c),
+            self.a.max(self.b).max(self.c),
+        )
+    }
+
+    pub fn transform_by_matrix(&self, matrix: &glam::Mat4) -> Self {
+        Self {
+            a: matrix.transform_point3(self.a),
+            b: matrix.transform_point3(self.b),
+            c: matrix.transform_point3(self.c),
+        }
+    }
+
+    /// The point on the triangle nearest to `point`.
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        let weights = self.closest_point_weights(point);
+        self.a * weights.x + self.b * weights.y + self.c * weights.z
+    }
+
+    /// Barycentric weights of `a`, `b` and `c` for the point on the triangle nearest to `point`.
+    pub fn closest_point_weights(&self, point: Vec3) -> Vec3 {
+        // Ericson, Real-Time Collision Detection 5.1.5，按 Voronoi 区域判断
+        let (a, b, c) = (self.a, self.b, self.c);
+        let ab = b - a;
+        let ac = c - a;
+
+        let ap = point - a;
+        let d1 = ab.dot(ap);
+        let d2 = ac.dot(ap);
+        if d1 <= 0.0 && d2 <= 0.0 {
+            return Vec3::X;
+        }
+
+        let bp = point - b;
+        let d3 = ab.dot(bp);
+        let d4 = ac.dot(bp);
+        if d3 >= 0.0 && d4 <= d3 {
+            return Vec3::Y;
+        }
+
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+            let v = d1 / (d1 - d3);
+            return Vec3::new(1.0 - v, v, 0.0);
+        }
+
+        let cp = point - c;
+        let d5 = ab.dot(cp);
+        let d6 = ac.dot(cp);
+        if d6 >= 0.0 && d5 <= d6 {
+            return Vec3::Z;
+        }
+
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+            let w = d2 / (d2 - d6);
+            return Vec3::new(1.0 - w, 0.0, w);
+        }
+
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+            let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+            return Vec3::new(0.0, 1.0 - w, w);
+        }
+
+        // 退化 (面积为 0) 的三角形
+        let denom = va + vb + vc;
+        if denom <= 0.0 {
+            return Vec3::X;
+        }
+        let v = vb / denom;
+        let w = vc / denom;
+        Vec3::new(1.0 - v - w, v, w)
+    }
+
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+        sphere.contains_point(self.closest_point(sphere.center))
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        let center = aabb.center();
+        let local = Self::new(self.a - center, self.b - center, self.c - center);
+        local.intersects_box(aabb.half_extents())
+    }
+
+    pub fn intersects_obb(&self, obb: &Obb) -> bool {
+        // 在盒子的局部空间中计算
+        let inv_rotation = obb.rotation.inverse();
+        let local = Self::new(
+            inv_rotation * (self.a - obb.center),
+            inv_rotation * (self.b - obb.center),
+            inv_rotation * (self.c - obb.center),
+        );
+        local.intersects_box(obb.half_extents)
+    }
+
+    pub fn intersects_capsule(&self, capsule: &Capsule) -> bool {
+        let radius_squared = capsule.radius * capsule.radius;
+
+        // 线段穿过三角形
+        let axis = capsule.end - capsule.start;
+        let length = axis.length();
+        if length > f32::EPSILON {
+            let ray = Ray::new(capsule.start, axis);
+            if ray.intersect_triangle(self).is_some_and(|t| t <= length) {
+                return true;
+            }
+        }
+
+        // 否则最近点在线段端点或三角形的边上
+        let near_end = [capsule.start, capsule.end]
+            .into_iter()
+            .any(|point| self.closest_point(point).distance_squared(point) <= radius_squared);
+        near_end
+            || self.edges().into_iter().any(|(from, to)| {
+                let (a, b) = closest_points_between_segments(capsule.start, capsule.end, from, to);
+                a.distance_squared(b) <= radius_squared
+            })
+    }
+
+    /// Separating axis test over both normals, the cross products of the edges and, for
+    /// coplanar triangles, the edge normals within the plane.
+    pub fn intersects_triangle(&self, other: &Triangle) -> bool {
+        let (normal, other_normal) = (self.normal(), other.normal());
+        let edges = self.edges().map(|(from, to)| to - from);
+        let other_edges = other.edges().map(|(from, to)| to - from);
+
+        let mut axes = vec![normal, other_normal];
+        for edge in edges {
+            axes.extend(other_edges.iter().map(|other_edge| edge.cross(*other_edge)));
+            axes.push(normal.cross(edge));
+        }
+        axes.extend(other_edges.iter().map(|edge| other_normal.cross(*edge)));
+
+        // 长度为 0 的轴上投影都是 0，不会误判为分离
+        axes.into_iter().all(|axis| {
+            let (min, max) = self.project(axis);
+            let (other_min, other_max) = other.project(axis);
+            min <= other_max && other_min <= max
+        })
+    }
+
+    /// Separating axis test against a box centered at the origin, Akenine-Möller's 13 axes.
+    fn intersects_box(&self, half_extents: Vec3) -> bool {
+        let edges = self.edges().map(|(from, to)| to - from);
+
+        let mut axes = vec![Vec3::X, Vec3::Y, Vec3::Z, edges[0].cross(edges[1])];
+        for edge in edges {
+            axes.extend([Vec3::X, Vec3::Y, Vec3::Z].map(|axis| axis.cross(edge)));
+        }
+
+        axes.into_iter().all(|axis| {
+            let (min, max) = self.project(axis);
+            let radius = half_extents.dot(axis.abs());
+            min <= radius && max >= -radius
+        })
+    }
+
+    fn edges(&self) -> [(Vec3, Vec3); 3] {
+        [(self.a, self.b), (self.b, self.c), (self.c, self.a)]
+    }
+
+    fn project(&self, axis: Vec3) -> (f32, f32) {
+        let [a, b, c] = [self.a, self.b, self.c].map(|v| v.dot(axis));
+        (a.min(b).min(c), a.max(b).max(c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Quat;
+    use std::f32::consts::FRAC_PI_4;
+
+    fn corner() -> Triangle {
+        Triangle::new(
+            Vec3::ZERO,
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+        )
+    }
+
+    fn unit_box() -> Aabb {
+        Aabb::new(Vec3::splat(-1.0), Vec3::splat(1.0))
+    }
+
+    #[test]
+    fn closest_point_in_each_region() {
+        let triangle = corner();
+        assert_eq!(
+            triangle.closest_point(Vec3::new(-1.0, -1.0, 0.0)),
+            Vec3::ZERO
+        );
+        assert_eq!(
+            triangle.closest_point(Vec3::new(3.0, -1.0, 0.0)),
+            Vec3::new(2.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            triangle.closest_point(Vec3::new(1.0, -1.0, 0.0)),
+            Vec3::new(1.0, 0.0, 0.0)
+        );
+        assert!(triangle
+            .closest_point(Vec3::new(2.0, 2.0, 0.0))
+            .abs_diff_eq(Vec3::new(1.0, 1.0, 0.0), 1e-5));
+        assert!(triangle
+            .closest_point(Vec3::new(0.5, 0.5, 1.0))
+            .abs_diff_eq(Vec3::new(0.5, 0.5, 0.0), 1e-5));
+    }
+
+    #[test]
+    fn closest_point_on_degenerate_triangles() {
+        let line = Triangle::new(Vec3::ZERO, Vec3::X, Vec3::X * 2.0);
+        assert!(line
+            .closest_point(Vec3::new(1.5, 1.0, 0.0))
+            .abs_diff_eq(Vec3::new(1.5, 0.0, 0.0), 1e-5));
+        assert_eq!(line.normal(), Vec3::ZERO);
+
+        let point = Triangle::new(Vec3::ONE, Vec3::ONE, Vec3::ONE);
+        assert_eq!(point.closest_point(Vec3::ZERO), Vec3::ONE);
+    }
+
+    #[test]
+    fn triangle_against_sphere() {
+        let triangle = corner();
+        assert!(triangle.intersects_sphere(&Sphere::new(Vec3::new(0.5, 0.5, 1.0), 1.0)));
+        assert!(!triangle.intersects_sphere(&Sphere::new(Vec3::new(0.5, 0.5, 1.0), 0.9)));
+    }
+
+    #[test]
+    fn triangle_against_aabb() {
+        // 顶点都在盒子外，但三角形穿过盒子
+        let large = Triangle::new(
+            Vec3::new(-5.0, -5.0, 0.0),
+            Vec3::new(5.0, -5.0, 0.0),
+            Vec3::new(0.0, 5.0, 0.0),
+        );
+        assert!(large.intersects_aabb(&unit_box()));
+        let offset = Vec3::new(0.0, 0.0, 2.0);
+        let moved = Triangle::new(large.a + offset, large.b + offset, large.c + offset);
+        assert!(!moved.intersects_aabb(&unit_box()));
+
+        // 只有法线轴能分离，平面 x + y + z = 3 正好经过盒子的角
+        let diagonal = |d: f32| Triangle::new(Vec3::X * d, Vec3::Y * d, Vec3::Z * d);
+        assert!(diagonal(3.0).intersects_aabb(&unit_box()));
+        assert!(!diagonal(3.5).intersects_aabb(&unit_box()));
+
+        // 面轴和法线轴上都重叠，只有边的叉积轴能分离
+        let sliver = Triangle::new(
+            Vec3::new(2.5, -0.5, -0.5),
+            Vec3::new(0.0, 0.0, -2.0),
+            Vec3::new(0.5, 0.0, -1.5),
+        );
+        assert!(sliver.aabb().intersects_aabb(&unit_box()));
+        assert!(!sliver.intersects_aabb(&unit_box()));
+    }
+
+    #[test]
+    fn triangle_against_obb() {
+        let obb = Obb::new(Vec3::ZERO, Vec3::ONE, Quat::from_rotation_z(FRAC_PI_4));
+        let small = Triangle::new(
+            Vec3::new(1.3, -0.1, 0.0),
+            Vec3::new(1.3, 0.1, 0.0),
+            Vec3::new(1.35, 0.0, 0.1),
+        );
+        assert!(small.intersects_obb(&obb));
+        assert!(!small.intersects_aabb(&unit_box()));
+    }
+
+    #[test]
+    fn triangle_against_capsule() {
+        let triangle = corner();
+        let vertical = |x: f32, radius: f32| {
+            Capsule::new(Vec3::new(x, 0.5, -1.0), Vec3::new(x, 0.5, 1.0), radius)
+        };
+        // 线段穿过三角形
+        assert!(triangle.intersects_capsule(&vertical(0.5, 0.01)));
+        // 线段在边旁边
+        assert!(triangle.intersects_capsule(&vertical(-0.2, 0.3)));
+        assert!(!triangle.intersects_capsule(&vertical(-0.2, 0.1)));
+
+        // 端点相同的胶囊体
+        let point =
+            |radius| Capsule::new(Vec3::new(0.5, 0.5, 0.2), Vec3::new(0.5, 0.5, 0.2), radius);
+        assert!(triangle.intersects_capsule(&point(0.3)));
+        assert!(!triangle.intersects_capsule(&point(0.1)));
+    }
+
+    #[test]
+    fn triangles_coplanar_crossing_and_apart() {
+        let triangle = corner();
+        let moved = |offset: Vec3| {
+            Triangle::new(
+                Vec3::new(0.5, 0.5, 0.0) + offset,
+                Vec3::new(3.0, 0.5, 0.0) + offset,
+                Vec3::new(0.5, 3.0, 0.0) + offset,
+            )
+        };
+        // 同一平面
+        assert!(triangle.intersects_triangle(&moved(Vec3::ZERO)));
+        // 同一平面，只有平面内的边法线能分离
+        assert!(!triangle.intersects_triangle(&moved(Vec3::new(1.0, 1.0, 0.0))));
+        // 平行的平面
+        assert!(!triangle.intersects_triangle(&moved(Vec3::Z)));
+
+        let crossing = Triangle::new(
+            Vec3::new(0.5, 0.5, -1.0),
+            Vec3::new(0.6, 0.5, 1.0),
+            Vec3::new(0.5, 0.6, 1.0),
+        );
+        assert!(triangle.intersects_triangle(&crossing));
+        assert!(crossing.intersects_triangle(&triangle));
+    }
+
+    #[test]
+    fn degenerate_triangles_overlap_as_points() {
+        let triangle = corner();
+        let point = |p: Vec3| Triangle::new(p, p, p);
+        assert!(triangle.intersects_triangle(&point(Vec3::new(0.5, 0.5, 0.0))));
+        assert!(!triangle.intersects_triangle(&point(Vec3::new(0.5, 0.5, 1.0))));
+        assert!(!triangle.intersects_triangle(&point(Vec3::new(1.5, 1.5, 0.0))));
+    }
+}
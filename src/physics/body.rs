@@ -0,0 +1,42 @@
+use glam::Vec3;
+use hecs::Entity;
+use serde::{Deserialize, Serialize};
+
+/// How an entity takes part in the physics step. Entities with a [`crate::physics::Collider`]
+/// but no `RigidBody` are static.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RigidBody {
+    /// Moved by gravity, its [`Velocity`] and collisions.
+    #[default]
+    Dynamic,
+    /// Moved only by its [`Velocity`] (or by changing the transform), pushes dynamic bodies
+    /// without being pushed back.
+    Kinematic,
+    /// Never moves.
+    Static,
+}
+
+/// Velocity of a rigid body in world space. Added to dynamic bodies by the physics step if
+/// missing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Velocity {
+    pub linear: Vec3,
+    /// Axis times radians per second.
+    pub angular: Vec3,
+}
+
+impl Velocity {
+    pub fn linear(linear: Vec3) -> Self {
+        Self {
+            linear,
+            angular: Vec3::ZERO,
+        }
+    }
+}
+
+/// Sent when two colliders start or stop touching. Entities are in the same order for both.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollisionEvent {
+    Started(Entity, Entity),
+    Stopped(Entity, Entity),
+}
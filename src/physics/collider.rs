@@ -0,0 +1,220 @@
+use crate::math::aabb::Aabb;
+use crate::scene::d3::{CpuMesh, Model};
+use glam::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+/// Convex shape in the local space of the entity, scale is ignored.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ColliderShape {
+    Sphere {
+        radius: f32,
+    },
+    Cuboid {
+        half_extents: Vec3,
+    },
+    /// Along the local Y axis, `half_height` is half the distance between the two sphere centers.
+    Capsule {
+        half_height: f32,
+        radius: f32,
+    },
+    /// The convex hull of the points.
+    ConvexHull {
+        points: Vec<Vec3>,
+    },
+}
+
+/// 碰撞体组件，与 [`crate::physics::RigidBody`] 一起使用
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Collider {
+    pub shape: ColliderShape,
+    /// Bounciness from 0 to 1, the larger one of a colliding pair is used.
+    pub restitution: f32,
+    pub friction: f32,
+    /// Mass per unit volume, for dynamic bodies.
+    pub density: f32,
+    /// Only reports [`crate::physics::CollisionEvent`]s, without pushing anything.
+    pub sensor: bool,
+}
+
+impl Collider {
+    pub fn new(shape: ColliderShape) -> Self {
+        Self {
+            shape,
+            restitution: 0.0,
+            friction: 0.5,
+            density: 1.0,
+            sensor: false,
+        }
+    }
+
+    pub fn sphere(radius: f32) -> Self {
+        Self::new(ColliderShape::Sphere { radius })
+    }
+
+    pub fn cuboid(half_extents: Vec3) -> Self {
+        Self::new(ColliderShape::Cuboid { half_extents })
+    }
+
+    pub fn capsule(half_height: f32, radius: f32) -> Self {
+        Self::new(ColliderShape::Capsule {
+            half_height,
+            radius,
+        })
+    }
+
+    pub fn convex_hull(points: Vec<Vec3>) -> Self {
+        Self::new(ColliderShape::ConvexHull { points })
+    }
+
+    /// Convex hull of the vertices of a mesh.
+    pub fn convex_hull_from_mesh(mesh: &CpuMesh) -> Self {
+        Self::convex_hull(unique_points(mesh.positions.iter().copied()))
+    }
+
+    /// Convex hull of all meshes of a model, in the model's local space. Models without CPU
+    /// meshes use their bounding box.
+    pub fn convex_hull_from_model(model: &Model) -> Self {
+        if model.cpu_meshes.is_empty() {
+            return Self::convex_hull(model.aabb.corners().to_vec());
+        }
+
+        let points = model
+            .cpu_meshes
+            .iter()
+            .zip(&model.mesh_transforms)
+            .flat_map(|(mesh, transform)| {
+                let matrix = transform.matrix();
+                mesh.positions
+                    .iter()
+                    .map(move |p| matrix.transform_point3(*p))
+            });
+        Self::convex_hull(unique_points(points))
+    }
+
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution;
+        self
+    }
+
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density;
+        self
+    }
+
+    pub fn with_sensor(mut self, sensor: bool) -> Self {
+        self.sensor = sensor;
+        self
+    }
+
+    pub fn local_aabb(&self) -> Aabb {
+        match &self.shape {
+            ColliderShape::Sphere { radius } => {
+                Aabb::new(Vec3::splat(-radius), Vec3::splat(*radius))
+            }
+            ColliderShape::Cuboid { half_extents } => Aabb::new(-*half_extents, *half_extents),
+            ColliderShape::Capsule {
+                half_height,
+                radius,
+            } => {
+                let extent = Vec3::new(*radius, half_height + radius, *radius);
+                Aabb::new(-extent, extent)
+            }
+            ColliderShape::ConvexHull { points } => Aabb::from_points(points),
+        }
+    }
+
+    /// Mass and the diagonal of the inertia tensor, hulls are approximated by their bounding box.
+    pub(crate) fn mass_properties(&self) -> (f32, Vec3) {
+        let density = self.density;
+        match &self.shape {
+            ColliderShape::Sphere { radius } => {
+                let mass = density * 4.0 / 3.0 * PI * radius.powi(3);
+                (mass, Vec3::splat(0.4 * mass * radius * radius))
+            }
+            ColliderShape::Cuboid { half_extents } => cuboid_mass(density, *half_extents),
+            ColliderShape::Capsule {
+                half_height,
+                radius,
+            } => {
+                let (r, h) = (*radius, half_height * 2.0);
+                let cylinder = density * PI * r * r * h;
+                let spheres = density * 4.0 / 3.0 * PI * r.powi(3);
+                let axial = cylinder * r * r / 2.0 + spheres * 0.4 * r * r;
+                let lateral = cylinder * (h * h / 12.0 + r * r / 4.0)
+                    + spheres * (0.4 * r * r + h * h / 4.0 + 3.0 * h * r / 8.0);
+                (cylinder + spheres, Vec3::new(lateral, axial, lateral))
+            }
+            ColliderShape::ConvexHull { points } => {
+                let aabb = Aabb::from_points(points);
+                cuboid_mass(density, aabb.half_extents())
+            }
+        }
+    }
+}
+
+fn cuboid_mass(density: f32, half_extents: Vec3) -> (f32, Vec3) {
+    let mass = density * 8.0 * half_extents.x * half_extents.y * half_extents.z;
+    let sq = half_extents * half_extents;
+    let inertia = Vec3::new(sq.y + sq.z, sq.x + sq.z, sq.x + sq.y) * (mass / 3.0);
+    (mass, inertia)
+}
+
+/// 网格中相同位置的顶点 (法线或 UV 不同) 只保留一个
+fn unique_points(points: impl Iterator<Item = Vec3>) -> Vec<Vec3> {
+    let mut points: Vec<Vec3> = points.collect();
+    points.sort_by(|a, b| {
+        a.to_array()
+            .iter()
+            .zip(b.to_array().iter())
+            .map(|(a, b)| a.total_cmp(b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    points.dedup();
+    points
+}
+
+/// A collider placed in the world. Spheres and capsules are handled as a point or segment plus
+/// a radius, which keeps the GJK support functions simple.
+pub(crate) struct PosedCollider<'a> {
+    pub(crate) shape: &'a ColliderShape,
+    pub(crate) position: Vec3,
+    pub(crate) rotation: Quat,
+}
+
+impl PosedCollider<'_> {
+    pub(crate) fn radius(&self) -> f32 {
+        match self.shape {
+            ColliderShape::Sphere { radius } | ColliderShape::Capsule { radius, .. } => *radius,
+            _ => 0.0,
+        }
+    }
+
+    /// Furthest point of the shape without its radius in `direction`.
+    pub(crate) fn support(&self, direction: Vec3) -> Vec3 {
+        let local_direction = self.rotation.inverse() * direction;
+        let local = match self.shape {
+            ColliderShape::Sphere { .. } => Vec3::ZERO,
+            ColliderShape::Cuboid { half_extents } => Vec3::select(
+                local_direction.cmpge(Vec3::ZERO),
+                *half_extents,
+                -*half_extents,
+            ),
+            ColliderShape::Capsule { half_height, .. } => {
+                Vec3::new(0.0, half_height.copysign(local_direction.y), 0.0)
+            }
+            ColliderShape::ConvexHull { points } => points
+                .iter()
+                .copied()
+                .max_by(|a, b| a.dot(local_direction).total_cmp(&b.dot(local_direction)))
+                .unwrap_or(Vec3::ZERO),
+        };
+        self.position + self.rotation * local
+    }
+}
@@ -0,0 +1,308 @@
+use crate::math::triangle::Triangle;
+use crate::physics::collider::PosedCollider;
+use glam::Vec3;
+
+const MAX_ITERATIONS: usize = 64;
+/// GJK stops once an iteration gets less than this (relative) closer to the origin.
+const GJK_TOLERANCE: f32 = 1e-6;
+/// EPA stops once the polytope grows less than this towards the nearest face.
+const EPA_TOLERANCE: f32 = 1e-4;
+const EPSILON: f32 = 1e-6;
+
+/// Contact between two overlapping colliders.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Contact {
+    /// From `a` to `b`, moving `b` by `normal * depth` separates them.
+    pub(crate) normal: Vec3,
+    pub(crate) depth: f32,
+    /// Deepest points on the surfaces of `a` and `b`.
+    pub(crate) point_a: Vec3,
+    pub(crate) point_b: Vec3,
+}
+
+/// A point of the Minkowski difference `a - b`, with the points of `a` and `b` it came from.
+#[derive(Clone, Copy)]
+struct SupportPoint {
+    w: Vec3,
+    a: Vec3,
+    b: Vec3,
+}
+
+fn support(a: &PosedCollider, b: &PosedCollider, direction: Vec3) -> SupportPoint {
+    let point_a = a.support(direction);
+    let point_b = b.support(-direction);
+    SupportPoint {
+        w: point_a - point_b,
+        a: point_a,
+        b: point_b,
+    }
+}
+
+enum Gjk {
+    /// Nearest points of the two shapes.
+    Separated(Vec3, Vec3),
+    /// The simplex contains the origin, `Vec3` is a point in both shapes.
+    Overlapping(Vec<SupportPoint>, Vec3),
+}
+
+/// GJK on the shapes without their radius, then EPA if those overlap.
+pub(crate) fn contact(a: &PosedCollider, b: &PosedCollider) -> Option<Contact> {
+    let radius = a.radius() + b.radius();
+
+    let (normal, depth, point_a, point_b) = match gjk(a, b) {
+        Gjk::Separated(point_a, point_b) => {
+            let offset = point_b - point_a;
+            let distance = offset.length();
+            if distance >= radius {
+                return None;
+            }
+            match offset.try_normalize() {
+                Some(normal) => (normal, -distance, point_a, point_b),
+                None => (fallback_normal(a, b), 0.0, point_a, point_b),
+            }
+        }
+        // 球和胶囊体的核心是点或线段，Minkowski 差是扁平的，EPA 无法处理
+        Gjk::Overlapping(simplex, point) => {
+            epa(a, b, simplex).unwrap_or_else(|| (fallback_normal(a, b), 0.0, point, point))
+        }
+    };
+
+    Some(Contact {
+        normal,
+        depth: depth + radius,
+        point_a: point_a + normal * a.radius(),
+        point_b: point_b - normal * b.radius(),
+    })
+}
+
+/// For cores that touch in a single point, which gives no direction.
+fn fallback_normal(a: &PosedCollider, b: &PosedCollider) -> Vec3 {
+    (b.position - a.position).try_normalize().unwrap_or(Vec3::Y)
+}
+
+fn gjk(a: &PosedCollider, b: &PosedCollider) -> Gjk {
+    let direction = (a.position - b.position).try_normalize().unwrap_or(Vec3::X);
+    let mut simplex = vec![(support(a, b, direction), 1.0)];
+
+    for _ in 0..MAX_ITERATIONS {
+        let v: Vec3 = simplex.iter().map(|(p, weight)| p.w * *weight).sum();
+        let (point_a, point_b) = witness_points(&simplex);
+
+        let v_length_squared = v.length_squared();
+        if v_length_squared <= EPSILON * EPSILON {
+            return Gjk::Overlapping(simplex.into_iter().map(|(p, _)| p).collect(), point_a);
+        }
+
+        let s = support(a, b, -v);
+        // 新的支撑点没有更接近原点，v 即为最近点
+        let no_progress = v_length_squared - v.dot(s.w) <= GJK_TOLERANCE * v_length_squared;
+        let duplicate = simplex
+            .iter()
+            .any(|(p, _)| p.w.distance_squared(s.w) <= EPSILON * EPSILON);
+        if no_progress || duplicate {
+            return Gjk::Separated(point_a, point_b);
+        }
+
+        let mut points: Vec<SupportPoint> = simplex.iter().map(|(p, _)| *p).collect();
+        points.push(s);
+        simplex = closest_on_simplex(&points);
+
+        if simplex.len() == 4 {
+            let (point_a, _) = witness_points(&simplex);
+            return Gjk::Overlapping(points, point_a);
+        }
+    }
+
+    let (point_a, point_b) = witness_points(&simplex);
+    Gjk::Separated(point_a, point_b)
+}
+
+fn witness_points(simplex: &[(SupportPoint, f32)]) -> (Vec3, Vec3) {
+    simplex
+        .iter()
+        .fold((Vec3::ZERO, Vec3::ZERO), |(a, b), (p, weight)| {
+            (a + p.a * *weight, b + p.b * *weight)
+        })
+}
+
+/// The smallest sub-simplex containing the point nearest to the origin, with the barycentric
+/// weights of that point. All four points of a tetrahedron containing the origin are kept.
+fn closest_on_simplex(points: &[SupportPoint]) -> Vec<(SupportPoint, f32)> {
+    let weighted: Vec<(SupportPoint, f32)> = match points {
+        [p] => vec![(*p, 1.0)],
+        [p, q] => {
+            let pq = q.w - p.w;
+            let t = (-p.w.dot(pq) / pq.length_squared()).clamp(0.0, 1.0);
+            vec![(*p, 1.0 - t), (*q, t)]
+        }
+        [p, q, r] => closest_on_triangle(p, q, r),
+        [p, q, r, s] => {
+            // 原点在某个面外侧时，最近点在这些面上
+            let faces = [(p, q, r, s), (p, r, s, q), (p, s, q, r), (q, s, r, p)];
+            let mut nearest: Option<(Vec<(SupportPoint, f32)>, f32)> = None;
+            for (p, q, r, opposite) in faces {
+                let normal = (q.w - p.w).cross(r.w - p.w);
+                let side_origin = normal.dot(-p.w);
+                let side_opposite = normal.dot(opposite.w - p.w);
+                let outside =
+                    side_opposite.abs() <= EPSILON * EPSILON || side_origin * side_opposite < 0.0;
+                if !outside {
+                    continue;
+                }
+
+                let weighted = closest_on_triangle(p, q, r);
+                let distance = weighted
+                    .iter()
+                    .map(|(p, weight)| p.w * *weight)
+                    .sum::<Vec3>()
+                    .length_squared();
+                if nearest.as_ref().is_none_or(|(_, d)| distance < *d) {
+                    nearest = Some((weighted, distance));
+                }
+            }
+
+            match nearest {
+                Some((weighted, _)) => weighted,
+                None => return points.iter().map(|p| (*p, 0.25)).collect(),
+            }
+        }
+        _ => unreachable!("GJK simplex has 1 to 4 points"),
+    };
+
+    weighted
+        .into_iter()
+        .filter(|(_, weight)| *weight > 0.0)
+        .collect()
+}
+
+fn closest_on_triangle(
+    p: &SupportPoint,
+    q: &SupportPoint,
+    r: &SupportPoint,
+) -> Vec<(SupportPoint, f32)> {
+    let weights = Triangle::new(p.w, q.w, r.w).closest_point_weights(Vec3::ZERO);
+    vec![(*p, weights.x), (*q, weights.y), (*r, weights.z)]
+}
+
+/// Penetration normal and depth of the shapes, and the deepest points of each.
+fn epa(
+    a: &PosedCollider,
+    b: &PosedCollider,
+    simplex: Vec<SupportPoint>,
+) -> Option<(Vec3, f32, Vec3, Vec3)> {
+    let mut vertices = expand_to_tetrahedron(a, b, simplex)?;
+    // 多面体保持凸，初始四面体的中心始终在内部，用于确定面的朝向
+    let center = vertices.iter().map(|v| v.w).sum::<Vec3>() / 4.0;
+
+    let face = |vertices: &[SupportPoint], i: usize, j: usize, k: usize| {
+        let normal = (vertices[j].w - vertices[i].w).cross(vertices[k].w - vertices[i].w);
+        if normal.dot(vertices[i].w - center) < 0.0 {
+            [i, k, j]
+        } else {
+            [i, j, k]
+        }
+    };
+    let mut faces: Vec<[usize; 3]> = [[0, 1, 2], [0, 1, 3], [0, 2, 3], [1, 2, 3]]
+        .iter()
+        .map(|[i, j, k]| face(&vertices, *i, *j, *k))
+        .collect();
+
+    for _ in 0..MAX_ITERATIONS {
+        let (nearest, normal, distance) = faces
+            .iter()
+            .enumerate()
+            .filter_map(|(index, [i, j, k])| {
+                let normal = (vertices[*j].w - vertices[*i].w)
+                    .cross(vertices[*k].w - vertices[*i].w)
+                    .try_normalize()?;
+                Some((index, normal, normal.dot(vertices[*i].w)))
+            })
+            .min_by(|a, b| a.2.total_cmp(&b.2))?;
+
+        let s = support(a, b, normal);
+        if s.w.dot(normal) - distance < EPA_TOLERANCE {
+            let [i, j, k] = faces[nearest];
+            let (p, q, r) = (vertices[i], vertices[j], vertices[k]);
+            let weights = Triangle::new(p.w, q.w, r.w).closest_point_weights(normal * distance);
+            let point_a = p.a * weights.x + q.a * weights.y + r.a * weights.z;
+            let point_b = p.b * weights.x + q.b * weights.y + r.b * weights.z;
+            return Some((normal, distance, point_a, point_b));
+        }
+
+        // 删除新顶点能看到的面，用剩下的边界边与新顶点组成新的面
+        let index = vertices.len();
+        vertices.push(s);
+        let mut horizon: Vec<(usize, usize)> = Vec::new();
+        faces.retain(|[i, j, k]| {
+            let normal = (vertices[*j].w - vertices[*i].w).cross(vertices[*k].w - vertices[*i].w);
+            if normal.dot(s.w - vertices[*i].w) <= 0.0 {
+                return true;
+            }
+            for (from, to) in [(*i, *j), (*j, *k), (*k, *i)] {
+                match horizon.iter().position(|edge| *edge == (to, from)) {
+                    Some(shared) => {
+                        horizon.swap_remove(shared);
+                    }
+                    None => horizon.push((from, to)),
+                }
+            }
+            false
+        });
+
+        for (from, to) in horizon {
+            faces.push(face(&vertices, from, to, index));
+        }
+    }
+
+    None
+}
+
+/// EPA needs a tetrahedron around the origin, but GJK may stop with fewer points when the
+/// origin lies on the simplex.
+fn expand_to_tetrahedron(
+    a: &PosedCollider,
+    b: &PosedCollider,
+    mut points: Vec<SupportPoint>,
+) -> Option<Vec<SupportPoint>> {
+    if points.len() == 1 {
+        let directions = [
+            Vec3::X,
+            Vec3::NEG_X,
+            Vec3::Y,
+            Vec3::NEG_Y,
+            Vec3::Z,
+            Vec3::NEG_Z,
+        ];
+        let s = directions
+            .into_iter()
+            .map(|direction| support(a, b, direction))
+            .find(|s| s.w.distance_squared(points[0].w) > EPSILON * EPSILON)?;
+        points.push(s);
+    }
+
+    if points.len() == 2 {
+        let axis = (points[1].w - points[0].w).normalize();
+        let (u, v) = axis.any_orthonormal_pair();
+        let s = [u, -u, v, -v]
+            .into_iter()
+            .map(|direction| support(a, b, direction))
+            .find(|s| {
+                let offset = s.w - points[0].w;
+                (offset - axis * offset.dot(axis)).length_squared() > EPSILON * EPSILON
+            })?;
+        points.push(s);
+    }
+
+    if points.len() == 3 {
+        let normal = (points[1].w - points[0].w)
+            .cross(points[2].w - points[0].w)
+            .try_normalize()?;
+        let s = [normal, -normal]
+            .into_iter()
+            .map(|direction| support(a, b, direction))
+            .find(|s| normal.dot(s.w - points[0].w).abs() > EPSILON)?;
+        points.push(s);
+    }
+
+    (points.len() == 4).then_some(points)
+}
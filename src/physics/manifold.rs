@@ -0,0 +1,146 @@
+use crate::physics::gjk::Contact;
+use glam::{Quat, Vec3};
+
+/// Points further apart than this (separated or sliding) are dropped from a manifold.
+const PERSISTENT_THRESHOLD: f32 = 0.02;
+const MAX_POINTS: usize = 4;
+
+/// A body's position and rotation, for moving contact points between world and body space.
+#[derive(Clone, Copy)]
+pub(crate) struct Pose {
+    pub(crate) position: Vec3,
+    pub(crate) rotation: Quat,
+}
+
+impl Pose {
+    fn to_local(self, point: Vec3) -> Vec3 {
+        self.rotation.inverse() * (point - self.position)
+    }
+
+    fn to_world(self, point: Vec3) -> Vec3 {
+        self.position + self.rotation * point
+    }
+}
+
+pub(crate) struct ManifoldPoint {
+    /// Contact points in the space of each body, so they follow the bodies between steps.
+    local_a: Vec3,
+    local_b: Vec3,
+    pub(crate) point: Vec3,
+    pub(crate) depth: f32,
+    /// Impulses accumulated by the solver, reused as a warm start in the next step.
+    pub(crate) normal_impulse: f32,
+    pub(crate) tangent_impulse: [f32; 2],
+}
+
+/// Contact points between two colliders, kept across steps. GJK/EPA finds a single point per
+/// step, collecting them gives resting boxes a stable base.
+pub(crate) struct Manifold {
+    /// From the first body to the second.
+    pub(crate) normal: Vec3,
+    pub(crate) points: Vec<ManifoldPoint>,
+}
+
+impl Manifold {
+    pub(crate) fn new() -> Self {
+        Self {
+            normal: Vec3::Y,
+            points: Vec::new(),
+        }
+    }
+
+    pub(crate) fn update(&mut self, contact: &Contact, a: Pose, b: Pose) {
+        let normal = contact.normal;
+
+        // 1. 移动旧的接触点，丢弃已分开或滑开的点
+        self.points.retain_mut(|point| {
+            let world_a = a.to_world(point.local_a);
+            let world_b = b.to_world(point.local_b);
+            let offset = world_a - world_b;
+            let depth = offset.dot(normal);
+            let drift = offset - normal * depth;
+            if depth < -PERSISTENT_THRESHOLD
+                || drift.length_squared() > PERSISTENT_THRESHOLD.powi(2)
+            {
+                return false;
+            }
+            point.point = (world_a + world_b) * 0.5;
+            point.depth = depth;
+            true
+        });
+        self.normal = normal;
+
+        // 2. 加入新的接触点，与旧点重合时替换它并保留冲量
+        let new_point = ManifoldPoint {
+            local_a: a.to_local(contact.point_a),
+            local_b: b.to_local(contact.point_b),
+            point: (contact.point_a + contact.point_b) * 0.5,
+            depth: contact.depth,
+            normal_impulse: 0.0,
+            tangent_impulse: [0.0; 2],
+        };
+        let existing = self.points.iter_mut().find(|point| {
+            point.point.distance_squared(new_point.point) < PERSISTENT_THRESHOLD.powi(2)
+        });
+        match existing {
+            Some(point) => {
+                point.local_a = new_point.local_a;
+                point.local_b = new_point.local_b;
+                point.point = new_point.point;
+                point.depth = new_point.depth;
+            }
+            None => self.points.push(new_point),
+        }
+
+        if self.points.len() > MAX_POINTS {
+            self.reduce();
+        }
+    }
+
+    /// Keeps the deepest point and the three that span the largest area with it.
+    fn reduce(&mut self) {
+        let deepest = (0..self.points.len())
+            .max_by(|i, j| self.points[*i].depth.total_cmp(&self.points[*j].depth))
+            .unwrap_or(0);
+        let p0 = self.points[deepest].point;
+
+        let mut kept = vec![deepest];
+        let furthest = (0..self.points.len())
+            .filter(|i| !kept.contains(i))
+            .max_by(|i, j| {
+                let di = self.points[*i].point.distance_squared(p0);
+                let dj = self.points[*j].point.distance_squared(p0);
+                di.total_cmp(&dj)
+            });
+        kept.extend(furthest);
+
+        // 依次选取使多边形面积最大的点
+        while kept.len() < MAX_POINTS {
+            let area = |candidate: usize| -> f32 {
+                let p = self.points[candidate].point;
+                kept.iter()
+                    .zip(kept.iter().skip(1).chain(kept.first()))
+                    .map(|(i, j)| {
+                        let (pi, pj) = (self.points[*i].point, self.points[*j].point);
+                        (pi - p).cross(pj - p).length()
+                    })
+                    .fold(0.0, f32::max)
+            };
+            let Some(next) = (0..self.points.len())
+                .filter(|i| !kept.contains(i))
+                .max_by(|i, j| area(*i).total_cmp(&area(*j)))
+            else {
+                break;
+            };
+            kept.push(next);
+        }
+
+        kept.sort_unstable();
+        let mut index = 0;
+        self.points.retain(|_| {
+            let keep = kept.contains(&index);
+            index += 1;
+            keep
+        });
+    }
+}
@@ -0,0 +1,9 @@
+pub mod body;
+pub mod collider;
+mod gjk;
+mod manifold;
+pub mod solver;
+
+pub use body::*;
+pub use collider::*;
+pub use solver::*;
@@ -0,0 +1,453 @@
+use crate::math::aabb::Aabb;
+use crate::math::bvh::Bvh;
+use crate::physics::collider::{Collider, PosedCollider};
+use crate::physics::gjk;
+use crate::physics::manifold::{Manifold, Pose};
+use crate::physics::{CollisionEvent, RigidBody, Velocity};
+use crate::scene::components::{CTransform3d, Parent};
+use crate::scene::hierarchy::world_matrix;
+use glam::{Mat3, Mat4, Quat, Vec3};
+use hecs::{Entity, World};
+use std::collections::BTreeMap;
+
+/// Fraction of the penetration corrected per step.
+const BAUMGARTE: f32 = 0.2;
+/// Penetration allowed without correction, keeps resting contacts from jittering.
+const SLOP: f32 = 0.005;
+/// Keeps bodies spawned deep inside each other from being shot apart.
+const MAX_RECOVERY_SPEED: f32 = 2.0;
+/// Approach speed below which contacts don't bounce.
+const RESTITUTION_THRESHOLD: f32 = 1.0;
+
+struct Body {
+    entity: Entity,
+    kind: RigidBody,
+    /// Child entities are placed by their parents and only push others.
+    simulated: bool,
+    position: Vec3,
+    rotation: Quat,
+    linear: Vec3,
+    angular: Vec3,
+    inverse_mass: f32,
+    /// Inverse inertia tensor in world space.
+    inverse_inertia_world: Mat3,
+    collider: Option<Collider>,
+}
+
+impl Body {
+    fn is_dynamic(&self) -> bool {
+        self.simulated && self.kind == RigidBody::Dynamic
+    }
+
+    fn pose(&self) -> Pose {
+        Pose {
+            position: self.position,
+            rotation: self.rotation,
+        }
+    }
+
+    fn velocity_at(&self, r: Vec3) -> Vec3 {
+        self.linear + self.angular.cross(r)
+    }
+
+    fn apply_impulse(&mut self, r: Vec3, impulse: Vec3) {
+        self.linear += impulse * self.inverse_mass;
+        self.angular += self.inverse_inertia_world * r.cross(impulse);
+    }
+}
+
+struct ContactConstraint {
+    a: usize,
+    b: usize,
+    key: (Entity, Entity),
+    point: usize,
+    normal: Vec3,
+    tangents: [Vec3; 2],
+    ra: Vec3,
+    rb: Vec3,
+    normal_mass: f32,
+    tangent_mass: [f32; 2],
+    /// Separating speed the solver aims for, from restitution or penetration recovery.
+    target: f32,
+    friction: f32,
+    normal_impulse: f32,
+    tangent_impulse: [f32; 2],
+}
+
+struct Pair {
+    manifold: Manifold,
+    /// At least one of the colliders is a sensor, the contact is only reported.
+    sensor: bool,
+}
+
+/// Rigid-body simulation of the entities with a [`RigidBody`] or [`Collider`] and a
+/// `CTransform3d`, stepped once per fixed tick. Bodies are convex and scale is ignored.
+///
+/// Contact state is kept in ordered maps and bodies are processed in entity order, so the same
+/// inputs give the same results.
+pub struct Physics {
+    pub gravity: Vec3,
+    /// Velocity solver iterations per step, more iterations make stacks stiffer.
+    pub iterations: u32,
+    pairs: BTreeMap<(Entity, Entity), Pair>,
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Self {
+            gravity: Vec3::new(0.0, -9.81, 0.0),
+            iterations: 10,
+            pairs: BTreeMap::new(),
+        }
+    }
+}
+
+impl Physics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_gravity(mut self, gravity: Vec3) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Pairs of entities whose colliders are touching, with the first entity of each pair
+    /// ordered before the second.
+    pub fn contacts(&self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.pairs.keys().copied()
+    }
+
+    /// Whether the colliders of the two entities are touching.
+    pub fn in_contact(&self, a: Entity, b: Entity) -> bool {
+        self.pairs.contains_key(&(a.min(b), a.max(b)))
+    }
+
+    /// Advances the simulation by `dt` and writes the results to `CTransform3d` and [`Velocity`].
+    /// Returns the collision events of this step.
+    pub fn step(&mut self, ecs: &mut World, dt: f32) -> Vec<CollisionEvent> {
+        if dt <= 0.0 {
+            return Vec::new();
+        }
+
+        // 1. 动态刚体缺少速度组件时补上
+        let missing: Vec<Entity> = ecs
+            .query::<(Entity, &RigidBody)>()
+            .without::<&Velocity>()
+            .iter()
+            .filter(|(_, body)| **body == RigidBody::Dynamic)
+            .map(|(id, _)| id)
+            .collect();
+        for id in missing {
+            let _ = ecs.insert_one(id, Velocity::default());
+        }
+
+        let mut bodies = gather_bodies(ecs);
+
+        // 2. 重力
+        for body in bodies.iter_mut().filter(|body| body.is_dynamic()) {
+            body.linear += self.gravity * dt;
+        }
+
+        // 3. 粗检测与精检测，更新接触流形
+        let previous: Vec<(Entity, Entity)> = self.pairs.keys().copied().collect();
+        self.update_pairs(&bodies);
+
+        // 4. 求解速度约束
+        let mut constraints = self.prepare_constraints(&bodies, dt);
+        for constraint in &constraints {
+            warm_start(&mut bodies, constraint);
+        }
+        for _ in 0..self.iterations {
+            for constraint in &mut constraints {
+                solve(&mut bodies, constraint);
+            }
+        }
+        for constraint in &constraints {
+            if let Some(pair) = self.pairs.get_mut(&constraint.key) {
+                let point = &mut pair.manifold.points[constraint.point];
+                point.normal_impulse = constraint.normal_impulse;
+                point.tangent_impulse = constraint.tangent_impulse;
+            }
+        }
+
+        // 5. 积分位置
+        for body in bodies.iter_mut() {
+            if !body.simulated || body.kind == RigidBody::Static {
+                continue;
+            }
+            body.position += body.linear * dt;
+            let spin = Quat::from_xyzw(body.angular.x, body.angular.y, body.angular.z, 0.0);
+            body.rotation = (body.rotation + spin * body.rotation * (0.5 * dt)).normalize();
+        }
+
+        // 6. 写回组件
+        for body in &bodies {
+            if !body.simulated || body.kind == RigidBody::Static {
+                continue;
+            }
+            if let Ok(mut transform) = ecs.get::<&mut CTransform3d>(body.entity) {
+                // 静止的物体不写回，避免每帧都标记变换已改变
+                if transform.position != body.position || transform.rotation != body.rotation {
+                    transform.position = body.position;
+                    transform.rotation = body.rotation;
+                }
+            }
+            if body.kind == RigidBody::Dynamic {
+                if let Ok(mut velocity) = ecs.get::<&mut Velocity>(body.entity) {
+                    velocity.linear = body.linear;
+                    velocity.angular = body.angular;
+                }
+            }
+        }
+
+        // 7. 对比前后的接触对生成事件
+        let mut events: Vec<CollisionEvent> = previous
+            .iter()
+            .filter(|key| !self.pairs.contains_key(key))
+            .map(|(a, b)| CollisionEvent::Stopped(*a, *b))
+            .collect();
+        events.extend(
+            self.pairs
+                .keys()
+                .filter(|key| previous.binary_search(key).is_err())
+                .map(|(a, b)| CollisionEvent::Started(*a, *b)),
+        );
+        events
+    }
+
+    fn update_pairs(&mut self, bodies: &[Body]) {
+        let aabbs: Vec<(Aabb, usize)> = bodies
+            .iter()
+            .enumerate()
+            .filter_map(|(index, body)| {
+                let local = body.collider.as_ref()?.local_aabb();
+                let matrix = Mat4::from_rotation_translation(body.rotation, body.position);
+                Some((local.transform_by_matrix(&matrix), index))
+            })
+            .collect();
+        let bvh = Bvh::build(aabbs.clone());
+
+        let mut pairs = BTreeMap::new();
+        let mut candidates = Vec::new();
+        for (aabb, i) in &aabbs {
+            candidates.clear();
+            bvh.query_aabb(aabb, &mut candidates);
+            candidates.sort_unstable();
+
+            for j in candidates.iter().copied().filter(|j| j > i) {
+                let (a, b) = (&bodies[*i], &bodies[j]);
+                let (Some(collider_a), Some(collider_b)) = (&a.collider, &b.collider) else {
+                    continue;
+                };
+                let sensor = collider_a.sensor || collider_b.sensor;
+                let moving = |body: &Body| !body.simulated || body.kind != RigidBody::Static;
+                // 至少一方是动态刚体，或传感器与移动的物体相交
+                if !(a.is_dynamic() || b.is_dynamic() || (sensor && (moving(a) || moving(b)))) {
+                    continue;
+                }
+
+                let posed_a = PosedCollider {
+                    shape: &collider_a.shape,
+                    position: a.position,
+                    rotation: a.rotation,
+                };
+                let posed_b = PosedCollider {
+                    shape: &collider_b.shape,
+                    position: b.position,
+                    rotation: b.rotation,
+                };
+                let Some(contact) = gjk::contact(&posed_a, &posed_b) else {
+                    continue;
+                };
+
+                // bodies 按实体排序，a 在 b 之前
+                let key = (a.entity, b.entity);
+                let mut pair = self.pairs.remove(&key).unwrap_or(Pair {
+                    manifold: Manifold::new(),
+                    sensor,
+                });
+                pair.sensor = sensor;
+                pair.manifold.update(&contact, a.pose(), b.pose());
+                pairs.insert(key, pair);
+            }
+        }
+
+        self.pairs = pairs;
+    }
+
+    fn prepare_constraints(&self, bodies: &[Body], dt: f32) -> Vec<ContactConstraint> {
+        let index: BTreeMap<Entity, usize> = bodies
+            .iter()
+            .enumerate()
+            .map(|(index, body)| (body.entity, index))
+            .collect();
+
+        let mut constraints = Vec::new();
+        for (key, pair) in self.pairs.iter().filter(|(_, pair)| !pair.sensor) {
+            let (a, b) = (index[&key.0], index[&key.1]);
+            let (body_a, body_b) = (&bodies[a], &bodies[b]);
+            let (Some(collider_a), Some(collider_b)) = (&body_a.collider, &body_b.collider) else {
+                continue;
+            };
+            let restitution = collider_a.restitution.max(collider_b.restitution);
+            let friction = (collider_a.friction * collider_b.friction).sqrt();
+
+            let normal = pair.manifold.normal;
+            let (u, v) = normal.any_orthonormal_pair();
+            let tangents = [u, v];
+
+            for (point_index, point) in pair.manifold.points.iter().enumerate() {
+                let ra = point.point - body_a.position;
+                let rb = point.point - body_b.position;
+                let effective_mass = |direction: Vec3| {
+                    let k = body_a.inverse_mass
+                        + body_b.inverse_mass
+                        + (body_a.inverse_inertia_world * ra.cross(direction))
+                            .cross(ra)
+                            .dot(direction)
+                        + (body_b.inverse_inertia_world * rb.cross(direction))
+                            .cross(rb)
+                            .dot(direction);
+                    if k > 0.0 {
+                        1.0 / k
+                    } else {
+                        0.0
+                    }
+                };
+
+                // 接近速度足够大时反弹，否则只修正穿透
+                let approach = (body_b.velocity_at(rb) - body_a.velocity_at(ra)).dot(normal);
+                let bounce = if approach < -RESTITUTION_THRESHOLD {
+                    -restitution * approach
+                } else {
+                    0.0
+                };
+                let recovery =
+                    (BAUMGARTE / dt * (point.depth - SLOP).max(0.0)).min(MAX_RECOVERY_SPEED);
+
+                constraints.push(ContactConstraint {
+                    a,
+                    b,
+                    key: *key,
+                    point: point_index,
+                    normal,
+                    tangents,
+                    ra,
+                    rb,
+                    normal_mass: effective_mass(normal),
+                    tangent_mass: [effective_mass(u), effective_mass(v)],
+                    target: bounce.max(recovery),
+                    friction,
+                    normal_impulse: point.normal_impulse,
+                    tangent_impulse: point.tangent_impulse,
+                });
+            }
+        }
+        constraints
+    }
+}
+
+fn gather_bodies(ecs: &World) -> Vec<Body> {
+    let mut bodies: Vec<Body> = ecs
+        .query::<(
+            Entity,
+            &CTransform3d,
+            Option<&RigidBody>,
+            Option<&Collider>,
+            Option<&Velocity>,
+            Option<&Parent>,
+        )>()
+        .iter()
+        .filter(|(_, _, body, collider, _, _)| body.is_some() || collider.is_some())
+        .map(|(entity, transform, body, collider, velocity, parent)| {
+            let kind = body.copied().unwrap_or(RigidBody::Static);
+            let simulated = parent.is_none();
+            let (position, rotation) = if simulated {
+                (transform.position, transform.rotation)
+            } else {
+                let (_, rotation, position) =
+                    world_matrix(ecs, entity).to_scale_rotation_translation();
+                (position, rotation)
+            };
+
+            let dynamic = simulated && kind == RigidBody::Dynamic;
+            let (mass, inertia) = collider.map_or((1.0, Vec3::ONE), |c| c.mass_properties());
+            let invert = |x: f32| if dynamic && x > 0.0 { 1.0 / x } else { 0.0 };
+            let inverse_inertia =
+                Vec3::new(invert(inertia.x), invert(inertia.y), invert(inertia.z));
+            let rotation_matrix = Mat3::from_quat(rotation);
+
+            let velocity = velocity
+                .filter(|_| simulated && kind != RigidBody::Static)
+                .copied()
+                .unwrap_or_default();
+
+            Body {
+                entity,
+                kind,
+                simulated,
+                position,
+                rotation,
+                linear: velocity.linear,
+                angular: velocity.angular,
+                inverse_mass: invert(mass),
+                inverse_inertia_world: rotation_matrix
+                    * Mat3::from_diagonal(inverse_inertia)
+                    * rotation_matrix.transpose(),
+                collider: collider.cloned(),
+            }
+        })
+        .collect();
+
+    // 查询顺序取决于 archetype，按实体排序保证结果可复现
+    bodies.sort_by_key(|body| body.entity);
+    bodies
+}
+
+fn pair_mut(bodies: &mut [Body], a: usize, b: usize) -> (&mut Body, &mut Body) {
+    if a < b {
+        let (left, right) = bodies.split_at_mut(b);
+        (&mut left[a], &mut right[0])
+    } else {
+        let (left, right) = bodies.split_at_mut(a);
+        (&mut right[0], &mut left[b])
+    }
+}
+
+fn warm_start(bodies: &mut [Body], constraint: &ContactConstraint) {
+    let impulse = constraint.normal * constraint.normal_impulse
+        + constraint.tangents[0] * constraint.tangent_impulse[0]
+        + constraint.tangents[1] * constraint.tangent_impulse[1];
+    let (a, b) = pair_mut(bodies, constraint.a, constraint.b);
+    a.apply_impulse(constraint.ra, -impulse);
+    b.apply_impulse(constraint.rb, impulse);
+}
+
+fn solve(bodies: &mut [Body], constraint: &mut ContactConstraint) {
+    let (a, b) = pair_mut(bodies, constraint.a, constraint.b);
+
+    // 摩擦力，上限取决于法向冲量
+    let limit = constraint.friction * constraint.normal_impulse;
+    for axis in 0..2 {
+        let tangent = constraint.tangents[axis];
+        let relative = b.velocity_at(constraint.rb) - a.velocity_at(constraint.ra);
+        let lambda = -relative.dot(tangent) * constraint.tangent_mass[axis];
+        let accumulated = (constraint.tangent_impulse[axis] + lambda).clamp(-limit, limit);
+        let applied = accumulated - constraint.tangent_impulse[axis];
+        constraint.tangent_impulse[axis] = accumulated;
+
+        a.apply_impulse(constraint.ra, -tangent * applied);
+        b.apply_impulse(constraint.rb, tangent * applied);
+    }
+
+    // 法向冲量只能推开，累计值不小于 0
+    let relative = b.velocity_at(constraint.rb) - a.velocity_at(constraint.ra);
+    let lambda = (constraint.target - relative.dot(constraint.normal)) * constraint.normal_mass;
+    let accumulated = (constraint.normal_impulse + lambda).max(0.0);
+    let applied = accumulated - constraint.normal_impulse;
+    constraint.normal_impulse = accumulated;
+
+    a.apply_impulse(constraint.ra, -constraint.normal * applied);
+    b.apply_impulse(constraint.rb, constraint.normal * applied);
+}
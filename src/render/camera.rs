@@ -1,4 +1,5 @@
 use crate::render::render_graph::BufferKey;
+use crate::window::WindowId;
 use glam::Mat4;
 
 /// This only manages CPU resources.
@@ -13,12 +14,48 @@ pub(crate) enum CameraType {
 pub(crate) struct ExtractedCameras {
     pub(crate) types: Vec<CameraType>,
     pub(crate) uniforms: Vec<CameraUniform>,
+    pub(crate) targets: Vec<WindowId>,
+    /// `RenderLayers` bitmasks.
+    pub(crate) layers: Vec<u32>,
 }
 
 impl ExtractedCameras {
-    pub(crate) fn add(&mut self, camera_type: CameraType, uniform: CameraUniform) {
+    pub(crate) fn add(
+        &mut self,
+        camera_type: CameraType,
+        uniform: CameraUniform,
+        target: WindowId,
+        layers: u32,
+    ) {
         self.types.push(camera_type);
         self.uniforms.push(uniform);
+        self.targets.push(target);
+        self.layers.push(layers);
+    }
+
+    /// The cameras rendering to `target`.
+    pub(crate) fn for_target(&self, target: WindowId) -> ExtractedCameras {
+        let mut cameras = ExtractedCameras::default();
+        for i in 0..self.targets.len() {
+            if self.targets[i] == target {
+                cameras.add(
+                    self.types[i].clone(),
+                    self.uniforms[i],
+                    target,
+                    self.layers[i],
+                );
+            }
+        }
+        cameras
+    }
+
+    /// Layers drawn by any camera of the type.
+    pub(crate) fn layers(&self, camera_type: CameraType) -> u32 {
+        self.types
+            .iter()
+            .zip(&self.layers)
+            .filter(|(t, _)| **t == camera_type)
+            .fold(0, |layers, (_, l)| layers | l)
     }
 
     pub fn get_buffer_key(&self) -> BufferKey {
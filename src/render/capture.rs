@@ -0,0 +1,119 @@
+use crate::render::RenderContext;
+use image::RgbaImage;
+use std::sync::mpsc::Sender;
+
+/// Frame capture requested by the logic thread, fulfilled after the next rendered frame.
+pub struct CaptureRequest {
+    pub(crate) sender: Sender<RgbaImage>,
+}
+
+/// Copy `texture` into a mappable buffer and block until it can be read back.
+///
+/// Only 8-bit RGBA/BGRA targets are supported, which covers the surface formats we pick
+/// and the headless offscreen target.
+pub(crate) fn read_texture(
+    render_context: &RenderContext,
+    texture: &wgpu::Texture,
+) -> Option<RgbaImage> {
+    let format = texture.format();
+    let swap_red_blue = match format {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        _ => {
+            log::error!("Frame capture: unsupported target format {:?}", format);
+            return None;
+        }
+    };
+
+    if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+        log::error!("Frame capture: target texture doesn't support COPY_SRC");
+        return None;
+    }
+
+    let width = texture.width();
+    let height = texture.height();
+
+    // 每行字节数必须按 COPY_BYTES_PER_ROW_ALIGNMENT (256) 对齐
+    let unpadded_bytes_per_row = width * 4;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+
+    let buffer = render_context
+        .device
+        .create_buffer(&wgpu::BufferDescriptor {
+            label: Some("frame capture buffer"),
+            size: padded_bytes_per_row as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+    let mut encoder = render_context
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Frame Capture Encoder"),
+        });
+
+    encoder.copy_texture_to_buffer(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+
+    render_context.queue.submit(Some(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (tx, rx) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = tx.send(result);
+    });
+
+    // 截图很少发生，这里直接阻塞等待 GPU 完成
+    if let Err(e) = render_context
+        .device
+        .poll(wgpu::PollType::wait_indefinitely())
+    {
+        log::error!("Frame capture: failed to wait for GPU: {:?}", e);
+        return None;
+    }
+
+    match rx.recv() {
+        Ok(Ok(())) => {}
+        _ => {
+            log::error!("Frame capture: failed to map readback buffer");
+            return None;
+        }
+    }
+
+    let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    {
+        let data = slice.get_mapped_range();
+        for row in data.chunks(padded_bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+        }
+    }
+    buffer.unmap();
+
+    if swap_red_blue {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    RgbaImage::from_raw(width, height, pixels)
+}
@@ -0,0 +1,213 @@
+use crate::render::RenderContext;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Most render graph nodes timed per frame, the rest are skipped.
+const MAX_TIMED_NODES: u32 = 64;
+/// Frame start and end, then one pair per node.
+const QUERY_COUNT: u32 = 2 + MAX_TIMED_NODES * 2;
+const QUERY_BUFFER_SIZE: u64 = QUERY_COUNT as u64 * 8;
+
+/// One frame of timestamps on its way back to the CPU.
+struct Readback {
+    buffer: wgpu::Buffer,
+    mapped: Arc<AtomicBool>,
+    /// In use by the GPU or waiting to be read.
+    active: bool,
+    /// Timed nodes in query order.
+    nodes: Vec<String>,
+    /// See [`crate::core::Profiler::frame`].
+    frame: u64,
+    submitted_at: Instant,
+}
+
+/// Hardware timestamps around the whole frame and each render graph node of the primary window.
+/// Results are read back a few frames later, without stalling.
+pub(crate) struct GpuProfiler {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    // Multi-buffered
+    readbacks: Vec<Readback>,
+    current: usize,
+    /// The current frame is being timed.
+    recording: bool,
+    frame_nodes: Vec<String>,
+    frame: u64,
+}
+
+impl GpuProfiler {
+    /// None if the device was created without timestamp queries.
+    pub(crate) fn new(render_context: &RenderContext) -> Option<Self> {
+        if !render_context.features.gpu_timestamps {
+            log::warn!("GPU Profiling: Disabled (hardware doesn't support TIMESTAMP_QUERY_INSIDE_ENCODERS)");
+            return None;
+        }
+        log::info!("GPU Profiling: Enabled (using hardware timestamps)");
+
+        let device = &render_context.device;
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("timestamp query set"),
+            count: QUERY_COUNT,
+            ty: wgpu::QueryType::Timestamp,
+        });
+
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("timestamp resolve buffer"),
+            size: QUERY_BUFFER_SIZE,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let readbacks = (0..render_context.frames_in_flight)
+            .map(|i| Readback {
+                buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(&format!("timestamp destination buffer {}", i)),
+                    size: QUERY_BUFFER_SIZE,
+                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                }),
+                mapped: Arc::new(AtomicBool::new(false)),
+                active: false,
+                nodes: Vec::new(),
+                frame: 0,
+                submitted_at: Instant::now(),
+            })
+            .collect();
+
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            readbacks,
+            current: 0,
+            recording: false,
+            frame_nodes: Vec::new(),
+            frame: 0,
+        })
+    }
+
+    /// Start timing a frame, `frame` tags its spans for the profiler. Skipped while the next
+    /// readback buffer is still in use.
+    pub(crate) fn begin_frame(&mut self, encoder: &mut wgpu::CommandEncoder, frame: u64) {
+        self.recording = !self.readbacks[self.current].active;
+        if self.recording {
+            self.frame = frame;
+            self.frame_nodes.clear();
+            encoder.write_timestamp(&self.query_set, 0);
+        }
+    }
+
+    /// Returns the query index to pass to [`GpuProfiler::end_node`], if the node is timed.
+    pub(crate) fn begin_node(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        name: &str,
+    ) -> Option<u32> {
+        if !self.recording || self.frame_nodes.len() as u32 >= MAX_TIMED_NODES {
+            return None;
+        }
+
+        let query = 2 + self.frame_nodes.len() as u32 * 2;
+        encoder.write_timestamp(&self.query_set, query);
+        self.frame_nodes.push(name.to_string());
+        Some(query)
+    }
+
+    pub(crate) fn end_node(&mut self, encoder: &mut wgpu::CommandEncoder, query: u32) {
+        encoder.write_timestamp(&self.query_set, query + 1);
+    }
+
+    /// Write the end timestamp and copy the results to the readback buffer.
+    pub(crate) fn end_frame(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if !self.recording {
+            return;
+        }
+
+        encoder.write_timestamp(&self.query_set, 1);
+
+        let query_count = 2 + self.frame_nodes.len() as u32 * 2;
+        let readback = &mut self.readbacks[self.current];
+        encoder.resolve_query_set(&self.query_set, 0..query_count, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &readback.buffer,
+            0,
+            query_count as u64 * 8,
+        );
+
+        readback.nodes = std::mem::take(&mut self.frame_nodes);
+        readback.frame = self.frame;
+        readback.active = true;
+    }
+
+    /// Map the readback buffer of the submitted frame.
+    pub(crate) fn after_submit(&mut self) {
+        if !self.recording {
+            return;
+        }
+        self.recording = false;
+
+        let readback = &mut self.readbacks[self.current];
+        readback.submitted_at = Instant::now();
+
+        let mapped = readback.mapped.clone();
+        readback
+            .buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                if result.is_ok() {
+                    mapped.store(true, Ordering::Release);
+                }
+            });
+
+        self.current = (self.current + 1) % self.readbacks.len();
+    }
+
+    /// Read the finished frames into the frame GPU time and the profiler.
+    pub(crate) fn collect(&mut self, render_context: &RenderContext) {
+        let period = render_context.queue.get_timestamp_period() as f64;
+
+        for readback in &mut self.readbacks {
+            if !readback.mapped.load(Ordering::Acquire) {
+                continue;
+            }
+
+            {
+                let data = readback.buffer.slice(..).get_mapped_range();
+                let timestamps: &[u64] = bytemuck::cast_slice(&data[..]);
+                let nanos = |from: u64, to: u64| to.wrapping_sub(from) as f64 * period;
+
+                let frame_nanos = nanos(timestamps[0], timestamps[1]);
+                render_context
+                    .gpu_time
+                    .store(frame_nanos as u64, Ordering::Relaxed);
+
+                // GPU 时钟与 CPU 时钟无关，以提交时刻为帧起点对齐
+                let profiler = &render_context.profiler;
+                let frame_start = readback.submitted_at;
+                profiler.record_gpu_span(
+                    "frame",
+                    frame_start,
+                    Duration::from_nanos(frame_nanos as u64),
+                    readback.frame,
+                );
+                for (i, node) in readback.nodes.iter().enumerate() {
+                    let start = timestamps[2 + i * 2];
+                    let end = timestamps[3 + i * 2];
+                    profiler.record_gpu_span(
+                        node,
+                        frame_start + Duration::from_nanos(nanos(timestamps[0], start) as u64),
+                        Duration::from_nanos(nanos(start, end) as u64),
+                        readback.frame,
+                    );
+                }
+                profiler.gpu_frame_done(readback.frame);
+            }
+
+            readback.buffer.unmap();
+            readback.mapped.store(false, Ordering::Release);
+            readback.active = false;
+        }
+    }
+}
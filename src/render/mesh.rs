@@ -72,6 +72,8 @@ pub struct ExtractedMesh {
     pub(crate) transform: Transform3d,
     pub(crate) mesh_id: MeshId,
     pub(crate) material_id: Option<MaterialId>,
+    /// `RenderLayers` bitmask.
+    pub(crate) layers: u32,
 }
 
 #[repr(C)]
@@ -196,8 +198,11 @@ pub struct MeshRenderResources {
 #[derive(Clone, Copy)]
 pub(crate) struct MeshInstanceInfo {
     pub(crate) mesh_id: MeshId,
+    pub(crate) material_id: Option<MaterialId>,
     pub(crate) base_instance: u32,
     pub(crate) instance_count: u32,
+    /// `RenderLayers` bitmask of the instances.
+    pub(crate) layers: u32,
 }
 
 // impl MeshRenderResources {
@@ -6,6 +6,9 @@ pub struct MeshAllocator {
 
     pub vertex_count: u32,
     pub index_count: u32,
+
+    /// CPU 端副本，设备丢失后用于重新上传，见 [`MeshAllocator::with_retained_data`]
+    retained: Option<(Vec<Vertex3d>, Vec<u32>)>,
 }
 
 const DEFAULT_VERTEX_BUFFER_SIZE: u64 = 128 * 1024 * 1024; // 128MB
@@ -13,6 +16,30 @@ const DEFAULT_INDEX_BUFFER_SIZE: u64 = 64 * 1024 * 1024; // 64MB
 
 impl MeshAllocator {
     pub fn new(device: &wgpu::Device) -> Self {
+        let (vertex_buffer, index_buffer) = Self::create_buffers(device);
+
+        Self {
+            vertex_buffer,
+            index_buffer,
+            vertex_count: 0,
+            index_count: 0,
+            retained: None,
+        }
+    }
+
+    /// Keep a CPU copy of all allocated meshes, so they can be uploaded again after a device
+    /// loss. This doubles the memory used by meshes.
+    pub fn with_retained_data(mut self, retain: bool) -> Self {
+        self.retained = retain.then(|| (Vec::new(), Vec::new()));
+        self
+    }
+
+    /// Whether allocated meshes keep a CPU copy, see [`Self::with_retained_data`].
+    pub fn retains_data(&self) -> bool {
+        self.retained.is_some()
+    }
+
+    fn create_buffers(device: &wgpu::Device) -> (wgpu::Buffer, wgpu::Buffer) {
         let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Global Vertex Buffer"),
             size: DEFAULT_VERTEX_BUFFER_SIZE,
@@ -27,11 +54,23 @@ impl MeshAllocator {
             mapped_at_creation: false,
         });
 
-        Self {
-            vertex_buffer,
-            index_buffer,
-            vertex_count: 0,
-            index_count: 0,
+        (vertex_buffer, index_buffer)
+    }
+
+    /// Recreate the buffers on a new device and upload all allocated meshes again. Without
+    /// retained data the buffers start out empty, and the meshes have to be allocated again.
+    pub(crate) fn restore(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        (self.vertex_buffer, self.index_buffer) = Self::create_buffers(device);
+
+        match &self.retained {
+            Some((vertices, indices)) => {
+                queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(vertices));
+                queue.write_buffer(&self.index_buffer, 0, bytemuck::cast_slice(indices));
+            }
+            None => {
+                self.vertex_count = 0;
+                self.index_count = 0;
+            }
         }
     }
 
@@ -74,6 +113,10 @@ impl MeshAllocator {
             bytemuck::cast_slice(indices),
         );
 
+        if let Some((retained_vertices, retained_indices)) = &mut self.retained {
+            retained_vertices.extend_from_slice(vertices);
+            retained_indices.extend_from_slice(indices);
+        }
         self.vertex_count += vertices.len() as u32;
         self.index_count += indices.len() as u32;
 
@@ -81,7 +124,7 @@ impl MeshAllocator {
     }
 
     pub fn update(
-        &self,
+        &mut self,
         queue: &wgpu::Queue,
         v_offset: u32,
         i_offset: u32,
@@ -108,5 +151,19 @@ impl MeshAllocator {
             (i_offset as usize * size_of::<u32>()) as u64,
             bytemuck::cast_slice(indices),
         );
+
+        let Some((retained_vertices, retained_indices)) = &mut self.retained else {
+            return;
+        };
+        let v_range = v_offset as usize..v_offset as usize + vertices.len();
+        let i_range = i_offset as usize..i_offset as usize + indices.len();
+        if retained_vertices.len() < v_range.end {
+            retained_vertices.resize(v_range.end, bytemuck::Zeroable::zeroed());
+        }
+        if retained_indices.len() < i_range.end {
+            retained_indices.resize(i_range.end, 0);
+        }
+        retained_vertices[v_range].copy_from_slice(vertices);
+        retained_indices[i_range].copy_from_slice(indices);
     }
 }
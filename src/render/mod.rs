@@ -1,10 +1,12 @@
 pub(crate) mod atlas;
+pub(crate) mod capture;
 pub(crate) mod gizmo;
+pub(crate) mod gpu_profiler;
 pub(crate) mod light;
 pub(crate) mod mesh;
 pub(crate) mod mesh_allocator;
 pub(crate) mod render_context;
-pub(crate) mod render_graph;
+pub mod render_graph;
 pub(crate) mod texture;
 pub(crate) mod vertex;
 
@@ -1,16 +1,23 @@
+use crate::core::Track;
+use crate::render::camera::CameraType;
+use crate::render::capture::{read_texture, CaptureRequest};
+use crate::render::gpu_profiler::GpuProfiler;
 use crate::render::material::{MaterialCache, MaterialId, MaterialStandard, MaterialUniform};
 use crate::render::mesh_allocator::MeshAllocator;
 use crate::render::render_graph::RenderGraph;
-use crate::render::render_world::Extracted;
+use crate::render::render_world::{Extracted, RenderGraphCallback};
 use crate::render::sky::{prepare_sky, SkyImportedResources};
 use crate::render::sprite::ExtractedSprite2d;
 use crate::render::{
     ExtractedMesh, Instance, InstanceRaw, MeshCache, MeshId, MeshInstanceInfo, MeshMetadata,
-    RenderContext, TextureCache, TextureId,
+    RenderContext, TextureCache, TextureId, MAX_BINDLESS_TEXTURES,
 };
 use crate::scene::Bvh;
-use std::collections::HashMap;
+use crate::window::WindowId;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, RwLock};
+use std::time::Instant;
+use winit::window::Window;
 
 pub struct PreparedFrame {
     pub(crate) extracted: Extracted,
@@ -41,21 +48,112 @@ pub struct PreparedFrame {
     // ---------------------------
 }
 
+impl PreparedFrame {
+    /// Ranges of `indirect_commands` drawn by a camera with the `RenderLayers` bitmask. Commands
+    /// are sorted by layers, so each bitmask is one contiguous range.
+    pub(crate) fn indirect_ranges(&self, camera_layers: u32) -> Vec<std::ops::Range<u32>> {
+        let mut ranges: Vec<std::ops::Range<u32>> = Vec::new();
+        for (i, info) in self.mesh_infos.iter().enumerate() {
+            if info.layers & camera_layers == 0 {
+                continue;
+            }
+            let i = i as u32;
+            match ranges.last_mut() {
+                Some(range) if range.end == i => range.end += 1,
+                _ => ranges.push(i..i + 1),
+            }
+        }
+        ranges
+    }
+}
+
 pub struct TransparentBatch {
     pub mesh_id: MeshId,
+    pub material_id: Option<MaterialId>,
     pub instance_range: std::ops::Range<u32>,
+    /// `RenderLayers` bitmask of the instances.
+    pub layers: u32,
+}
+
+/// Name of the pooled texture headless apps render into.
+pub(crate) const OFFSCREEN_OUTPUT: &str = "offscreen_output";
+
+/// Standard render graph with the app's modifications applied.
+fn build_render_graph(callbacks: &[RenderGraphCallback]) -> RenderGraph {
+    let mut render_graph = RenderGraph::new();
+    render_graph.setup_standard_nodes();
+    for configure in callbacks {
+        configure(&mut render_graph);
+    }
+    render_graph
+}
+
+/// Pooled offscreen target used in place of the surface texture by headless apps.
+/// It follows the size and format of the surface config, so resizing recreates it.
+fn offscreen_output(graph: &mut RenderGraph, render_context: &RenderContext) -> crate::render::Texture {
+    let config = &render_context.surface_config;
+    graph.pool.acquire_persistent_texture(
+        &render_context.device,
+        OFFSCREEN_OUTPUT,
+        crate::render::render_graph::TextureKey::d2(
+            config.width,
+            config.height,
+            config.format,
+            config.usage | wgpu::TextureUsages::RENDER_ATTACHMENT,
+        ),
+    )
 }
 
 pub enum RenderCommand {
     Render(Extracted),
-    Resize(u32, u32),
+    /// Resize a window's surface (physical pixels).
+    Resize(WindowId, u32, u32),
+    /// Read back the next rendered frame of the primary window.
+    Capture(CaptureRequest),
+    /// Reconfigure the surfaces with a new present mode (e.g. toggling vsync).
+    SetPresentMode(wgpu::PresentMode),
+    /// Start rendering to a newly created window of the given physical size.
+    AddWindow(WindowId, WindowSurface, u32, u32),
+    RemoveWindow(WindowId),
+    /// The logic thread has re-uploaded the shared resources to the recreated device.
+    ResourcesRestored,
+    /// Wait for the GPU to finish and stop the render thread.
+    Shutdown,
 }
 
-/// 运行在独立线程的渲染后端
-pub struct RenderBackend {
+/// A window's surface, with the window kept to recreate the surface when it is lost.
+pub struct WindowSurface {
+    pub(crate) window: Arc<Window>,
     pub(crate) surface: wgpu::Surface<'static>,
+}
+
+impl WindowSurface {
+    fn recreate(&mut self, render_context: &RenderContext, config: &wgpu::SurfaceConfiguration) {
+        match render_context.instance.create_surface(self.window.clone()) {
+            Ok(surface) => {
+                surface.configure(&render_context.device, config);
+                self.surface = surface;
+            }
+            Err(err) => log::error!("Failed to recreate lost surface: {}", err),
+        }
+    }
+}
+
+/// 窗口的渲染目标，每个窗口有自己的交换链配置和渲染图（资源池跟随窗口尺寸）
+pub(crate) struct WindowTarget {
+    /// 无窗口（headless）时为 None，此时渲染到离屏纹理
+    pub(crate) surface: Option<WindowSurface>,
+    pub(crate) surface_config: wgpu::SurfaceConfiguration,
     /// 渲染图，各帧共享
     pub(crate) render_graph: RenderGraph,
+}
+
+/// 运行在独立线程的渲染后端
+pub struct RenderBackend {
+    /// 按 id 排序，主窗口最先渲染
+    pub(crate) windows: BTreeMap<WindowId, WindowTarget>,
+    /// 应用于每个新窗口的渲染图
+    graph_callbacks: Vec<RenderGraphCallback>,
     // 保存一些各帧共享的资源
     pub(crate) dummy_2d_texture: Arc<wgpu::Texture>,
     pub(crate) dummy_2d_view: wgpu::TextureView,
@@ -71,19 +169,18 @@ pub struct RenderBackend {
     bind_group_layouts: HashMap<String, wgpu::BindGroupLayout>,
     pipeline_layouts: HashMap<String, wgpu::PipelineLayout>,
 
-    // GPU Profiling (Multi-buffered)
-    timestamp_query_set: Option<wgpu::QuerySet>,
-    timestamp_resolve_buffer: Option<wgpu::Buffer>,
-    timestamp_destination_buffers: Vec<wgpu::Buffer>,
-    timestamp_mapped_flags: Vec<Arc<std::sync::atomic::AtomicBool>>,
-    timestamp_active: Vec<bool>, // 新增：追踪缓冲区是否正在被 GPU 或 CPU 使用
-    current_timestamp_index: usize,
+    // GPU Profiling
+    pub(crate) gpu_profiler: Option<GpuProfiler>,
+
+    /// 等待下一帧完成后回读的截图请求
+    pub(crate) pending_captures: Vec<CaptureRequest>,
 }
 
 impl RenderBackend {
     pub fn new(
         render_server: &RenderContext,
-        surface: wgpu::Surface<'static>,
+        surface: Option<WindowSurface>,
+        graph_callbacks: Vec<RenderGraphCallback>,
         imported_texture_cache: Arc<RwLock<TextureCache>>,
         imported_mesh_cache: Arc<RwLock<MeshCache>>,
         imported_material_cache: Arc<RwLock<MaterialCache>>,
@@ -131,66 +228,23 @@ impl RenderBackend {
             ..Default::default()
         });
 
-        let mut render_graph = RenderGraph::new();
-        render_graph.setup_standard_nodes();
-
-        // --- GPU Profiling Setup ---
-        let mut timestamp_query_set = None;
-        let mut timestamp_resolve_buffer = None;
-        let mut timestamp_destination_buffers = Vec::new();
-        let mut timestamp_mapped_flags = Vec::new();
-        let mut timestamp_active = Vec::new();
-
-        let has_basic_query = render_server
-            .device
-            .features()
-            .contains(wgpu::Features::TIMESTAMP_QUERY);
-        let has_encoder_query = render_server
-            .device
-            .features()
-            .contains(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS);
-
-        if has_basic_query && has_encoder_query {
-            log::info!("GPU Profiling: Enabled (using hardware timestamps)");
-            timestamp_query_set = Some(render_server.device.create_query_set(
-                &wgpu::QuerySetDescriptor {
-                    label: Some("timestamp query set"),
-                    count: 2,
-                    ty: wgpu::QueryType::Timestamp,
-                },
-            ));
-
-            timestamp_resolve_buffer =
-                Some(render_server.device.create_buffer(&wgpu::BufferDescriptor {
-                    label: Some("timestamp resolve buffer"),
-                    size: 16,
-                    usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
-                    mapped_at_creation: false,
-                }));
-
-            for i in 0..render_server.frames_in_flight {
-                timestamp_destination_buffers.push(render_server.device.create_buffer(
-                    &wgpu::BufferDescriptor {
-                        label: Some(&format!("timestamp destination buffer {}", i)),
-                        size: 16,
-                        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
-                        mapped_at_creation: false,
-                    },
-                ));
-                timestamp_mapped_flags.push(Arc::new(std::sync::atomic::AtomicBool::new(false)));
-                timestamp_active.push(false);
-            }
-        } else {
-            log::warn!("GPU Profiling: Disabled (hardware doesn't support TIMESTAMP_QUERY_INSIDE_ENCODERS)");
-        }
+        let mut windows = BTreeMap::new();
+        windows.insert(
+            WindowId::PRIMARY,
+            WindowTarget {
+                surface,
+                surface_config: render_server.surface_config.clone(),
+                render_graph: build_render_graph(&graph_callbacks),
+            },
+        );
 
         Self {
-            surface,
+            windows,
+            graph_callbacks,
             dummy_2d_texture: Arc::new(dummy_2d_texture),
             dummy_2d_view,
             dummy_cube_texture: Arc::new(dummy_cube_texture),
             dummy_cube_view,
-            render_graph,
             sky_imported_resources,
             imported_texture_cache,
             imported_material_cache,
@@ -198,12 +252,8 @@ impl RenderBackend {
             imported_mesh_allocator,
             bind_group_layouts: Default::default(),
             pipeline_layouts: Default::default(),
-            timestamp_query_set,
-            timestamp_resolve_buffer,
-            timestamp_destination_buffers,
-            timestamp_mapped_flags,
-            timestamp_active,
-            current_timestamp_index: 0,
+            gpu_profiler: GpuProfiler::new(render_server),
+            pending_captures: Vec::new(),
         }
     }
 
@@ -211,150 +261,299 @@ impl RenderBackend {
         let cpu_render_start = std::time::Instant::now();
 
         // 处理旧数据并释放缓冲区
-        self.process_timestamps(render_context);
-
-        let surface_texture = match self.surface.get_current_texture() {
-            wgpu::CurrentSurfaceTexture::Success(texture) => texture,
-            wgpu::CurrentSurfaceTexture::Suboptimal(texture) => {
-                // 不要在这里调用 configure，因为 texture 还没有被释放。
-                // 次优状态下依然可以渲染，配置留给专门的 Resize 指令即可。
-                texture
-            }
-            wgpu::CurrentSurfaceTexture::Lost | wgpu::CurrentSurfaceTexture::Outdated => {
-                self.surface.configure(&render_context.device, &render_context.surface_config);
-                return;
-            }
-            wgpu::CurrentSurfaceTexture::Timeout | wgpu::CurrentSurfaceTexture::Occluded => {
-                return;
-            }
-            wgpu::CurrentSurfaceTexture::Validation => {
-                log::error!("WGPU Surface Validation Error");
-                return;
+        if let Some(gpu_profiler) = &mut self.gpu_profiler {
+            gpu_profiler.collect(render_context);
+        }
+
+        // 主窗口总是渲染，其他窗口只在有摄像机时渲染
+        let targets: Vec<WindowId> = self
+            .windows
+            .keys()
+            .copied()
+            .filter(|id| *id == WindowId::PRIMARY || extracted.cameras.targets.contains(id))
+            .collect();
+
+        if !targets.is_empty() {
+            // 只保留至少一个摄像机绘制的图层上的物体，每个摄像机绘制时再按自己的图层过滤
+            let mesh_layers = extracted.cameras.layers(CameraType::D3);
+            extracted
+                .meshes
+                .retain(|mesh| mesh.layers & mesh_layers != 0);
+            let sprite_layers = extracted.cameras.layers(CameraType::D2);
+            extracted
+                .sprites
+                .retain(|sprite| sprite.layers & sprite_layers != 0);
+
+            // 材质、BVH 与实例数据由所有窗口共用，每帧只准备一次
+            let cameras = std::mem::take(&mut extracted.cameras);
+            let prepare_start = Instant::now();
+            let mut prepared_frame = self.prepare(render_context, extracted);
+            render_context
+                .profiler
+                .record(Track::Render, "prepare", prepare_start);
+
+            for id in targets {
+                prepared_frame.extracted.cameras = cameras.for_target(id);
+                self.prepare_transparent(render_context, &mut prepared_frame);
+                self.render_window(render_context, id, &prepared_frame, cpu_render_start);
             }
+        }
+
+        // 必须调用 poll(Poll) 来推进异步映射的进度，但这不会阻塞线程
+        let _ = render_context.device.poll(wgpu::PollType::Poll);
+
+        render_context.profiler.end_frame();
+    }
+
+    /// Render the cameras targeting one window, `prepared_frame` holds only their cameras. GPU
+    /// timing and captures only cover the primary window.
+    fn render_window(
+        &mut self,
+        render_context: &RenderContext,
+        id: WindowId,
+        prepared_frame: &PreparedFrame,
+        cpu_render_start: std::time::Instant,
+    ) {
+        let Some(target) = self.windows.get_mut(&id) else {
+            return;
+        };
+        let is_primary = id == WindowId::PRIMARY;
+
+        // 节点从 render_context 读取输出尺寸与格式，因此换成该窗口的配置
+        let mut window_context = render_context.clone();
+        window_context.surface_config = target.surface_config.clone();
+        let render_context = &window_context;
+
+        let surface_texture = match &mut target.surface {
+            Some(window_surface) => match window_surface.surface.get_current_texture() {
+                wgpu::CurrentSurfaceTexture::Success(texture) => Some(texture),
+                wgpu::CurrentSurfaceTexture::Suboptimal(texture) => {
+                    // 不要在这里调用 configure，因为 texture 还没有被释放。
+                    // 次优状态下依然可以渲染，配置留给专门的 Resize 指令即可。
+                    Some(texture)
+                }
+                wgpu::CurrentSurfaceTexture::Outdated => {
+                    window_surface
+                        .surface
+                        .configure(&render_context.device, &target.surface_config);
+                    return;
+                }
+                wgpu::CurrentSurfaceTexture::Lost => {
+                    log::warn!("Surface of window {:?} lost, recreating", id);
+                    window_surface.recreate(render_context, &target.surface_config);
+                    return;
+                }
+                wgpu::CurrentSurfaceTexture::Timeout | wgpu::CurrentSurfaceTexture::Occluded => {
+                    return;
+                }
+                wgpu::CurrentSurfaceTexture::Validation => {
+                    log::error!("WGPU Surface Validation Error");
+                    return;
+                }
+            },
+            None => None,
+        };
+
+        let mut graph = std::mem::take(&mut target.render_graph);
+
+        let output_texture = match &surface_texture {
+            Some(surface_texture) => surface_texture.texture.clone(),
+            None => offscreen_output(&mut graph, render_context).texture,
         };
 
-        let final_output_view = surface_texture
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        let final_output_view = output_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        // 1. Prepare frame data
-        let prepared_frame = self.prepare(render_context, &mut extracted);
+        // Run graph and record render commands
+        let mut start_encoder =
+            render_context
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("GPU Start Timer"),
+                });
+        if let (true, Some(gpu_profiler)) = (is_primary, &mut self.gpu_profiler) {
+            gpu_profiler.begin_frame(&mut start_encoder, render_context.profiler.frame());
+        }
 
-        // 2. Run graph and record render commands
-        let mut graph = std::mem::take(&mut self.render_graph);
-        let cmd_buf = graph.run(render_context, self, &prepared_frame, &final_output_view);
-        self.render_graph = graph;
+        let cmd_buf = graph.run(render_context, self, prepared_frame, &final_output_view);
+        if let Some(target) = self.windows.get_mut(&id) {
+            target.render_graph = graph;
+        }
 
         // 记录 CPU Render Time (仅包含命令录制和数据准备，不包含 GPU 等待)
-        render_context.render_cpu_time.store(
-            cpu_render_start.elapsed().as_nanos() as u64,
-            std::sync::atomic::Ordering::Relaxed,
-        );
+        if is_primary {
+            render_context.render_cpu_time.store(
+                cpu_render_start.elapsed().as_nanos() as u64,
+                std::sync::atomic::Ordering::Relaxed,
+            );
+        }
 
         // --- 提交渲染工作和时间戳解析 ---
-        let mut submission = Vec::new();
-        let mut timestamp_recorded = false;
+        let mut end_encoder =
+            render_context
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("GPU End Timer"),
+                });
+        if let Some(gpu_profiler) = &mut self.gpu_profiler {
+            gpu_profiler.end_frame(&mut end_encoder);
+        }
 
-        if let (Some(query_set), Some(resolve_buf)) =
-            (&self.timestamp_query_set, &self.timestamp_resolve_buffer)
-        {
-            // 关键修复：只有当缓冲区不处于 Active (映射中) 时才使用它
-            if !self.timestamp_active[self.current_timestamp_index] {
-                // 1. 创建起始时间戳
-                let mut start_encoder =
-                    render_context
-                        .device
-                        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                            label: Some("GPU Start Timer"),
-                        });
-                start_encoder.write_timestamp(query_set, 0);
-                submission.push(start_encoder.finish());
-
-                // 2. 加入主渲染任务
-                submission.push(cmd_buf);
-
-                // 3. 创建结束时间戳并解析
-                let mut end_encoder =
-                    render_context
-                        .device
-                        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                            label: Some("GPU End Timer"),
-                        });
-                end_encoder.write_timestamp(query_set, 1);
-
-                let dest_buf = &self.timestamp_destination_buffers[self.current_timestamp_index];
-                end_encoder.resolve_query_set(query_set, 0..2, resolve_buf, 0);
-                end_encoder.copy_buffer_to_buffer(resolve_buf, 0, dest_buf, 0, 16);
-                submission.push(end_encoder.finish());
-
-                self.timestamp_active[self.current_timestamp_index] = true;
-                timestamp_recorded = true;
-            } else {
-                submission.push(cmd_buf);
+        render_context
+            .queue
+            .submit([start_encoder.finish(), cmd_buf, end_encoder.finish()]);
+
+        // 在 present 之前回读最终输出（tonemapping 与 sprite 之后）
+        if is_primary && !self.pending_captures.is_empty() {
+            let image = read_texture(render_context, &output_texture);
+            for request in self.pending_captures.drain(..) {
+                if let Some(image) = &image {
+                    let _ = request.sender.send(image.clone());
+                }
             }
-        } else {
-            submission.push(cmd_buf);
         }
 
-        render_context.queue.submit(submission);
-        surface_texture.present();
+        if let Some(surface_texture) = surface_texture {
+            surface_texture.present();
+        }
+
+        if let Some(gpu_profiler) = &mut self.gpu_profiler {
+            gpu_profiler.after_submit();
+        }
+    }
+
+    pub(crate) fn add_window(
+        &mut self,
+        render_context: &RenderContext,
+        id: WindowId,
+        window_surface: WindowSurface,
+        width: u32,
+        height: u32,
+    ) {
+        let surface = &window_surface.surface;
+        let Some(mut surface_config) =
+            surface.get_default_config(&render_context.adapter, width, height)
+        else {
+            log::error!("Surface of window {:?} unsupported by adapter", id);
+            return;
+        };
+
+        // 与主窗口保持相同的呈现模式（不支持时退回 Fifo）
+        let capabilities = surface.get_capabilities(&render_context.adapter);
+        let present_mode = render_context.surface_config.present_mode;
+        if capabilities.present_modes.contains(&present_mode) {
+            surface_config.present_mode = present_mode;
+        }
+        if capabilities.usages.contains(wgpu::TextureUsages::COPY_SRC) {
+            surface_config.usage |= wgpu::TextureUsages::COPY_SRC;
+        }
 
-        if timestamp_recorded {
-            let dest_buf = &self.timestamp_destination_buffers[self.current_timestamp_index];
-            let flag = self.timestamp_mapped_flags[self.current_timestamp_index].clone();
+        surface.configure(&render_context.device, &surface_config);
 
-            dest_buf
-                .slice(..)
-                .map_async(wgpu::MapMode::Read, move |result| {
-                    if result.is_ok() {
-                        flag.store(true, std::sync::atomic::Ordering::Release);
-                    }
-                });
+        self.windows.insert(
+            id,
+            WindowTarget {
+                surface: Some(window_surface),
+                surface_config,
+                render_graph: build_render_graph(&self.graph_callbacks),
+            },
+        );
+    }
 
-            self.current_timestamp_index =
-                (self.current_timestamp_index + 1) % self.timestamp_destination_buffers.len();
+    pub(crate) fn remove_window(&mut self, id: WindowId) {
+        self.windows.remove(&id);
+    }
+
+    pub(crate) fn resize(&mut self, render_context: &RenderContext, id: WindowId, w: u32, h: u32) {
+        let Some(target) = self.windows.get_mut(&id) else {
+            return;
+        };
+
+        target.surface_config.width = w;
+        target.surface_config.height = h;
+        if let Some(window_surface) = &target.surface {
+            window_surface
+                .surface
+                .configure(&render_context.device, &target.surface_config);
         }
 
-        // 必须调用 poll(Poll) 来推进异步映射的进度，但这不会阻塞线程
-        let _ = render_context.device.poll(wgpu::PollType::Poll);
+        // 关键修复：缩放后不仅清理 BindGroup，也要清空旧分辨率的瞬时资源池，防止显存泄露
+        target.render_graph.pool.clear_bind_group_cache();
+        target.render_graph.pool.clear_transient_pools();
     }
 
-    fn process_timestamps(&mut self, render_context: &RenderContext) {
-        // 检查所有缓冲区，看看哪个已经准备好读取了
-        for (i, buffer) in self.timestamp_destination_buffers.iter().enumerate() {
-            let flag = &self.timestamp_mapped_flags[i];
-
-            if flag.load(std::sync::atomic::Ordering::Acquire) {
-                let slice = buffer.slice(..);
-                {
-                    let data = slice.get_mapped_range();
-                    let timestamps: &[u64] = bytemuck::cast_slice(&data[..]);
-                    if timestamps.len() >= 2 {
-                        let diff = timestamps[1].wrapping_sub(timestamps[0]);
-                        let period = render_context.queue.get_timestamp_period();
-                        let gpu_nanos = diff as f64 * period as f64;
-                        render_context
-                            .gpu_time
-                            .store(gpu_nanos as u64, std::sync::atomic::Ordering::Relaxed);
-                    }
-                }
-                buffer.unmap();
-                flag.store(false, std::sync::atomic::Ordering::Release);
-                self.timestamp_active[i] = false; // 释放标志，该缓冲区现在可以再次被 GPU 使用
+    /// Windows not supporting `present_mode` keep their current mode.
+    pub(crate) fn set_present_mode(
+        &mut self,
+        render_context: &RenderContext,
+        present_mode: wgpu::PresentMode,
+    ) {
+        for target in self.windows.values_mut() {
+            let Some(surface) = target.surface.as_ref().map(|s| &s.surface) else {
+                continue;
+            };
+
+            let supported = surface.get_capabilities(&render_context.adapter).present_modes;
+            if supported.contains(&present_mode)
+                || matches!(
+                    present_mode,
+                    wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync
+                )
+            {
+                target.surface_config.present_mode = present_mode;
+                surface.configure(&render_context.device, &target.surface_config);
             }
         }
     }
 
-    fn prepare(
-        &mut self,
-        render_server: &RenderContext,
-        extracted: &mut Extracted,
-    ) -> PreparedFrame {
+    /// Recreate the lost device and everything the render thread owns on it. The shared
+    /// caches are restored by the logic thread, which uploads to them.
+    pub(crate) fn recover_device(&mut self, render_context: &mut RenderContext) -> anyhow::Result<()> {
+        let primary_surface = self
+            .windows
+            .get(&WindowId::PRIMARY)
+            .and_then(|target| target.surface.as_ref())
+            .map(|window_surface| &window_surface.surface);
+        render_context.recreate_device(primary_surface)?;
+
+        let windows = std::mem::take(&mut self.windows);
+        let pending_captures = std::mem::take(&mut self.pending_captures);
+
+        *self = RenderBackend::new(
+            render_context,
+            None,
+            std::mem::take(&mut self.graph_callbacks),
+            self.imported_texture_cache.clone(),
+            self.imported_mesh_cache.clone(),
+            self.imported_material_cache.clone(),
+            self.imported_mesh_allocator.clone(),
+        );
+        self.pending_captures = pending_captures;
+
+        for (id, mut target) in windows {
+            if let Some(window_surface) = &target.surface {
+                window_surface
+                    .surface
+                    .configure(&render_context.device, &target.surface_config);
+            }
+            target.render_graph = build_render_graph(&self.graph_callbacks);
+            self.windows.insert(id, target);
+        }
+
+        Ok(())
+    }
+
+    /// Data shared by all windows. The transparent instances depend on the camera and are
+    /// filled in by [`Self::prepare_transparent`] for each window.
+    fn prepare(&mut self, render_server: &RenderContext, extracted: Extracted) -> PreparedFrame {
         self.setup_layouts(render_server);
 
+        let profiler = &render_server.profiler;
+
         // 3. Prepare Bindless Materials (Includes all 2D textures)
+        let step_start = Instant::now();
         let (texture_index_map, material_index_map, material_uniforms, bindless_texture_ids) =
-            self.prepare_materials(&extracted.sprites);
+            self.prepare_materials(&extracted.sprites, render_server.features.is_bindless());
+        profiler.record(Track::Render, "prepare_materials", step_start);
 
         // Separate opaque and transparent meshes
         let mut opaque_meshes = Vec::new();
@@ -378,6 +577,7 @@ impl RenderBackend {
         }
 
         // Prepare 3D mesh BVH (only for opaque meshes)
+        let step_start = Instant::now();
         let mesh_cache = self.imported_mesh_cache.read().unwrap();
         let opaque_bvh = if !opaque_meshes.is_empty() {
             let bvh_objects: Vec<_> = opaque_meshes
@@ -394,8 +594,10 @@ impl RenderBackend {
         } else {
             Bvh::default()
         };
+        profiler.record(Track::Render, "prepare_bvh", step_start);
 
         // 1. Prepare Opaque Instances (GPU Culling Path)
+        let step_start = Instant::now();
         let (
             all_instances,
             mesh_id_to_index,
@@ -419,19 +621,73 @@ impl RenderBackend {
                 0,
             )
         };
+        profiler.record(Track::Render, "prepare_instances", step_start);
+
+        drop(mesh_cache);
+
+        // 6.5 Re-include MASKED transparent meshes for SSAO (normal pre-pass)
+        let mut ssao_meshes = opaque_meshes.clone();
+        // ... (SSAO 逻辑保持不变)
+        {
+            let material_cache = self.imported_material_cache.read().unwrap();
+            for mesh in &transparent_meshes {
+                if let Some(mat_id) = mesh.material_id {
+                    if let Some(mat) = material_cache.get(&mat_id) {
+                        if mat.alpha_mode == crate::render::material::AlphaMode::Mask {
+                            ssao_meshes.push(*mesh);
+                        }
+                    }
+                }
+            }
+        }
+
+        // 准备天空盒的永驻资源
+        if let Some(sky) = &extracted.sky {
+            prepare_sky(
+                &mut self.sky_imported_resources,
+                render_server,
+                &sky.texture,
+            );
+        }
+
+        PreparedFrame {
+            extracted,
+            texture_index_map,
+            material_index_map,
+            material_uniforms,
+            bindless_texture_ids,
+            opaque_meshes: ssao_meshes,
+            transparent_meshes,
+            bvh: opaque_bvh,
+            all_instances,
+            mesh_id_to_index,
+            draw_counts,
+            mesh_infos,
+            mesh_metadatas,
+            indirect_commands,
+            instance_buffer_size,
+            indirect_buffer_size,
+            sorted_transparent_instances: Vec::new(),
+            transparent_draw_batches: Vec::new(),
+        }
+    }
 
-        // 2. Prepare Transparent Instances (CPU Sorted Path + Simple Frustum Culling)
+    /// Sort and cull the transparent meshes for the first 3D camera in `prepared.extracted`.
+    fn prepare_transparent(&self, render_server: &RenderContext, prepared: &mut PreparedFrame) {
+        let mesh_cache = self.imported_mesh_cache.read().unwrap();
+        let step_start = Instant::now();
         let mut sorted_transparent_instances = Vec::new();
         let mut transparent_draw_batches: Vec<TransparentBatch> = Vec::new();
 
-        if !transparent_meshes.is_empty() {
+        if !prepared.transparent_meshes.is_empty() {
             // A. 获取主相机视角和视锥体
-            let (view_pos, frustum) = extracted
+            let (view_pos, frustum) = prepared
+                .extracted
                 .cameras
                 .uniforms
                 .iter()
                 .enumerate()
-                .find(|(i, _)| extracted.cameras.types[*i] == crate::render::camera::CameraType::D3)
+                .find(|(i, _)| prepared.extracted.cameras.types[*i] == CameraType::D3)
                 .map(|(_, u)| {
                     let pos = glam::Vec3::from_slice(&u.view_position[0..3]);
                     let vp = glam::Mat4::from_cols_array_2d(&u.view_proj);
@@ -443,7 +699,8 @@ impl RenderBackend {
                 ));
 
             // B. 直接线性过滤可见物体 (不需要 BVH)
-            let mut visible_transparent: Vec<_> = transparent_meshes
+            let mut visible_transparent: Vec<_> = prepared
+                .transparent_meshes
                 .iter()
                 .filter(|mesh| {
                     if let Some(m) = mesh_cache.get(mesh.mesh_id) {
@@ -468,7 +725,7 @@ impl RenderBackend {
             for mesh in visible_transparent {
                 let material_idx = mesh
                     .material_id
-                    .and_then(|id| material_index_map.get(&id))
+                    .and_then(|id| prepared.material_index_map.get(&id))
                     .cloned()
                     .unwrap_or(0);
                 let instance = crate::render::mesh::Instance {
@@ -483,7 +740,10 @@ impl RenderBackend {
                 sorted_transparent_instances.push(instance);
 
                 if let Some(last) = transparent_draw_batches.last_mut() {
-                    if last.mesh_id == mesh.mesh_id {
+                    if last.mesh_id == mesh.mesh_id
+                        && last.material_id == mesh.material_id
+                        && last.layers == mesh.layers
+                    {
                         last.instance_range.end += 1;
                         continue;
                     }
@@ -491,63 +751,27 @@ impl RenderBackend {
 
                 transparent_draw_batches.push(TransparentBatch {
                     mesh_id: mesh.mesh_id,
+                    material_id: mesh.material_id,
                     instance_range: current_idx..current_idx + 1,
+                    layers: mesh.layers,
                 });
             }
         }
 
         drop(mesh_cache);
-
-        // 6.5 Re-include MASKED transparent meshes for SSAO (normal pre-pass)
-        let mut ssao_meshes = opaque_meshes.clone();
-        // ... (SSAO 逻辑保持不变)
-        {
-            let material_cache = self.imported_material_cache.read().unwrap();
-            for mesh in &transparent_meshes {
-                if let Some(mat_id) = mesh.material_id {
-                    if let Some(mat) = material_cache.get(&mat_id) {
-                        if mat.alpha_mode == crate::render::material::AlphaMode::Mask {
-                            ssao_meshes.push(*mesh);
-                        }
-                    }
-                }
-            }
-        }
-
-        // 准备天空盒的永驻资源
-        if let Some(sky) = &extracted.sky {
-            prepare_sky(
-                &mut self.sky_imported_resources,
-                render_server,
-                &sky.texture,
-            );
-        }
-
-        PreparedFrame {
-            extracted: extracted.clone(),
-            texture_index_map,
-            material_index_map,
-            material_uniforms,
-            bindless_texture_ids,
-            opaque_meshes: ssao_meshes,
-            transparent_meshes,
-            bvh: opaque_bvh,
-            all_instances,
-            mesh_id_to_index,
-            draw_counts,
-            mesh_infos,
-            mesh_metadatas,
-            indirect_commands,
-            instance_buffer_size,
-            indirect_buffer_size,
-            sorted_transparent_instances,
-            transparent_draw_batches,
-        }
+        prepared.sorted_transparent_instances = sorted_transparent_instances;
+        prepared.transparent_draw_batches = transparent_draw_batches;
+        render_server
+            .profiler
+            .record(Track::Render, "prepare_transparent", step_start);
     }
 
+    /// Without bindless textures, materials only sample their color texture at index 0, which
+    /// the mesh nodes bind for each draw.
     pub fn prepare_materials(
         &mut self,
         extracted_sprites_2d: &Vec<ExtractedSprite2d>,
+        bindless: bool,
     ) -> (
         HashMap<TextureId, u32>,
         HashMap<MaterialId, u32>,
@@ -612,7 +836,12 @@ impl RenderBackend {
 
         for (id, material) in &sorted_materials {
             material_index_map.insert(**id, material_uniforms.len() as u32);
-            material_uniforms.push(material.to_uniform(&texture_index_map));
+            if bindless {
+                material_uniforms.push(material.to_uniform(&texture_index_map));
+            } else {
+                let color_texture = material.color_texture.map(|id| (id, 0)).into_iter();
+                material_uniforms.push(material.to_uniform(&color_texture.collect()));
+            }
         }
 
         // 没有材质，推入一个 dummy 材质
@@ -644,7 +873,9 @@ impl RenderBackend {
         usize,
         usize,
     ) {
-        let mut grouped_instances: HashMap<MeshId, Vec<InstanceRaw>> = HashMap::new();
+        // 按图层、网格和材质分组，同一图层的间接绘制指令是连续的
+        let mut grouped_instances: HashMap<(u32, MeshId, Option<MaterialId>), Vec<InstanceRaw>> =
+            HashMap::new();
 
         for mesh in extracted_meshes {
             let material_idx = mesh
@@ -653,15 +884,18 @@ impl RenderBackend {
                 .cloned()
                 .unwrap_or(0);
 
-            grouped_instances.entry(mesh.mesh_id).or_default().push(
-                Instance {
-                    position: mesh.transform.position,
-                    scale: mesh.transform.scale,
-                    rotation: mesh.transform.rotation,
-                    material_idx,
-                }
-                .to_raw(),
-            );
+            grouped_instances
+                .entry((mesh.layers, mesh.mesh_id, mesh.material_id))
+                .or_default()
+                .push(
+                    Instance {
+                        position: mesh.transform.position,
+                        scale: mesh.transform.scale,
+                        rotation: mesh.transform.rotation,
+                        material_idx,
+                    }
+                    .to_raw(),
+                );
         }
 
         let mut all_instances = Vec::new();
@@ -673,18 +907,21 @@ impl RenderBackend {
 
         let mut current_base_instance = 0u32;
         let mut sorted_meshes: Vec<_> = grouped_instances.keys().cloned().collect();
-        sorted_meshes.sort_by_key(|id| id.0);
+        sorted_meshes
+            .sort_by_key(|(layers, id, material_id)| (*layers, id.0, material_id.map(|id| id.0)));
 
-        for mesh_id in sorted_meshes {
-            let instances = &grouped_instances[&mesh_id];
+        for (layers, mesh_id, material_id) in sorted_meshes {
+            let instances = &grouped_instances[&(layers, mesh_id, material_id)];
             let mesh = mesh_cache.get(mesh_id).unwrap();
 
             mesh_id_to_index.insert(mesh_id, mesh_metadatas.len() as u32);
 
             mesh_infos.push(MeshInstanceInfo {
                 mesh_id,
+                material_id,
                 base_instance: current_base_instance,
                 instance_count: instances.len() as u32,
+                layers,
             });
 
             mesh_metadatas.push(crate::render::mesh::MeshMetadata {
@@ -754,7 +991,12 @@ impl RenderBackend {
                                 view_dimension: wgpu::TextureViewDimension::D2,
                                 sample_type: wgpu::TextureSampleType::Float { filterable: true },
                             },
-                            count: std::num::NonZeroU32::new(1024),
+                            // 简化档位只绑定一张纹理
+                            count: if render_context.features.is_bindless() {
+                                std::num::NonZeroU32::new(MAX_BINDLESS_TEXTURES)
+                            } else {
+                                None
+                            },
                         },
                         wgpu::BindGroupLayoutEntry {
                             binding: 2,
@@ -1,37 +1,188 @@
-use std::sync::atomic::AtomicU64;
+use crate::core::{Profiler, Time};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 
+/// Size of the bindless texture array.
+pub(crate) const MAX_BINDLESS_TEXTURES: u32 = 1024;
+
+/// Feature tier the renderer runs at, chosen from what the adapter supports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderTier {
+    /// Bindless materials and sprites.
+    Full,
+    /// No texture binding arrays: meshes are drawn with their material factors only and
+    /// sprites are batched per texture.
+    Reduced,
+}
+
+/// Optional GPU features the renderer was initialized with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RenderFeatures {
+    pub tier: RenderTier,
+    /// Hardware timestamps for the GPU frame and node times.
+    pub gpu_timestamps: bool,
+}
+
+impl RenderFeatures {
+    const BINDLESS: wgpu::Features = wgpu::Features::TEXTURE_BINDING_ARRAY
+        .union(wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING);
+    const TIMESTAMPS: wgpu::Features =
+        wgpu::Features::TIMESTAMP_QUERY.union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS);
+    /// Needed by every tier.
+    const REQUIRED: wgpu::Features = wgpu::Features::INDIRECT_FIRST_INSTANCE;
+
+    /// Pick the highest tier the adapter supports.
+    pub(crate) fn probe(adapter: &wgpu::Adapter) -> Self {
+        let features = adapter.features();
+
+        let bindless = features.contains(Self::BINDLESS)
+            && adapter.limits().max_binding_array_elements_per_shader_stage
+                >= MAX_BINDLESS_TEXTURES;
+
+        Self {
+            tier: if bindless {
+                RenderTier::Full
+            } else {
+                RenderTier::Reduced
+            },
+            gpu_timestamps: features.contains(Self::TIMESTAMPS),
+        }
+    }
+
+    /// The features a device was actually created with.
+    fn from_device(device: &wgpu::Device) -> Self {
+        let features = device.features();
+        Self {
+            tier: if features.contains(Self::BINDLESS) {
+                RenderTier::Full
+            } else {
+                RenderTier::Reduced
+            },
+            gpu_timestamps: features.contains(Self::TIMESTAMPS),
+        }
+    }
+
+    pub fn is_bindless(&self) -> bool {
+        self.tier == RenderTier::Full
+    }
+
+    pub(crate) fn wgpu_features(&self) -> wgpu::Features {
+        let mut features = Self::REQUIRED;
+        if self.is_bindless() {
+            features |= Self::BINDLESS;
+        }
+        if self.gpu_timestamps {
+            features |= Self::TIMESTAMPS;
+        }
+        features
+    }
+
+    pub(crate) fn limits(&self) -> wgpu::Limits {
+        let mut limits = wgpu::Limits::default();
+        if self.is_bindless() {
+            limits.max_binding_array_elements_per_shader_stage = MAX_BINDLESS_TEXTURES;
+        }
+        limits
+    }
+}
+
+/// Why the renderer couldn't be initialized.
+#[derive(Debug)]
+pub enum InitError {
+    CreateSurface(wgpu::CreateSurfaceError),
+    NoAdapter(wgpu::RequestAdapterError),
+    /// The adapter lacks features every tier needs.
+    MissingFeatures(wgpu::Features),
+    RequestDevice(wgpu::RequestDeviceError),
+    /// The adapter can't present to the window surface.
+    SurfaceUnsupported,
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitError::CreateSurface(err) => write!(f, "failed to create window surface: {}", err),
+            InitError::NoAdapter(err) => write!(f, "no suitable GPU adapter found: {}", err),
+            InitError::MissingFeatures(features) => {
+                write!(f, "GPU adapter lacks required features: {:?}", features)
+            }
+            InitError::RequestDevice(err) => write!(f, "failed to create GPU device: {}", err),
+            InitError::SurfaceUnsupported => write!(f, "window surface unsupported by adapter"),
+        }
+    }
+}
+
+impl std::error::Error for InitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InitError::CreateSurface(err) => Some(err),
+            InitError::NoAdapter(err) => Some(err),
+            InitError::RequestDevice(err) => Some(err),
+            InitError::MissingFeatures(_) | InitError::SurfaceUnsupported => None,
+        }
+    }
+}
+
 /// Contains render context (but not GPU resources)
 #[derive(Clone)]
 pub struct RenderContext {
+    /// Kept to create surfaces for windows spawned later.
+    pub instance: wgpu::Instance,
+    pub adapter: wgpu::Adapter,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub surface_config: wgpu::SurfaceConfiguration,
     pub frames_in_flight: u32,
+    /// Tier and optional features the device was created with.
+    pub features: RenderFeatures,
+    /// Present modes the surface supports, empty without a surface.
+    pub supported_present_modes: Vec<wgpu::PresentMode>,
 
     pub render_cpu_time: Arc<AtomicU64>,
     pub gpu_time: Arc<AtomicU64>,
+    pub profiler: Profiler,
+
+    /// Set by wgpu when the device is lost, checked by the render thread.
+    pub(crate) device_lost: Arc<AtomicBool>,
 }
 
 impl RenderContext {
     pub(crate) fn new(
         surface_config: wgpu::SurfaceConfiguration,
+        instance: wgpu::Instance,
+        adapter: wgpu::Adapter,
         device: wgpu::Device,
         queue: wgpu::Queue,
         frames_in_flight: u32,
-        render_cpu_time: Arc<AtomicU64>,
-        gpu_time: Arc<AtomicU64>,
+        time: &Time,
     ) -> Self {
         let now = Instant::now();
 
+        let device_lost = Arc::new(AtomicBool::new(false));
+        watch_device_lost(&device, device_lost.clone());
+
+        let features = RenderFeatures::from_device(&device);
+        log::info!(
+            "Render tier: {:?} on {}",
+            features.tier,
+            adapter.get_info().name
+        );
+
         let context = Self {
+            instance,
+            adapter,
             device,
             queue,
             surface_config,
             frames_in_flight,
-            render_cpu_time,
-            gpu_time,
+            features,
+            supported_present_modes: Vec::new(),
+            render_cpu_time: time.render_cpu_time.clone(),
+            gpu_time: time.gpu_time.clone(),
+            profiler: time.profiler.clone(),
+            device_lost,
         };
 
         let elapsed_time = now.elapsed();
@@ -42,6 +193,96 @@ impl RenderContext {
 
         context
     }
+
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Acquire)
+    }
+
+    /// Replace a lost device with a new one with the same features and limits. The adapter is
+    /// requested again if it can't create devices anymore (e.g. after a driver reset).
+    pub(crate) fn recreate_device(
+        &mut self,
+        compatible_surface: Option<&wgpu::Surface<'_>>,
+    ) -> anyhow::Result<()> {
+        let descriptor = wgpu::DeviceDescriptor {
+            label: None,
+            required_features: self.device.features(),
+            required_limits: self.device.limits(),
+            memory_hints: Default::default(),
+            experimental_features: Default::default(),
+            trace: Default::default(),
+        };
+
+        let (adapter, (device, queue)) = pollster::block_on(async {
+            if let Ok(device) = self.adapter.request_device(&descriptor).await {
+                return anyhow::Ok((self.adapter.clone(), device));
+            }
+
+            let adapter = self
+                .instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::HighPerformance,
+                    compatible_surface,
+                    force_fallback_adapter: false,
+                })
+                .await?;
+            let device = adapter.request_device(&descriptor).await?;
+            Ok((adapter, device))
+        })?;
+
+        self.device_lost.store(false, Ordering::Release);
+        watch_device_lost(&device, self.device_lost.clone());
+
+        self.adapter = adapter;
+        self.device = device;
+        self.queue = queue;
+        Ok(())
+    }
+}
+
+fn watch_device_lost(device: &wgpu::Device, device_lost: Arc<AtomicBool>) {
+    device.set_device_lost_callback(move |reason, message| {
+        // 主动销毁（退出时）不算丢失
+        if reason == wgpu::DeviceLostReason::Unknown {
+            log::error!("GPU device lost: {}", message);
+            device_lost.store(true, Ordering::Release);
+        }
+    });
+}
+
+/// Adapt a shader using the bindless texture array to the reduced tier, where a single texture
+/// is bound instead and the array indexing is dropped.
+pub(crate) fn specialize_bindless(source: String, features: &RenderFeatures) -> String {
+    if features.is_bindless() {
+        return source;
+    }
+
+    let source = source.replace("binding_array<texture_2d<f32>>", "texture_2d<f32>");
+
+    let mut specialized = String::with_capacity(source.len());
+    let mut rest = source.as_str();
+    while let Some(start) = rest.find("t_textures[") {
+        specialized.push_str(&rest[..start + "t_textures".len()]);
+        rest = &rest[start + "t_textures[".len()..];
+
+        // 跳过下标表达式（可能包含嵌套的方括号）
+        let mut depth = 1;
+        let end = rest
+            .char_indices()
+            .find(|(_, c)| {
+                match c {
+                    '[' => depth += 1,
+                    ']' => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            })
+            .map_or(rest.len(), |(i, _)| i + 1);
+        rest = &rest[end..];
+    }
+    specialized.push_str(rest);
+
+    specialized
 }
 
 /// Set up resource pipeline using the pipeline layout.
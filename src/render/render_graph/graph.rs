@@ -1,3 +1,4 @@
+use crate::core::Track;
 use crate::render::render_backend::{PreparedFrame, RenderBackend};
 use crate::render::render_graph::frame_context::FrameContext;
 use crate::render::render_graph::resource_pool::ResourcePool;
@@ -126,6 +127,30 @@ impl RenderGraph {
         self.cached_execution_order = None;
     }
 
+    /// Removes a node from the graph. Nodes that ran after it inherit its dependencies,
+    /// so the remaining execution order is preserved. Returns false if there is no such node.
+    pub fn remove_node(&mut self, name: &str) -> bool {
+        if self.nodes.remove(name).is_none() {
+            return false;
+        }
+
+        let removed_deps = self.dependencies.remove(name).unwrap_or_default();
+        for deps in self.dependencies.values_mut() {
+            if let Some(pos) = deps.iter().position(|x| x == name) {
+                deps.remove(pos);
+                for dep in &removed_deps {
+                    if !deps.contains(dep) {
+                        deps.push(dep.clone());
+                    }
+                }
+            }
+        }
+
+        // Reset cache.
+        self.cached_execution_order = None;
+        true
+    }
+
     pub fn get_node_mut<T: Node>(&mut self, name: &str) -> Option<&mut T> {
         self.nodes
             .get_mut(name)
@@ -303,10 +328,26 @@ impl RenderGraph {
                 active_resources: &mut active_resources,
             };
 
-            // 执行所有节点
+            // 执行所有节点，记录每个节点的 CPU 录制耗时与 GPU 耗时
             for node_name in execution_order {
                 if let Some(node_state) = self.nodes.get_mut(&node_name) {
+                    let cpu_start = std::time::Instant::now();
+                    let query = context
+                        .backend
+                        .gpu_profiler
+                        .as_mut()
+                        .and_then(|profiler| profiler.begin_node(context.encoder, &node_name));
+
                     node_state.node.run(&mut context);
+
+                    if let (Some(profiler), Some(query)) =
+                        (context.backend.gpu_profiler.as_mut(), query)
+                    {
+                        profiler.end_node(context.encoder, query);
+                    }
+                    render_context
+                        .profiler
+                        .record(Track::Render, &node_name, cpu_start);
                 }
             }
         }
@@ -1,18 +1,23 @@
 use crate::render::camera::{CameraType, CameraUniform};
 use crate::render::render_backend::PreparedFrame;
+use crate::render::render_graph::nodes::TextureBindGroups;
 use crate::render::render_graph::standard_resources;
 use crate::render::render_graph::{FrameContext, Node};
 use crate::render::vertex::{Vertex3d, VertexBuffer};
-use crate::render::{InstanceRaw, Texture};
+use crate::render::{specialize_bindless, InstanceRaw, Texture};
 use std::any::Any;
 
 pub struct MeshNode {
     pipeline: Option<wgpu::RenderPipeline>,
+    texture_bind_groups: TextureBindGroups,
 }
 
 impl Default for MeshNode {
     fn default() -> Self {
-        Self { pipeline: None }
+        Self {
+            pipeline: None,
+            texture_bind_groups: TextureBindGroups::default(),
+        }
     }
 }
 
@@ -101,9 +106,12 @@ impl Node for MeshNode {
                 immediate_size: 0,
             });
 
-            let source = include_str!("../../../shaders/mesh.wgsl").replace(
-                "#import eureka::camera::Camera",
-                crate::render::camera::CAMERA_STRUCT_WGSL,
+            let source = specialize_bindless(
+                include_str!("../../../shaders/mesh.wgsl").replace(
+                    "#import eureka::camera::Camera",
+                    crate::render::camera::CAMERA_STRUCT_WGSL,
+                ),
+                &context.render_context.features,
             );
             let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
                 label: Some("Opaque Mesh Shader"),
@@ -154,6 +162,25 @@ impl Node for MeshNode {
         }
 
         let (camera_bg, light_bg, bindless_bg) = super::shared_mesh::get_mesh_bind_groups(context);
+
+        // 简化档位没有纹理数组，每条间接绘制指令绑定其材质的纹理
+        let material_bind_groups: Vec<wgpu::BindGroup> =
+            if context.render_context.features.is_bindless() {
+                Vec::new()
+            } else {
+                self.texture_bind_groups
+                    .retain_existing(&context.backend.imported_texture_cache.read().unwrap());
+                let prepared = context.prepared;
+                prepared
+                    .mesh_infos
+                    .iter()
+                    .map(|info| {
+                        self.texture_bind_groups
+                            .get_for_material(context, info.material_id)
+                    })
+                    .collect()
+            };
+
         let main_color = FrameContext::texture(context, &standard_resources::main_color());
         let main_depth = context.texture(&standard_resources::main_depth());
 
@@ -201,12 +228,24 @@ impl Node for MeshNode {
                 render_pass
                     .set_index_buffer(allocator.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
 
+                // 只绘制该摄像机图层上的实例
                 if !context.prepared.draw_counts.is_empty() {
-                    render_pass.multi_draw_indexed_indirect(
-                        &indirect_buffer.buffer,
-                        0,
-                        context.prepared.draw_counts[0],
-                    );
+                    let layers = extracted.cameras.layers[camera_idx];
+                    for range in context.prepared.indirect_ranges(layers) {
+                        if material_bind_groups.is_empty() {
+                            render_pass.multi_draw_indexed_indirect(
+                                &indirect_buffer.buffer,
+                                range.start as u64 * 20,
+                                range.end - range.start,
+                            );
+                            continue;
+                        }
+                        for i in range {
+                            render_pass.set_bind_group(2, &material_bind_groups[i as usize], &[]);
+                            render_pass
+                                .draw_indexed_indirect(&indirect_buffer.buffer, i as u64 * 20);
+                        }
+                    }
                 }
             }
         }
@@ -1,6 +1,9 @@
+use crate::render::material::MaterialId;
 use crate::render::render_backend::PreparedFrame;
 use crate::render::render_graph::{standard_resources, FrameContext, Node, SamplerKey};
+use crate::render::{TextureCache, TextureId, MAX_BINDLESS_TEXTURES};
 use std::any::Any;
+use std::collections::HashMap;
 
 #[derive(Default)]
 pub struct PrepareMaterialsNode;
@@ -44,31 +47,34 @@ impl Node for PrepareMaterialsNode {
             .unwrap()
             .clone();
 
-        let dummy_sampler = context.get_sampler(SamplerKey {
-            address_mode_u: wgpu::AddressMode::Repeat,
-            address_mode_v: wgpu::AddressMode::Repeat,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            ..Default::default()
-        });
+        let dummy_sampler = context.get_sampler(material_sampler_key());
+
+        let bindless = context.render_context.features.is_bindless();
 
-        // 占位纹理
-        let placeholder_view = if !context.prepared.bindless_texture_ids.is_empty() {
+        let final_bindless_views = if bindless {
+            // 占位纹理
             let texture_cache = context.backend.imported_texture_cache.read().unwrap();
+            let placeholder_view = match context.prepared.bindless_texture_ids.first() {
+                Some(texture_id) => texture_cache.get(*texture_id).unwrap().view.clone(),
+                None => context.backend.dummy_2d_view.clone(),
+            };
 
-            let texture_id = context.prepared.bindless_texture_ids[0];
-            texture_cache.get(texture_id).unwrap().view.clone()
+            let mut views = vec![placeholder_view; MAX_BINDLESS_TEXTURES as usize];
+            for (i, texture_id) in context.prepared.bindless_texture_ids.iter().enumerate() {
+                views[i] = texture_cache.get(*texture_id).unwrap().view.clone();
+            }
+            views
         } else {
-            context.backend.dummy_2d_view.clone()
+            // 简化档位：材质和精灵在绘制时绑定各自的纹理，见 TextureBindGroups
+            vec![context.backend.dummy_2d_view.clone()]
         };
 
-        let mut final_bindless_views = vec![placeholder_view; 1024];
-        for (i, texture_id) in context.prepared.bindless_texture_ids.iter().enumerate() {
-            let texture_cache = context.backend.imported_texture_cache.read().unwrap();
-            final_bindless_views[i] = texture_cache.get(*texture_id).unwrap().view.clone();
-        }
-
         let bindless_views_ref: Vec<&wgpu::TextureView> = final_bindless_views.iter().collect();
+        let texture_resource = if bindless {
+            wgpu::BindingResource::TextureViewArray(&bindless_views_ref)
+        } else {
+            wgpu::BindingResource::TextureView(bindless_views_ref[0])
+        };
 
         // FIXME: add view keys.
         let _bindless_bind_group = context.create_bind_group(
@@ -84,7 +90,7 @@ impl Node for PrepareMaterialsNode {
                         },
                         wgpu::BindGroupEntry {
                             binding: 1,
-                            resource: wgpu::BindingResource::TextureViewArray(&bindless_views_ref),
+                            resource: texture_resource,
                         },
                         wgpu::BindGroupEntry {
                             binding: 2,
@@ -97,3 +103,96 @@ impl Node for PrepareMaterialsNode {
         );
     }
 }
+
+/// Sampler shared by all material and sprite textures.
+pub(crate) fn material_sampler_key() -> SamplerKey {
+    SamplerKey {
+        address_mode_u: wgpu::AddressMode::Repeat,
+        address_mode_v: wgpu::AddressMode::Repeat,
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        ..Default::default()
+    }
+}
+
+/// Material bind groups of the reduced tier, which has no texture array and binds the texture of
+/// each draw instead. Cached by texture, and rebuilt when the material buffer or the texture
+/// changes.
+#[derive(Default)]
+pub(crate) struct TextureBindGroups {
+    /// Keyed by texture, with the ids of the material buffer and the texture view they were
+    /// built with.
+    bind_groups: HashMap<Option<TextureId>, (u64, u64, wgpu::BindGroup)>,
+}
+
+impl TextureBindGroups {
+    /// Bind group sampling `texture_id`, or the dummy texture if there is none.
+    pub(crate) fn get(
+        &mut self,
+        context: &mut FrameContext,
+        texture_id: Option<TextureId>,
+    ) -> wgpu::BindGroup {
+        let sampler = context.get_sampler(material_sampler_key());
+        let materials = context.buffer(&standard_resources::material_storage_buffer());
+        let texture_cache = context.backend.imported_texture_cache.read().unwrap();
+        let texture = texture_id.and_then(|id| texture_cache.get(id));
+        let view_id = texture.map_or(0, |texture| texture.view_id);
+
+        if let Some((buffer_id, cached_view_id, bind_group)) = self.bind_groups.get(&texture_id) {
+            if *buffer_id == materials.id && *cached_view_id == view_id {
+                return bind_group.clone();
+            }
+        }
+
+        let view = texture.map_or(&context.backend.dummy_2d_view, |texture| &texture.view);
+        let layout = context
+            .backend
+            .get_bind_group_layout("bindless_bind_group_layout")
+            .unwrap();
+        let bind_group =
+            context
+                .render_context
+                .device
+                .create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: materials.buffer.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::TextureView(view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::Sampler(&sampler),
+                        },
+                    ],
+                    label: Some("texture bind group"),
+                });
+
+        self.bind_groups
+            .insert(texture_id, (materials.id, view_id, bind_group.clone()));
+        bind_group
+    }
+
+    /// Bind group sampling the color texture of a material.
+    pub(crate) fn get_for_material(
+        &mut self,
+        context: &mut FrameContext,
+        material_id: Option<MaterialId>,
+    ) -> wgpu::BindGroup {
+        let texture_id = material_id.and_then(|id| {
+            let material_cache = context.backend.imported_material_cache.read().unwrap();
+            material_cache.get(&id)?.color_texture
+        });
+        self.get(context, texture_id)
+    }
+
+    /// Drop the bind groups of removed textures.
+    pub(crate) fn retain_existing(&mut self, texture_cache: &TextureCache) {
+        self.bind_groups
+            .retain(|id, _| id.is_none_or(|id| texture_cache.get(id).is_some()));
+    }
+}
@@ -4,7 +4,7 @@ use crate::render::render_graph::{
     standard_resources, FrameContext, Node,
 };
 use crate::render::vertex::{Vertex3d, VertexBuffer};
-use crate::render::{create_render_pipeline_with_entry, InstanceRaw, Texture};
+use crate::render::{specialize_bindless, InstanceRaw, Texture};
 use std::any::Any;
 use wgpu::BufferAddress;
 
@@ -131,8 +131,11 @@ impl Node for PrePassNode {
                 immediate_size: 0,
             });
 
-            let source = include_str!("../../../shaders/prepass.wgsl")
-                .replace("#import eureka::camera::Camera", crate::render::camera::CAMERA_STRUCT_WGSL);
+            let source = specialize_bindless(
+                include_str!("../../../shaders/prepass.wgsl")
+                    .replace("#import eureka::camera::Camera", crate::render::camera::CAMERA_STRUCT_WGSL),
+                &context.render_context.features,
+            );
 
             let shader = wgpu::ShaderModuleDescriptor {
                 label: Some("PrePass Shader"),
@@ -152,7 +155,7 @@ impl Node for PrePassNode {
                 fragment: Some(wgpu::FragmentState {
                     module: &device.create_shader_module(wgpu::ShaderModuleDescriptor {
                         label: None,
-                        source: wgpu::ShaderSource::Wgsl(include_str!("../../../shaders/prepass.wgsl").replace("#import eureka::camera::Camera", crate::render::camera::CAMERA_STRUCT_WGSL).into()),
+                        source: wgpu::ShaderSource::Wgsl(specialize_bindless(include_str!("../../../shaders/prepass.wgsl").replace("#import eureka::camera::Camera", crate::render::camera::CAMERA_STRUCT_WGSL), &context.render_context.features).into()),
                     }),
                     entry_point: Some("fs_main"),
                     compilation_options: Default::default(),
@@ -263,7 +266,10 @@ impl Node for PrePassNode {
                     render_pass.set_index_buffer(mesh_allocator.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
 
                     if !context.prepared.draw_counts.is_empty() {
-                        render_pass.multi_draw_indexed_indirect(&indirect_buffer.buffer, 0, context.prepared.draw_counts[0]);
+                        let layers = context.extracted.cameras.layers[camera_idx];
+                        for range in context.prepared.indirect_ranges(layers) {
+                            render_pass.multi_draw_indexed_indirect(&indirect_buffer.buffer, range.start as u64 * 20, range.end - range.start);
+                        }
                     }
                 }
             }
@@ -1,21 +1,25 @@
-use crate::render::camera::CameraUniform;
-use crate::render::create_render_pipeline;
+use crate::render::camera::{CameraType, CameraUniform};
 use crate::render::render_backend::PreparedFrame;
+use crate::render::render_graph::nodes::TextureBindGroups;
 use crate::render::render_graph::{standard_resources, FrameContext, Node, PooledBuffer};
 use crate::render::sprite::ExtractedSprite2d;
 use crate::render::vertex::{Vertex2d, VertexBuffer};
-use crate::render::Texture;
+use crate::render::{create_render_pipeline, specialize_bindless, Texture, TextureId};
 use glam::Vec2;
 use std::any::Any;
 use std::ops::Range;
 
 pub struct SpriteNode {
     pipeline: Option<wgpu::RenderPipeline>,
+    texture_bind_groups: TextureBindGroups,
 }
 
 impl Default for SpriteNode {
     fn default() -> Self {
-        Self { pipeline: None }
+        Self {
+            pipeline: None,
+            texture_bind_groups: TextureBindGroups::default(),
+        }
     }
 }
 
@@ -125,8 +129,13 @@ impl Node for SpriteNode {
                 immediate_size: 0,
             });
 
-            let source = include_str!("../../../shaders/sprite.wgsl")
-                .replace("#import eureka::camera::Camera", crate::render::camera::CAMERA_STRUCT_WGSL);
+            let source = specialize_bindless(
+                include_str!("../../../shaders/sprite.wgsl").replace(
+                    "#import eureka::camera::Camera",
+                    crate::render::camera::CAMERA_STRUCT_WGSL,
+                ),
+                &context.render_context.features,
+            );
 
             let shader = wgpu::ShaderModuleDescriptor {
                 label: Some("sprite shader"),
@@ -163,6 +172,19 @@ impl Node for SpriteNode {
             )
             .clone();
 
+        // 简化档位没有纹理数组，每个批次绑定自己的纹理
+        let texture_bind_groups: Vec<Option<wgpu::BindGroup>> =
+            if context.render_context.features.is_bindless() {
+                vec![None; batches.len()]
+            } else {
+                self.texture_bind_groups
+                    .retain_existing(&context.backend.imported_texture_cache.read().unwrap());
+                batches
+                    .iter()
+                    .map(|b| Some(self.texture_bind_groups.get(context, Some(b.texture_id))))
+                    .collect()
+            };
+
         let vertex_buffer = context.buffer(&standard_resources::sprite_vertex_buffer());
         let index_buffer = context.buffer(&standard_resources::sprite_index_buffer());
 
@@ -200,12 +222,27 @@ impl Node for SpriteNode {
 
         render_pass.set_bind_group(1, &bindless_bind_group, &[]);
 
-        for b in batches {
-            let camera_offset = CameraUniform::get_uniform_offset_unit() * b.camera_index;
+        // 每个 2D 相机只绘制自己图层上的批次
+        let cameras = &context.extracted.cameras;
+        for (camera_index, layers) in cameras.layers.iter().enumerate() {
+            if cameras.types[camera_index] != CameraType::D2 {
+                continue;
+            }
 
+            let camera_offset = CameraUniform::get_uniform_offset_unit() * camera_index as u32;
             render_pass.set_bind_group(0, &camera_bind_group, &[camera_offset]);
 
-            render_pass.draw_indexed(b.index_range.clone(), 0, 0..1);
+            for (b, texture_bind_group) in batches.iter().zip(&texture_bind_groups) {
+                if b.layers & layers == 0 {
+                    continue;
+                }
+
+                if let Some(bind_group) = texture_bind_group {
+                    render_pass.set_bind_group(1, bind_group, &[]);
+                }
+
+                render_pass.draw_indexed(b.index_range.clone(), 0, 0..1);
+            }
         }
     }
 }
@@ -220,22 +257,17 @@ fn create_batches(
         return vec![];
     }
 
-    // 找到第一个 D2 类型的相机
-    let camera_index = extracted_cameras
-        .types
-        .iter()
-        .position(|t| *t == crate::render::camera::CameraType::D2);
-
     // 如果没有 2D 相机，则不渲染任何 2D 元素
-    let camera_index = match camera_index {
-        Some(idx) => idx as u32,
-        None => return vec![],
-    };
+    if !extracted_cameras.types.contains(&CameraType::D2) {
+        return vec![];
+    }
 
     let total_quads = sprites.len();
 
     let mut all_vertices = Vec::with_capacity(total_quads * 4);
     let mut all_indices = Vec::with_capacity(total_quads * 6);
+    let mut batches: Vec<SpriteBatch> = Vec::new();
+    let bindless = context.render_context.features.is_bindless();
 
     // 计算 Z 步长。我们希望越后抽取的元素 Z 越小（越靠近相机，在正交投影中，Z 越小越靠前）。
     let z_step = 1.0 / (total_quads as f32 + 1.0);
@@ -268,6 +300,21 @@ fn create_batches(
             .unwrap_or(&0);
         let vertex_start = all_vertices.len() as u32;
 
+        // 简化档位中每种纹理需要单独的绘制调用
+        let index_start = all_indices.len() as u32;
+        match batches.last_mut() {
+            Some(batch)
+                if batch.layers == e.layers && (bindless || batch.texture_id == e.texture_id) =>
+            {
+                batch.index_range.end = index_start + 6;
+            }
+            _ => batches.push(SpriteBatch {
+                index_range: index_start..index_start + 6,
+                layers: e.layers,
+                texture_id: e.texture_id,
+            }),
+        }
+
         for i in 0..4 {
             let mut quad_pos = QUAD_VERTEX_POSITIONS[i];
             if !e.centered {
@@ -303,11 +350,6 @@ fn create_batches(
         bytemuck::cast_slice(&all_indices),
     );
 
-    let batches = vec![SpriteBatch {
-        index_range: 0..all_indices.len() as u32,
-        camera_index,
-    }];
-
     batches
 }
 
@@ -323,7 +365,10 @@ pub(crate) const QUAD_VERTEX_POSITIONS: [Vec2; 4] = [
 #[derive(Debug, Clone)]
 pub struct SpriteBatch {
     pub(crate) index_range: Range<u32>,
-    pub(crate) camera_index: u32,
+    /// `RenderLayers` bitmask of the sprites.
+    pub(crate) layers: u32,
+    /// Only used by the reduced tier, where a batch can't mix textures.
+    pub(crate) texture_id: TextureId,
 }
 
 pub struct PreparedSprites {
@@ -1,18 +1,23 @@
 use crate::render::camera::{CameraType, CameraUniform};
 use crate::render::render_backend::PreparedFrame;
+use crate::render::render_graph::nodes::TextureBindGroups;
 use crate::render::render_graph::standard_resources;
 use crate::render::render_graph::{FrameContext, Node};
 use crate::render::vertex::{Vertex3d, VertexBuffer};
-use crate::render::{create_render_pipeline, InstanceRaw, Texture};
+use crate::render::{create_render_pipeline, specialize_bindless, InstanceRaw, Texture};
 use std::any::Any;
 
 pub struct TransparentMeshNode {
     pipeline: Option<wgpu::RenderPipeline>,
+    texture_bind_groups: TextureBindGroups,
 }
 
 impl Default for TransparentMeshNode {
     fn default() -> Self {
-        Self { pipeline: None }
+        Self {
+            pipeline: None,
+            texture_bind_groups: TextureBindGroups::default(),
+        }
     }
 }
 
@@ -77,8 +82,11 @@ impl Node for TransparentMeshNode {
                 immediate_size: 0,
             });
 
-            let source = include_str!("../../../shaders/mesh.wgsl")
-                .replace("#import eureka::camera::Camera", crate::render::camera::CAMERA_STRUCT_WGSL);
+            let source = specialize_bindless(
+                include_str!("../../../shaders/mesh.wgsl")
+                    .replace("#import eureka::camera::Camera", crate::render::camera::CAMERA_STRUCT_WGSL),
+                &context.render_context.features,
+            );
 
             self.pipeline = Some(create_render_pipeline(
                 device, &pipeline_layout, Some(wgpu::TextureFormat::Rgba16Float), Some(Texture::DEPTH_FORMAT),
@@ -89,6 +97,25 @@ impl Node for TransparentMeshNode {
         }
 
         let (camera_bg, light_bg, bindless_bg) = super::shared_mesh::get_mesh_bind_groups(context);
+
+        // 简化档位没有纹理数组，每个批次绑定其材质的纹理
+        let material_bind_groups: Vec<wgpu::BindGroup> =
+            if context.render_context.features.is_bindless() {
+                Vec::new()
+            } else {
+                self.texture_bind_groups
+                    .retain_existing(&context.backend.imported_texture_cache.read().unwrap());
+                let prepared = context.prepared;
+                prepared
+                    .transparent_draw_batches
+                    .iter()
+                    .map(|batch| {
+                        self.texture_bind_groups
+                            .get_for_material(context, batch.material_id)
+                    })
+                    .collect()
+            };
+
         let main_color = context.texture(&standard_resources::main_color());
         let main_depth = context.texture(&standard_resources::main_depth());
 
@@ -124,7 +151,14 @@ impl Node for TransparentMeshNode {
                 render_pass.set_vertex_buffer(1, transparent_instance_buffer.buffer.slice(..));
                 render_pass.set_index_buffer(allocator.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
 
-                for batch in &context.prepared.transparent_draw_batches {
+                let layers = extracted.cameras.layers[camera_idx];
+                for (i, batch) in context.prepared.transparent_draw_batches.iter().enumerate() {
+                    if batch.layers & layers == 0 {
+                        continue;
+                    }
+                    if let Some(bind_group) = material_bind_groups.get(i) {
+                        render_pass.set_bind_group(2, bind_group, &[]);
+                    }
                     if let Some(mesh) = mesh_cache.get(batch.mesh_id) {
                         render_pass.draw_indexed(mesh.index_offset..mesh.index_offset + mesh.index_count, mesh.vertex_offset as i32, batch.instance_range.clone());
                     }
@@ -294,6 +294,7 @@ pub enum ResourceLifetime {
 }
 
 /// 节点资源声明集合
+#[derive(Default)]
 pub struct NodeResources {
     pub inputs: Vec<ResourceDecl>,
     pub outputs: Vec<ResourceDecl>,
@@ -302,11 +303,7 @@ pub struct NodeResources {
 
 impl NodeResources {
     pub fn new() -> Self {
-        Self {
-            inputs: Vec::new(),
-            outputs: Vec::new(),
-            internals: Vec::new(),
-        }
+        Self::default()
     }
 
     pub fn input<T>(mut self, id: ResourceId<T>, spec: ResourceSpec) -> Self {
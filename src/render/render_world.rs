@@ -1,12 +1,21 @@
 use crate::render::camera::ExtractedCameras;
+use crate::render::capture::CaptureRequest;
 use crate::render::light::ExtractedLights;
 use crate::render::material::MaterialCache;
 use crate::render::mesh_allocator::MeshAllocator;
-pub(crate) use crate::render::render_backend::{RenderBackend, RenderCommand};
+pub(crate) use crate::render::render_backend::{RenderBackend, RenderCommand, WindowSurface};
+use crate::render::render_graph::RenderGraph;
 use crate::render::sky::ExtractedSky;
 use crate::render::sprite::ExtractedSprite2d;
 use crate::render::{ExtractedMesh, MeshCache, RenderContext, TextureCache};
+use crate::window::WindowId;
+use image::RgbaImage;
+use std::sync::mpsc::Receiver;
 use std::sync::{Arc, RwLock};
+use std::thread::JoinHandle;
+
+/// Modifies the standard render graph, applied to the graph of every window.
+pub(crate) type RenderGraphCallback = Box<dyn Fn(&mut RenderGraph) + Send>;
 
 #[derive(Clone, Default)]
 pub struct Extracted {
@@ -29,15 +38,28 @@ pub struct RenderWorld {
     pub imported_mesh_cache: Arc<RwLock<MeshCache>>,
     pub imported_material_cache: Arc<RwLock<MaterialCache>>,
     pub imported_mesh_allocator: Arc<RwLock<MeshAllocator>>,
+    /// Receives the render context with the new device after a device loss.
+    pub(crate) device_recovered: Receiver<RenderContext>,
+    thread: Option<JoinHandle<()>>,
 }
 
 impl RenderWorld {
-    pub fn new(render_context: RenderContext, surface: wgpu::Surface<'static>) -> Self {
-        let imported_texture_cache = Arc::new(RwLock::new(TextureCache::new()));
+    /// Without a surface (headless), frames are rendered into an offscreen texture.
+    /// `configure_graph` callbacks are applied to the standard render graph of each window.
+    pub(crate) fn new(
+        render_context: RenderContext,
+        surface: Option<WindowSurface>,
+        configure_graph: Vec<RenderGraphCallback>,
+        retain_asset_data: bool,
+    ) -> Self {
+        let imported_texture_cache = Arc::new(RwLock::new(
+            TextureCache::new().with_retained_data(retain_asset_data),
+        ));
         let imported_mesh_cache = Arc::new(RwLock::new(MeshCache::new()));
         let imported_material_cache = Arc::new(RwLock::new(MaterialCache::new()));
-        let imported_mesh_allocator =
-            Arc::new(RwLock::new(MeshAllocator::new(&render_context.device)));
+        let imported_mesh_allocator = Arc::new(RwLock::new(
+            MeshAllocator::new(&render_context.device).with_retained_data(retain_asset_data),
+        ));
 
         // Use a capacity based on frames in flight to prevent deadlocks in get_current_texture.
         let channel_cap = (render_context.frames_in_flight.saturating_sub(1)) as usize;
@@ -46,27 +68,73 @@ impl RenderWorld {
         let mut backend = RenderBackend::new(
             &render_context,
             surface,
+            configure_graph,
             imported_texture_cache.clone(),
             imported_mesh_cache.clone(),
             imported_material_cache.clone(),
             imported_mesh_allocator.clone(),
         );
 
-        std::thread::spawn(move || {
+        let (recovered_tx, recovered_rx) = std::sync::mpsc::channel();
+
+        let thread = std::thread::spawn(move || {
             let mut render_context = render_context;
+            // 设备已重建，等待逻辑线程重新上传共享资源，期间不渲染
+            let mut awaiting_restore = false;
+            let mut recovery_failed = false;
+
             while let Ok(cmd) = rx.recv() {
+                if render_context.is_device_lost() && !awaiting_restore {
+                    match backend.recover_device(&mut render_context) {
+                        Ok(()) => {
+                            log::info!("GPU device recreated");
+                            awaiting_restore = true;
+                            recovery_failed = false;
+                            let _ = recovered_tx.send(render_context.clone());
+                        }
+                        Err(err) => {
+                            if !recovery_failed {
+                                log::error!("Failed to recreate GPU device: {:#}", err);
+                            }
+                            recovery_failed = true;
+                        }
+                    }
+                }
+
                 match cmd {
                     RenderCommand::Render(extracted) => {
-                        backend.run(&render_context, extracted);
+                        if !awaiting_restore && !recovery_failed {
+                            backend.run(&render_context, extracted);
+                        }
+                    }
+                    RenderCommand::Resize(id, w, h) => {
+                        if id == WindowId::PRIMARY {
+                            render_context.surface_config.width = w;
+                            render_context.surface_config.height = h;
+                        }
+                        backend.resize(&render_context, id, w, h);
+                    }
+                    RenderCommand::Capture(request) => {
+                        backend.pending_captures.push(request);
+                    }
+                    RenderCommand::SetPresentMode(present_mode) => {
+                        render_context.surface_config.present_mode = present_mode;
+                        backend.set_present_mode(&render_context, present_mode);
+                    }
+                    RenderCommand::AddWindow(id, surface, w, h) => {
+                        backend.add_window(&render_context, id, surface, w, h);
+                    }
+                    RenderCommand::RemoveWindow(id) => {
+                        backend.remove_window(id);
                     }
-                    RenderCommand::Resize(w, h) => {
-                        render_context.surface_config.width = w;
-                        render_context.surface_config.height = h;
-                        backend.surface.configure(&render_context.device, &render_context.surface_config);
-
-                        // 关键修复：缩放后不仅清理 BindGroup，也要清空旧分辨率的瞬时资源池，防止显存泄露
-                        backend.render_graph.pool.clear_bind_group_cache();
-                        backend.render_graph.pool.clear_transient_pools();
+                    RenderCommand::ResourcesRestored => {
+                        awaiting_restore = false;
+                    }
+                    RenderCommand::Shutdown => {
+                        let _ = render_context
+                            .device
+                            .poll(wgpu::PollType::wait_indefinitely());
+                        break;
                     }
                 }
             }
@@ -78,6 +146,38 @@ impl RenderWorld {
             imported_mesh_cache,
             imported_material_cache,
             imported_mesh_allocator,
+            device_recovered: recovered_rx,
+            thread: Some(thread),
+        }
+    }
+
+    /// Finish the queued frames, wait for the GPU and join the render thread. Called on drop.
+    pub fn shutdown(&mut self) {
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+
+        let _ = self.sender.send(RenderCommand::Shutdown);
+        if thread.join().is_err() {
+            log::error!("Render thread panicked");
         }
     }
+
+    /// Request a copy of the next rendered frame, after tonemapping and sprites.
+    ///
+    /// The image arrives on the returned receiver once that frame has been rendered. If the
+    /// capture fails (e.g. unsupported surface format), the sender is dropped instead.
+    pub fn request_capture(&self) -> Receiver<RgbaImage> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let _ = self
+            .sender
+            .send(RenderCommand::Capture(CaptureRequest { sender: tx }));
+        rx
+    }
+}
+
+impl Drop for RenderWorld {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
 }
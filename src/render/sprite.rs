@@ -13,4 +13,6 @@ pub struct ExtractedSprite2d {
     pub(crate) flip_x: bool,
     pub(crate) flip_y: bool,
     pub(crate) mode: u32,
+    /// `RenderLayers` bitmask.
+    pub(crate) layers: u32,
 }
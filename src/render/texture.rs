@@ -64,10 +64,30 @@ pub struct Texture {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct TextureId(uuid::Uuid);
 
+/// What a texture was created from, kept to recreate it after a device loss.
+#[derive(Clone)]
+pub(crate) enum TextureSource {
+    Raw(RawTextureData),
+    /// The pixels of a raw texture weren't retained, see [`TextureCache::with_retained_data`].
+    Discarded,
+    /// Cubemap converted from a panorama texture.
+    Panorama(TextureId),
+    Depth {
+        width: u32,
+        height: u32,
+        layers: u32,
+        cube_view: bool,
+        label: Option<String>,
+    },
+}
+
 /// Imported texture cache, not managed by ResourcePool.
 pub struct TextureCache {
     pub(crate) storage: HashMap<TextureId, Texture>,
     pub(crate) path_to_id: HashMap<PathBuf, TextureId>,
+    /// 纹理的来源，设备丢失后用于重新创建
+    sources: HashMap<TextureId, TextureSource>,
+    retain_data: bool,
 }
 
 impl TextureCache {
@@ -75,9 +95,17 @@ impl TextureCache {
         Self {
             storage: HashMap::new(),
             path_to_id: HashMap::new(),
+            sources: HashMap::new(),
+            retain_data: false,
         }
     }
 
+    /// Keep the pixels of all raw textures, so they can be uploaded again after a device loss.
+    pub fn with_retained_data(mut self, retain: bool) -> Self {
+        self.retain_data = retain;
+        self
+    }
+
     pub(crate) fn add(&mut self, texture: Texture) -> TextureId {
         let id = TextureId(uuid::Uuid::new_v4());
         self.storage.insert(id, texture);
@@ -108,10 +136,55 @@ impl TextureCache {
 
     pub(crate) fn remove(&mut self, texture_id: TextureId) {
         self.storage.remove(&texture_id);
+        self.sources.remove(&texture_id);
         // Note: In a full implementation, we'd also want to remove from path_to_id.
         // For now, we'll keep it simple.
         self.path_to_id.retain(|_, v| *v != texture_id);
     }
+
+    /// Recreate all textures on a new device, keeping their ids. Textures whose pixels weren't
+    /// retained are removed together with the cubemaps made from them; their owners have to
+    /// create them again.
+    pub(crate) fn restore(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.storage.clear();
+
+        // 先恢复原始纹理，Cubemap 依赖于其全景图
+        for (id, source) in &self.sources {
+            let texture = match source {
+                TextureSource::Raw(raw) => Texture::create_from_raw(device, queue, raw),
+                TextureSource::Depth {
+                    width,
+                    height,
+                    layers,
+                    cube_view,
+                    label,
+                } => Texture::create_depth(
+                    device,
+                    *width,
+                    *height,
+                    *layers,
+                    *cube_view,
+                    label.as_deref(),
+                ),
+                TextureSource::Discarded | TextureSource::Panorama(_) => continue,
+            };
+            self.storage.insert(*id, texture);
+        }
+
+        for (id, source) in &self.sources {
+            if let TextureSource::Panorama(panorama_id) = source {
+                let Some(panorama) = self.storage.get(panorama_id) else {
+                    continue;
+                };
+                let cubemap = Texture::panorama_to_cubemap(device, queue, panorama, None);
+                self.storage.insert(*id, cubemap);
+            }
+        }
+
+        let storage = &self.storage;
+        self.sources.retain(|id, _| storage.contains_key(id));
+        self.path_to_id.retain(|_, id| storage.contains_key(id));
+    }
 }
 
 impl Texture {
@@ -337,28 +410,22 @@ impl Texture {
         label: Option<&str>,
     ) -> Result<TextureId> {
         // Image size.
-        let size = img.dimensions();
+        let (width, height) = img.dimensions();
 
-        let data: &[u8];
-        let bytes_per_row;
+        let pixels;
         let format;
-        let rgba_converted_from_rgb;
 
         match img {
             DynamicImage::ImageLuma8(gray) => {
-                data = &gray;
-                bytes_per_row = 1 * size.0;
+                pixels = gray.to_vec();
                 format = wgpu::TextureFormat::R8Unorm;
             }
             DynamicImage::ImageRgb8(_) => {
-                rgba_converted_from_rgb = img.to_rgba8();
-                data = &rgba_converted_from_rgb;
-                bytes_per_row = 4 * size.0;
+                pixels = img.to_rgba8().into_raw();
                 format = wgpu::TextureFormat::Rgba8UnormSrgb;
             }
             DynamicImage::ImageRgba8(rgba) => {
-                data = rgba;
-                bytes_per_row = 4 * size.0;
+                pixels = rgba.to_vec();
                 format = wgpu::TextureFormat::Rgba8UnormSrgb;
             }
             _ => {
@@ -366,59 +433,15 @@ impl Texture {
             }
         }
 
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label,
-            size: wgpu::Extent3d {
-                width: size.0,
-                height: size.1,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING
-                | wgpu::TextureUsages::COPY_DST
-                | wgpu::TextureUsages::RENDER_ATTACHMENT,
-            view_formats: &[],
-        });
-
-        let img_copy_texture = wgpu::TexelCopyTextureInfo {
-            aspect: wgpu::TextureAspect::All,
-            texture: &texture,
-            mip_level: 0,
-            origin: wgpu::Origin3d::ZERO,
-        };
-
-        // Write image data to texture.
-        queue.write_texture(
-            img_copy_texture,
-            data,
-            wgpu::TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(bytes_per_row),
-                rows_per_image: Some(size.1),
-            },
-            Extent3d {
-                width: size.0,
-                height: size.1,
-                depth_or_array_layers: 1,
-            },
-        );
-
-        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-
-        let texture = Texture {
-            size,
-            texture,
-            view,
+        let raw = RawTextureData {
+            name: label.unwrap_or_default().to_string(),
+            pixels,
+            width,
+            height,
             format,
-            id: NEXT_RESOURCE_ID.fetch_add(1, Ordering::Relaxed),
-            view_id: NEXT_VIEW_ID.fetch_add(1, Ordering::Relaxed),
-            view_cache: Arc::new(Mutex::new(HashMap::new())),
         };
 
-        Ok(cache.add(texture))
+        Ok(Self::from_raw(device, queue, cache, raw))
     }
 
     pub fn create_depth_texture_with_size(
@@ -430,6 +453,30 @@ impl Texture {
         cube_view: bool,
         label: Option<&str>,
     ) -> TextureId {
+        let id = cache.add(Self::create_depth(
+            device, width, height, layers, cube_view, label,
+        ));
+        cache.sources.insert(
+            id,
+            TextureSource::Depth {
+                width,
+                height,
+                layers,
+                cube_view,
+                label: label.map(str::to_string),
+            },
+        );
+        id
+    }
+
+    fn create_depth(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        layers: u32,
+        cube_view: bool,
+        label: Option<&str>,
+    ) -> Self {
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label,
             size: wgpu::Extent3d {
@@ -471,7 +518,7 @@ impl Texture {
             array_layer_count: Some(layers),
         });
 
-        let texture = Texture {
+        Texture {
             size: (width, height),
             texture,
             view,
@@ -479,9 +526,7 @@ impl Texture {
             id: NEXT_RESOURCE_ID.fetch_add(1, Ordering::Relaxed),
             view_id: NEXT_VIEW_ID.fetch_add(1, Ordering::Relaxed),
             view_cache: Arc::new(Mutex::new(HashMap::new())),
-        };
-
-        cache.add(texture)
+        }
     }
 
     pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
@@ -562,6 +607,17 @@ impl Texture {
         cache: &mut TextureCache,
         raw: RawTextureData,
     ) -> TextureId {
+        let id = cache.add(Self::create_from_raw(device, queue, &raw));
+        let source = if cache.retain_data {
+            TextureSource::Raw(raw)
+        } else {
+            TextureSource::Discarded
+        };
+        cache.sources.insert(id, source);
+        id
+    }
+
+    fn create_from_raw(device: &wgpu::Device, queue: &wgpu::Queue, raw: &RawTextureData) -> Self {
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some(&raw.name),
             size: wgpu::Extent3d {
@@ -601,7 +657,7 @@ impl Texture {
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        cache.add(Texture {
+        Texture {
             size: (raw.width, raw.height),
             texture,
             view,
@@ -609,7 +665,7 @@ impl Texture {
             id: NEXT_RESOURCE_ID.fetch_add(1, Ordering::Relaxed),
             view_id: NEXT_VIEW_ID.fetch_add(1, Ordering::Relaxed),
             view_cache: Arc::new(Mutex::new(HashMap::new())),
-        })
+        }
     }
 
     /// 从单张 Panorama (全景图) 创建 Cubemap。
@@ -621,7 +677,21 @@ impl Texture {
         label: Option<&str>,
     ) -> Result<TextureId> {
         let panorama = cache.get(panorama_texture_id).context("Panorama texture not found")?;
+        let cubemap = Self::panorama_to_cubemap(device, queue, panorama, label);
+
+        let id = cache.add(cubemap);
+        cache
+            .sources
+            .insert(id, TextureSource::Panorama(panorama_texture_id));
+        Ok(id)
+    }
 
+    fn panorama_to_cubemap(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        panorama: &Texture,
+        label: Option<&str>,
+    ) -> Self {
         // 1. 创建目标 Cubemap 纹理
         // 通常 Cubemap 的单面大小设为全景图高度的一半（或更小，保持 1:1）
         let face_size = panorama.size.1;
@@ -776,7 +846,7 @@ impl Texture {
             ..Default::default()
         });
 
-        Ok(cache.add(Texture {
+        Texture {
             size: (face_size, face_size),
             texture: cubemap_texture,
             view: cubemap_view,
@@ -784,6 +854,6 @@ impl Texture {
             id: NEXT_RESOURCE_ID.fetch_add(1, Ordering::Relaxed),
             view_id: NEXT_VIEW_ID.fetch_add(1, Ordering::Relaxed),
             view_cache: Arc::new(Mutex::new(HashMap::new())),
-        }))
+        }
     }
 }
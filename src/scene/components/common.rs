@@ -1,5 +1,6 @@
 use glam::Vec2;
 use hecs::Entity;
+use std::path::PathBuf;
 
 /// 节点名称
 pub struct Name(pub String);
@@ -7,12 +8,18 @@ pub struct Name(pub String);
 /// 父节点引用组件
 pub struct Parent(pub Entity);
 
+/// 子节点列表，按 [`Parent`] 维护 (见 `propagate_transforms`)，不要手动修改
+pub struct Children(pub Vec<Entity>);
+
 /// 节点大小 (2D)
 pub struct Size(pub Vec2);
 
 /// 标识当前激活的摄像机 (Tag 组件)
 pub struct ActiveCamera;
 
+/// 已加载的模型、天空盒或精灵纹理的源文件，用于设备丢失后重新加载和保存场景
+pub struct AssetSource(pub PathBuf);
+
 /// 3D 模型组件 (Legacy or wrapper)
 pub struct MeshComponent {
     pub model_path: String,
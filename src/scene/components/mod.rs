@@ -1,5 +1,9 @@
 pub mod common;
+pub mod timer;
 pub mod transform;
+pub mod visibility;
 
 pub use common::*;
+pub use timer::*;
 pub use transform::*;
+pub use visibility::*;
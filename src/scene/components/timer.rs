@@ -0,0 +1,177 @@
+use hecs::Entity;
+
+/// What a [`Timer`] does once its duration has elapsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimerMode {
+    /// Stay finished until reset.
+    Once,
+    /// Wrap around and keep running.
+    Repeating,
+}
+
+/// 倒计时组件，由内置的 timer 系统按虚拟时间推进
+#[derive(Clone, Debug)]
+pub struct Timer {
+    duration: f32,
+    elapsed: f32,
+    mode: TimerMode,
+    paused: bool,
+    finished: bool,
+    /// How many times the timer finished during the last tick (can be > 1 for short repeating
+    /// timers).
+    times_finished_this_tick: u32,
+}
+
+impl Timer {
+    pub fn new(duration: f32, mode: TimerMode) -> Self {
+        Self {
+            duration: duration.max(0.0),
+            elapsed: 0.0,
+            mode,
+            paused: false,
+            finished: false,
+            times_finished_this_tick: 0,
+        }
+    }
+
+    pub fn tick(&mut self, dt: f32) {
+        self.times_finished_this_tick = 0;
+
+        if self.paused || (self.finished && self.mode == TimerMode::Once) {
+            return;
+        }
+
+        self.elapsed += dt;
+
+        if self.elapsed < self.duration {
+            return;
+        }
+
+        self.finished = true;
+
+        match self.mode {
+            TimerMode::Once => {
+                self.elapsed = self.duration;
+                self.times_finished_this_tick = 1;
+            }
+            TimerMode::Repeating => {
+                if self.duration > 0.0 {
+                    self.times_finished_this_tick = (self.elapsed / self.duration) as u32;
+                    self.elapsed %= self.duration;
+                } else {
+                    self.times_finished_this_tick = 1;
+                    self.elapsed = 0.0;
+                }
+            }
+        }
+    }
+
+    /// Whether the timer has finished at least once (and, for `Once` timers, not been reset).
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Whether the timer finished during the last tick.
+    pub fn just_finished(&self) -> bool {
+        self.times_finished_this_tick > 0
+    }
+
+    pub fn times_finished_this_tick(&self) -> u32 {
+        self.times_finished_this_tick
+    }
+
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    pub fn remaining(&self) -> f32 {
+        (self.duration - self.elapsed).max(0.0)
+    }
+
+    /// Elapsed fraction of the duration, from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        if self.duration > 0.0 {
+            (self.elapsed / self.duration).min(1.0)
+        } else {
+            1.0
+        }
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    pub fn set_duration(&mut self, duration: f32) {
+        self.duration = duration.max(0.0);
+    }
+
+    pub fn mode(&self) -> TimerMode {
+        self.mode
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+        self.finished = false;
+        self.times_finished_this_tick = 0;
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
+
+/// Sent by the timer system for each [`Timer`] that finished during the tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimerFinished {
+    /// The entity with the timer.
+    pub entity: Entity,
+    /// Same as [`Timer::times_finished_this_tick`].
+    pub times: u32,
+}
+
+/// 计时组件，由内置的 timer 系统按虚拟时间推进
+#[derive(Clone, Debug, Default)]
+pub struct Stopwatch {
+    elapsed: f32,
+    paused: bool,
+}
+
+impl Stopwatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tick(&mut self, dt: f32) {
+        if !self.paused {
+            self.elapsed += dt;
+        }
+    }
+
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
@@ -1,12 +1,79 @@
 use crate::math::transform::{Transform2d, Transform3d};
 use glam::Mat4;
-use hecs::Entity;
+use std::ops::{Deref, DerefMut};
 
 /// 3D 局部变换组件
-pub struct CTransform3d(pub Transform3d);
+///
+/// Derefs to the [`Transform3d`]. Mutable access marks it as changed, so that
+/// `propagate_transforms` only updates entities whose transform was touched.
+///
+/// Breaking change: the fields are private, so the change can't be missed. Use
+/// [`CTransform3d::new`] or `.into()` instead of the tuple constructor, and access the
+/// transform through deref (`transform.position`) instead of `.0`. The same goes for
+/// [`CTransform2d`].
+pub struct CTransform3d {
+    transform: Transform3d,
+    changed: bool,
+}
+
+/// 2D 局部变换组件，与 [`CTransform3d`] 一样记录修改
+pub struct CTransform2d {
+    transform: Transform2d,
+    changed: bool,
+}
+
+macro_rules! impl_local_transform {
+    ($component:ident, $transform:ident) => {
+        impl $component {
+            /// New components count as changed, so they are propagated once.
+            pub fn new(transform: $transform) -> Self {
+                Self {
+                    transform,
+                    changed: true,
+                }
+            }
+
+            /// Whether the transform was mutably accessed since the last propagation.
+            pub fn is_changed(&self) -> bool {
+                self.changed
+            }
+
+            /// Force propagation without changing the transform.
+            pub fn set_changed(&mut self) {
+                self.changed = true;
+            }
+
+            /// Returns the change flag and clears it.
+            pub(crate) fn take_changed(&mut self) -> bool {
+                std::mem::take(&mut self.changed)
+            }
+        }
 
-/// 2D 局部变换组件
-pub struct CTransform2d(pub Transform2d);
+        impl From<$transform> for $component {
+            fn from(transform: $transform) -> Self {
+                Self::new(transform)
+            }
+        }
+
+        impl Deref for $component {
+            type Target = $transform;
+
+            fn deref(&self) -> &$transform {
+                &self.transform
+            }
+        }
+
+        impl DerefMut for $component {
+            fn deref_mut(&mut self) -> &mut $transform {
+                self.changed = true;
+                &mut self.transform
+            }
+        }
+    };
+}
+
+impl_local_transform!(CTransform3d, Transform3d);
+impl_local_transform!(CTransform2d, Transform2d);
 
 /// 全局变换组件
 pub struct GlobalTransform(pub Mat4);
@@ -17,3 +84,12 @@ impl Default for GlobalTransform {
     }
 }
 
+/// [`ChangeTick`](crate::scene::ChangeTick) at which the `GlobalTransform` last changed, added and
+/// updated by `propagate_transforms`. Systems remember the value they last saw to skip
+/// unchanged entities.
+pub struct TransformChanged(pub u64);
+
+/// Global transform as of the previous logic tick, for interpolating between ticks when
+/// rendering. Added and updated by `propagate_transforms`.
+pub struct PreviousGlobalTransform(pub Mat4);
+
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+/// 可见性组件，没有该组件的实体视为 [`Visibility::Inherited`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Visibility {
+    /// Same as the parent, visible for root entities.
+    #[default]
+    Inherited,
+    /// Hidden, along with the descendants that inherit it.
+    Hidden,
+    /// Visible even if the parent is hidden.
+    Visible,
+}
+
+/// Bitmask of the 32 layers an entity is drawn on, or a camera draws. Entities and cameras
+/// without it are on layer 0 only.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderLayers(pub u32);
+
+impl Default for RenderLayers {
+    fn default() -> Self {
+        Self::layer(0)
+    }
+}
+
+impl RenderLayers {
+    pub const NONE: Self = Self(0);
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// Only on `layer`, which must be less than 32.
+    pub const fn layer(layer: u32) -> Self {
+        Self(1 << layer)
+    }
+
+    /// Also on `layer`.
+    pub const fn with(self, layer: u32) -> Self {
+        Self(self.0 | 1 << layer)
+    }
+
+    /// No longer on `layer`.
+    pub const fn without(self, layer: u32) -> Self {
+        Self(self.0 & !(1 << layer))
+    }
+
+    pub const fn contains(&self, layer: u32) -> bool {
+        self.0 & (1 << layer) != 0
+    }
+
+    /// Whether the two share a layer, i.e. a camera with one draws an entity with the other.
+    pub const fn intersects(&self, other: &Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
@@ -1,9 +1,12 @@
 use crate::render::camera::{CameraUniform, OrthographicProjection};
+use crate::window::WindowId;
 use glam::{Mat4, UVec2};
 
 pub struct Camera2dComponent {
     pub(crate) viewport_size: UVec2,
     pub view: Option<u32>,
+    /// Window this camera renders to.
+    pub target: WindowId,
 }
 
 impl Camera2dComponent {
@@ -11,6 +14,7 @@ impl Camera2dComponent {
         Self {
             viewport_size: UVec2::new(1280, 720),
             view: None,
+            target: WindowId::PRIMARY,
         }
     }
 
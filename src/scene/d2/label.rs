@@ -11,6 +11,8 @@ pub struct LabelComponent {
     pub tracking: f32,
     pub atlas: Option<Atlas>,
     pub last_global_transform: Transform2d,
+    /// `TransformChanged` tick the transform was last checked at.
+    pub(crate) transform_tick: u64,
 }
 
 impl LabelComponent {
@@ -25,6 +27,7 @@ impl LabelComponent {
             tracking: 0.0,
             atlas: None,
             last_global_transform: Transform2d::default(),
+            transform_tick: 0,
         }
     }
 }
@@ -1,6 +1,7 @@
+use crate::math::ray::Ray;
 use crate::render::camera::CameraUniform;
-use crate::window::{InputContent, InputEvent, InputServer};
-use glam::{Mat4, UVec2, Vec3};
+use crate::window::{InputContent, InputEvent, InputServer, WindowId};
+use glam::{Mat4, UVec2, Vec2, Vec3};
 use winit::event::MouseButton;
 use winit::keyboard::KeyCode;
 
@@ -21,6 +22,8 @@ pub struct Camera3dComponent {
     pub viewport_size: UVec2,
     pub frame_count: u64,
     pub prev_view_proj: Mat4, // 新增：保存上一帧的矩阵
+    /// Window this camera renders to.
+    pub target: WindowId,
 }
 
 impl Camera3dComponent {
@@ -38,6 +41,7 @@ impl Camera3dComponent {
             viewport_size: UVec2::new(1280, 720),
             frame_count: 0,
             prev_view_proj: Mat4::IDENTITY,
+            target: WindowId::PRIMARY,
         }
     }
 
@@ -113,6 +117,24 @@ impl Camera3dComponent {
         self.prev_view_proj = proj_mat * view_mat;
     }
 
+    /// World-space ray through `screen_position`, in physical pixels of the target window.
+    pub fn viewport_to_ray(&self, global_transform: &Mat4, screen_position: Vec2) -> Ray {
+        let view_mat = self.calc_view_matrix(global_transform);
+        let aspect_ratio = self.viewport_size.x as f32 / self.viewport_size.y as f32;
+        let proj_mat = Mat4::perspective_rh(self.fov, aspect_ratio, self.near, self.far);
+        let inv_view_proj = (proj_mat * view_mat).inverse();
+
+        let ndc = Vec2::new(
+            screen_position.x / self.viewport_size.x as f32 * 2.0 - 1.0,
+            1.0 - screen_position.y / self.viewport_size.y as f32 * 2.0,
+        );
+        // 近平面深度为 0，远平面为 1
+        let near = inv_view_proj.project_point3(ndc.extend(0.0));
+        let far = inv_view_proj.project_point3(ndc.extend(1.0));
+
+        Ray::new(near, far - near)
+    }
+
     fn get_halton_jitter(&self, index: u64) -> (f32, f32) {
         fn halton(mut i: u64, base: u64) -> f32 {
             let mut f = 1.0;
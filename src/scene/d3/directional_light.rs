@@ -1,5 +1,7 @@
 use crate::math::color::ColorU;
+use serde::{Deserialize, Serialize};
 
+#[derive(Serialize, Deserialize)]
 pub struct DirectionalLightComponent {
     pub color: ColorU,
     pub strength: f32,
@@ -1,7 +1,7 @@
 use crate::animation::property::PropertyProvider;
-use crate::core::Singletons;
 use crate::math::aabb::Aabb;
 use crate::math::transform::Transform3d;
+use crate::math::triangle::Triangle;
 use crate::render::material::{MaterialCache, MaterialId, MaterialStandard};
 use crate::render::mesh_allocator::MeshAllocator;
 use crate::render::vertex::Vertex3d;
@@ -24,6 +24,18 @@ pub struct RawMeshData {
     pub material_index: Option<usize>,
     pub aabb: Aabb,
     pub local_transform: Transform3d,
+    /// glTF node the mesh belongs to, `local_transform` is then the node's transform in the model.
+    pub node: Option<usize>,
+}
+
+/// A glTF node.
+#[derive(Clone)]
+pub struct RawNodeData {
+    pub name: String,
+    /// Index of the parent node, None for scene roots.
+    pub parent: Option<usize>,
+    /// Relative to the parent.
+    pub transform: Transform3d,
 }
 
 #[derive(Clone)]
@@ -56,18 +68,58 @@ pub struct RawModelData {
     pub meshes: Vec<RawMeshData>,
     pub materials: Vec<RawMaterialData>,
     pub aabb: Aabb,
+    /// glTF node hierarchy, parents before children. Empty for other formats.
+    pub nodes: Vec<RawNodeData>,
 }
 
 pub struct Model {
     pub meshes: Vec<MeshId>,
     pub materials: Vec<Option<MaterialId>>,
     pub mesh_transforms: Vec<Transform3d>,
+    /// CPU copies of the meshes, for picking. Only kept with
+    /// [`AppConfig::retain_asset_data`](crate::core::AppConfig::retain_asset_data),
+    /// otherwise empty.
+    pub cpu_meshes: Vec<CpuMesh>,
     pub aabb: Aabb,
 }
 
+/// Vertex positions and triangle indices of a mesh, kept on the CPU after upload.
+#[derive(Clone, Default)]
+pub struct CpuMesh {
+    pub positions: Vec<Vec3>,
+    pub indices: Vec<u32>,
+}
+
+impl CpuMesh {
+    pub fn triangles(&self) -> impl Iterator<Item = Triangle> + '_ {
+        self.indices.chunks_exact(3).filter_map(|tri| {
+            Some(Triangle::new(
+                *self.positions.get(tri[0] as usize)?,
+                *self.positions.get(tri[1] as usize)?,
+                *self.positions.get(tri[2] as usize)?,
+            ))
+        })
+    }
+}
+
 /// 标记一个实体正在等待模型资产加载
 pub struct AssetPending(pub PathBuf);
 
+/// 与 [`AssetPending`] 一起使用：glTF 模型不合并为一个 [`Model`]，而是为每个节点生成一个子实体
+/// (`Name`, `CTransform3d`, `Parent`)，节点的网格作为该实体的 [`Model`]。
+/// 加载后保留在根实体上，场景保存时根实体记录为待加载的资产
+pub struct NodeHierarchy;
+
+/// A mesh on the GPU, before it is placed in a model.
+struct UploadedMesh {
+    mesh: MeshId,
+    material: Option<MaterialId>,
+    transform: Transform3d,
+    node: Option<usize>,
+    aabb: Aabb,
+    cpu: Option<CpuMesh>,
+}
+
 impl Model {
     /// Create a placeholder model that will be populated later.
     pub fn empty() -> Self {
@@ -75,6 +127,7 @@ impl Model {
             meshes: Vec::new(),
             materials: Vec::new(),
             mesh_transforms: Vec::new(),
+            cpu_meshes: Vec::new(),
             aabb: Aabb::default(),
         }
     }
@@ -239,6 +292,7 @@ impl Model {
                 material_index: m.mesh.material_id,
                 aabb,
                 local_transform: Transform3d::default(),
+                node: None,
             });
         }
 
@@ -246,6 +300,7 @@ impl Model {
             meshes: raw_meshes,
             materials: raw_materials,
             aabb: model_aabb,
+            nodes: Vec::new(),
         })
     }
 
@@ -453,9 +508,12 @@ impl Model {
             alpha_mode: AlphaMode::Opaque,
         });
 
-        let mut raw_meshes = Vec::new();
-        let mut model_aabb = Aabb::default();
-        let mut first = true;
+        let mut raw_model = RawModelData {
+            meshes: Vec::new(),
+            materials: raw_materials,
+            aabb: Aabb::default(),
+            nodes: Vec::new(),
+        };
 
         for scene in document.scenes() {
             for node in scene.nodes() {
@@ -463,29 +521,23 @@ impl Model {
                     &node,
                     &buffers,
                     &images,
-                    &mut raw_meshes,
-                    &mut model_aabb,
-                    &mut first,
+                    &mut raw_model,
+                    None,
                     Mat4::IDENTITY,
                     default_material_index,
                 );
             }
         }
 
-        Ok(RawModelData {
-            meshes: raw_meshes,
-            materials: raw_materials,
-            aabb: model_aabb,
-        })
+        Ok(raw_model)
     }
 
     fn process_node(
         node: &gltf::Node,
         buffers: &[gltf::buffer::Data],
         images: &[gltf::image::Data],
-        raw_meshes: &mut Vec<RawMeshData>,
-        model_aabb: &mut Aabb,
-        first: &mut bool,
+        raw_model: &mut RawModelData,
+        parent: Option<usize>,
         parent_transform: Mat4,
         default_material_index: usize,
     ) {
@@ -497,6 +549,20 @@ impl Model {
         );
         let world_mat = parent_transform * local_mat;
 
+        let node_index = raw_model.nodes.len();
+        raw_model.nodes.push(RawNodeData {
+            name: node
+                .name()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("Node{}", node.index())),
+            parent,
+            transform: Transform3d {
+                position: Vec3::from_array(translation),
+                rotation: Quat::from_array(rotation),
+                scale: Vec3::from_array(scale),
+            },
+        });
+
         if let Some(mesh) = node.mesh() {
             for primitive in mesh.primitives() {
                 let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
@@ -555,14 +621,13 @@ impl Model {
                 };
 
                 let world_aabb = aabb.transform(&transform);
-                if *first {
-                    *model_aabb = world_aabb;
-                    *first = false;
+                raw_model.aabb = if raw_model.meshes.is_empty() {
+                    world_aabb
                 } else {
-                    *model_aabb = model_aabb.union(&world_aabb);
-                }
+                    raw_model.aabb.union(&world_aabb)
+                };
 
-                raw_meshes.push(RawMeshData {
+                raw_model.meshes.push(RawMeshData {
                     name: mesh.name().unwrap_or("").to_string(),
                     vertices,
                     indices,
@@ -574,6 +639,7 @@ impl Model {
                     ),
                     aabb,
                     local_transform: transform,
+                    node: Some(node_index),
                 });
             }
         }
@@ -583,9 +649,8 @@ impl Model {
                 &child,
                 buffers,
                 images,
-                raw_meshes,
-                model_aabb,
-                first,
+                raw_model,
+                Some(node_index),
                 world_mat,
                 default_material_index,
             );
@@ -602,6 +667,70 @@ impl Model {
         global_mesh_cache: &mut MeshCache,
         global_mesh_allocator: &mut MeshAllocator,
     ) {
+        self.aabb = raw.aabb;
+
+        for mesh in Self::upload(
+            raw,
+            render_server,
+            global_texture_cache,
+            global_material_cache,
+            global_mesh_cache,
+            global_mesh_allocator,
+        ) {
+            self.meshes.push(mesh.mesh);
+            self.materials.push(mesh.material);
+            self.mesh_transforms.push(mesh.transform);
+            self.cpu_meshes.extend(mesh.cpu);
+        }
+    }
+
+    /// Upload a glTF model as one model per node, in node order. Meshes sit at the origin of
+    /// their node, nodes without meshes get an empty model.
+    pub fn finalize_nodes(
+        mut raw: RawModelData,
+        render_server: &RenderContext,
+        global_texture_cache: &mut TextureCache,
+        global_material_cache: &mut MaterialCache,
+        global_mesh_cache: &mut MeshCache,
+        global_mesh_allocator: &mut MeshAllocator,
+    ) -> Vec<(RawNodeData, Model)> {
+        let nodes = std::mem::take(&mut raw.nodes);
+        let mut models: Vec<Model> = nodes.iter().map(|_| Model::empty()).collect();
+
+        for mesh in Self::upload(
+            raw,
+            render_server,
+            global_texture_cache,
+            global_material_cache,
+            global_mesh_cache,
+            global_mesh_allocator,
+        ) {
+            let Some(model) = mesh.node.and_then(|node| models.get_mut(node)) else {
+                continue;
+            };
+
+            model.aabb = if model.meshes.is_empty() {
+                mesh.aabb
+            } else {
+                model.aabb.union(&mesh.aabb)
+            };
+            model.meshes.push(mesh.mesh);
+            model.materials.push(mesh.material);
+            model.mesh_transforms.push(Transform3d::default());
+            model.cpu_meshes.extend(mesh.cpu);
+        }
+
+        nodes.into_iter().zip(models).collect()
+    }
+
+    fn upload(
+        raw: RawModelData,
+        render_server: &RenderContext,
+        global_texture_cache: &mut TextureCache,
+        global_material_cache: &mut MaterialCache,
+        global_mesh_cache: &mut MeshCache,
+        global_mesh_allocator: &mut MeshAllocator,
+    ) -> Vec<UploadedMesh> {
         let mut material_ids = Vec::new();
 
         for m in raw.materials {
@@ -670,6 +799,8 @@ impl Model {
             }));
         }
 
+        let retain = global_mesh_allocator.retains_data();
+        let mut meshes = Vec::with_capacity(raw.meshes.len());
         for m in raw.meshes {
             let (v_offset, i_offset) =
                 global_mesh_allocator.allocate(&render_server.queue, &m.vertices, &m.indices);
@@ -681,13 +812,20 @@ impl Model {
                 m.indices.len() as u32,
                 m.aabb,
             ));
-            self.meshes.push(mesh_id);
-            self.materials
-                .push(m.material_index.map(|idx| material_ids[idx]));
-            self.mesh_transforms.push(m.local_transform);
+            meshes.push(UploadedMesh {
+                mesh: mesh_id,
+                material: m.material_index.map(|idx| material_ids[idx]),
+                transform: m.local_transform,
+                node: m.node,
+                aabb: m.aabb,
+                cpu: retain.then(|| CpuMesh {
+                    positions: m.vertices.iter().map(|v| Vec3::from(v.position)).collect(),
+                    indices: m.indices,
+                }),
+            });
         }
 
-        self.aabb = raw.aabb;
+        meshes
     }
 
     pub fn get_world_aabb(&self, transform: &Transform3d) -> Aabb {
@@ -1,6 +1,8 @@
 use crate::math::color::ColorU;
+use serde::{Deserialize, Serialize};
 
 /// 点光源组件
+#[derive(Serialize, Deserialize)]
 pub struct PointLightComponent {
     pub color: ColorU,
     pub strength: f32,
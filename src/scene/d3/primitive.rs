@@ -27,6 +27,7 @@ impl MeshPrimitive {
             material_index: Some(0),
             aabb,
             local_transform: Transform3d::default(),
+            node: None,
         };
 
         let material = RawMaterialData {
@@ -50,6 +51,7 @@ impl MeshPrimitive {
             meshes: vec![mesh],
             materials: vec![material],
             aabb,
+            nodes: Vec::new(),
         }
     }
 }
@@ -0,0 +1,253 @@
+use crate::scene::components::{CTransform2d, CTransform3d, Children, Name, Parent, Visibility};
+use anyhow::{bail, Result};
+use glam::{Mat4, Vec2};
+use hecs::{Entity, World};
+
+/// The `Parent` an entity was last synced with, for finding `Parent` components that were added,
+/// replaced or removed without [`set_parent`]. `listed` is false once the parent is despawned.
+pub(crate) struct SyncedParent {
+    parent: Entity,
+    listed: bool,
+}
+
+/// Whether `ancestor` is reached by following the parents of `entity`.
+pub fn is_descendant_of(ecs: &World, entity: Entity, ancestor: Entity) -> bool {
+    let mut current = entity;
+    // 层级不会超过实体数，防止环状引用导致死循环
+    for _ in 0..ecs.len() {
+        let Ok(parent) = ecs.get::<&Parent>(current) else {
+            return false;
+        };
+        if parent.0 == ancestor {
+            return true;
+        }
+        current = parent.0;
+    }
+    false
+}
+
+/// A descendant of `root` with the given [`Name`], e.g. a node of a model spawned with
+/// `NodeHierarchy`. If several match, any of them is returned.
+pub fn find_descendant(ecs: &World, root: Entity, name: &str) -> Option<Entity> {
+    ecs.query::<(Entity, &Name)>()
+        .with::<&Parent>()
+        .iter()
+        .find(|(entity, n)| n.0 == name && is_descendant_of(ecs, *entity, root))
+        .map(|(entity, _)| entity)
+}
+
+/// Matrix of the local 3D or 2D transform, None if the entity has neither.
+pub(crate) fn local_matrix(ecs: &World, entity: Entity) -> Option<Mat4> {
+    if let Ok(t) = ecs.get::<&CTransform3d>(entity) {
+        Some(t.matrix())
+    } else if let Ok(t) = ecs.get::<&CTransform2d>(entity) {
+        Some(t.to_mat4())
+    } else {
+        None
+    }
+}
+
+/// World matrix from the local transforms of the entity and its ancestors. Unlike
+/// `GlobalTransform` it includes changes made since the last propagation.
+pub fn world_matrix(ecs: &World, entity: Entity) -> Mat4 {
+    let mut matrix = local_matrix(ecs, entity).unwrap_or(Mat4::IDENTITY);
+    let mut current = entity;
+    for _ in 0..ecs.len() {
+        let Ok(parent) = ecs.get::<&Parent>(current).map(|p| p.0) else {
+            break;
+        };
+        matrix = local_matrix(ecs, parent).unwrap_or(Mat4::IDENTITY) * matrix;
+        current = parent;
+    }
+    matrix
+}
+
+/// Resolves [`Visibility::Inherited`] by following the parents of `entity`.
+pub fn is_visible(ecs: &World, entity: Entity) -> bool {
+    let mut current = entity;
+    for _ in 0..ecs.len() {
+        match ecs.get::<&Visibility>(current).map(|v| *v) {
+            Ok(Visibility::Hidden) => return false,
+            Ok(Visibility::Visible) => return true,
+            _ => {}
+        }
+        let Ok(parent) = ecs.get::<&Parent>(current).map(|p| p.0) else {
+            break;
+        };
+        current = parent;
+    }
+    true
+}
+
+/// Attach `child` to `parent`, or detach it with None. With `keep_world_transform` the local
+/// transform is adjusted so the child stays in place, otherwise it is kept and the child moves
+/// with its new parent.
+pub fn set_parent(
+    ecs: &mut World,
+    child: Entity,
+    parent: Option<Entity>,
+    keep_world_transform: bool,
+) -> Result<()> {
+    if !ecs.contains(child) {
+        bail!("Entity {:?} doesn't exist", child);
+    }
+    if let Some(parent) = parent {
+        if !ecs.contains(parent) {
+            bail!("Parent {:?} doesn't exist", parent);
+        }
+        if parent == child || is_descendant_of(ecs, parent, child) {
+            bail!("Parenting {:?} to {:?} would create a cycle", child, parent);
+        }
+    }
+
+    if keep_world_transform {
+        let world = world_matrix(ecs, child);
+        let parent_world = parent.map_or(Mat4::IDENTITY, |parent| world_matrix(ecs, parent));
+        set_local_matrix(ecs, child, parent_world.inverse() * world);
+    }
+
+    // 先同步可能由其他方式设置的父节点，保证 Children 与 SyncedParent 一致
+    if let Ok(synced) = ecs.get::<&SyncedParent>(child).map(|p| p.parent) {
+        remove_child(ecs, synced, child);
+    }
+
+    match parent {
+        Some(parent) => {
+            ecs.insert_one(child, Parent(parent))?;
+            add_child(ecs, parent, child);
+        }
+        None => {
+            let _ = ecs.remove::<(Parent, SyncedParent)>(child);
+        }
+    }
+    mark_changed(ecs, child);
+
+    Ok(())
+}
+
+/// Detach `child` from its parent, same as `set_parent(ecs, child, None, ..)`.
+pub fn remove_parent(ecs: &mut World, child: Entity, keep_world_transform: bool) -> Result<()> {
+    set_parent(ecs, child, None, keep_world_transform)
+}
+
+/// Despawn an entity and all its descendants.
+pub fn despawn_recursive(ecs: &mut World, entity: Entity) {
+    // 本 tick 直接插入 Parent 的子节点还没有进入 Children
+    sync_children(ecs);
+
+    if let Ok(parent) = ecs.get::<&SyncedParent>(entity).map(|p| p.parent) {
+        remove_child(ecs, parent, entity);
+    }
+
+    let mut stack = vec![entity];
+    while let Some(current) = stack.pop() {
+        if let Ok(children) = ecs.get::<&Children>(current) {
+            stack.extend(children.0.iter().copied());
+        }
+        let _ = ecs.despawn(current);
+    }
+}
+
+/// Update [`Children`] for the `Parent` components added, replaced or removed without
+/// [`set_parent`] since the last call, and for parents that were despawned. Only those entities
+/// are touched, new children are appended in entity order. Returns the entities whose parent
+/// changed.
+pub(crate) fn sync_children(ecs: &mut World) -> Vec<Entity> {
+    let mut changed: Vec<(Entity, Option<Entity>, Option<Entity>)> = ecs
+        .query::<(Entity, &Parent, Option<&SyncedParent>)>()
+        .iter()
+        .filter(|(_, parent, synced)| {
+            synced.is_none_or(|synced| {
+                synced.parent != parent.0 || (synced.listed && !ecs.contains(synced.parent))
+            })
+        })
+        .map(|(child, parent, synced)| (child, synced.map(|s| s.parent), Some(parent.0)))
+        .collect();
+    changed.extend(
+        ecs.query::<(Entity, &SyncedParent)>()
+            .without::<&Parent>()
+            .iter()
+            .map(|(child, synced)| (child, Some(synced.parent), None)),
+    );
+    changed.sort_by_key(|(child, _, _)| *child);
+
+    for (child, old, new) in &changed {
+        if let Some(old) = old {
+            remove_child(ecs, *old, *child);
+        }
+        match new {
+            Some(parent) => add_child(ecs, *parent, *child),
+            None => {
+                let _ = ecs.remove_one::<SyncedParent>(*child);
+            }
+        }
+    }
+
+    changed.into_iter().map(|(child, _, _)| child).collect()
+}
+
+/// Append `child` to the children of `parent`, and remember it in [`SyncedParent`].
+fn add_child(ecs: &mut World, parent: Entity, child: Entity) {
+    // 父节点不存在时子节点视为根节点
+    if !ecs.contains(parent) {
+        let synced = SyncedParent {
+            parent,
+            listed: false,
+        };
+        let _ = ecs.insert_one(child, synced);
+        return;
+    }
+
+    let added = ecs
+        .get::<&mut Children>(parent)
+        .map(|mut children| children.0.push(child))
+        .is_ok();
+    if !added {
+        let _ = ecs.insert_one(parent, Children(vec![child]));
+    }
+    let synced = SyncedParent {
+        parent,
+        listed: true,
+    };
+    let _ = ecs.insert_one(child, synced);
+}
+
+fn remove_child(ecs: &mut World, parent: Entity, child: Entity) {
+    let emptied = match ecs.get::<&mut Children>(parent) {
+        Ok(mut children) => {
+            if let Some(index) = children.0.iter().position(|c| *c == child) {
+                children.0.remove(index);
+            }
+            children.0.is_empty()
+        }
+        Err(_) => false,
+    };
+    if emptied {
+        let _ = ecs.remove_one::<Children>(parent);
+    }
+}
+
+/// Make `propagate_transforms` update the entity even if its local transform is unchanged.
+fn mark_changed(ecs: &World, entity: Entity) {
+    if let Ok(mut t) = ecs.get::<&mut CTransform3d>(entity) {
+        t.set_changed();
+    } else if let Ok(mut t) = ecs.get::<&mut CTransform2d>(entity) {
+        t.set_changed();
+    }
+}
+
+fn set_local_matrix(ecs: &mut World, entity: Entity, matrix: Mat4) {
+    if let Ok(mut t) = ecs.get::<&mut CTransform3d>(entity) {
+        let (scale, rotation, position) = matrix.to_scale_rotation_translation();
+        t.position = position;
+        t.rotation = rotation;
+        t.scale = scale;
+    } else if let Ok(mut t) = ecs.get::<&mut CTransform2d>(entity) {
+        t.position = matrix.w_axis.truncate().truncate();
+        t.rotation = matrix.x_axis.y.atan2(matrix.x_axis.x);
+        t.scale = Vec2::new(
+            matrix.x_axis.truncate().truncate().length(),
+            matrix.y_axis.truncate().truncate().length(),
+        );
+    }
+}
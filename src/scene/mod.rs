@@ -1,11 +1,23 @@
 pub mod components;
 pub mod d2;
 pub mod d3;
+pub mod hierarchy;
+pub mod picking;
+pub mod resources;
+pub mod schedule;
+pub mod serialization;
+pub mod spatial_index;
 pub mod systems;
 pub mod world;
 
 pub use components::*;
 pub use d2::*;
 pub use d3::*;
+pub use hierarchy::*;
+pub use picking::*;
+pub use resources::*;
+pub use schedule::*;
+pub use serialization::*;
+pub use spatial_index::*;
 pub use systems::*;
 pub use world::*;
@@ -0,0 +1,99 @@
+use crate::math::aabb::Aabb;
+use crate::math::bvh::Bvh;
+use crate::math::ray::Ray;
+use crate::scene::components::{ActiveCamera, GlobalTransform, RenderLayers};
+use crate::scene::d3::{Camera3dComponent, Model};
+use crate::scene::hierarchy::is_visible;
+use crate::window::WindowId;
+use glam::{Mat4, Vec2, Vec3};
+use hecs::{Entity, World};
+
+/// The nearest model hit by a ray.
+#[derive(Clone, Copy, Debug)]
+pub struct RayHit {
+    pub entity: Entity,
+    /// From the ray origin, in world units.
+    pub distance: f32,
+    pub position: Vec3,
+}
+
+/// The nearest visible model under `screen_position` (e.g. from
+/// `InputServer::get_mouse_position`), seen by the active 3D camera of `window` on its layers.
+pub fn pick(ecs: &World, screen_position: impl Into<Vec2>, window: WindowId) -> Option<RayHit> {
+    let (ray, layers) = ecs
+        .query::<(&Camera3dComponent, &GlobalTransform, Option<&RenderLayers>)>()
+        .with::<&ActiveCamera>()
+        .iter()
+        .find(|(camera, ..)| camera.target == window)
+        .map(|(camera, global, layers)| {
+            (
+                camera.viewport_to_ray(&global.0, screen_position.into()),
+                layers.copied().unwrap_or_default(),
+            )
+        })?;
+
+    cast_ray(ecs, &ray, layers)
+}
+
+/// The nearest visible model on `layers` hit by a world-space ray. Candidates come from a BVH
+/// over the world AABBs of the models and are then tested triangle by triangle, or by their AABB
+/// when the model keeps no CPU meshes.
+pub fn cast_ray(ecs: &World, ray: &Ray, layers: RenderLayers) -> Option<RayHit> {
+    let mut query = ecs.query::<(Entity, &Model, &GlobalTransform, Option<&RenderLayers>)>();
+    let models: Vec<(Entity, &Model, Mat4, Aabb)> = query
+        .iter()
+        .filter(|(id, _, _, model_layers)| {
+            layers.intersects(&model_layers.copied().unwrap_or_default()) && is_visible(ecs, *id)
+        })
+        .map(|(id, model, global, _)| {
+            (
+                id,
+                model,
+                global.0,
+                model.aabb.transform_by_matrix(&global.0),
+            )
+        })
+        .collect();
+
+    let bvh = Bvh::build(
+        models
+            .iter()
+            .enumerate()
+            .map(|(i, (.., aabb))| (*aabb, i))
+            .collect(),
+    );
+
+    let (i, distance) = bvh.ray_cast(ray, f32::INFINITY, |i| {
+        let (_, model, global, aabb) = models[*i];
+        if model.cpu_meshes.is_empty() {
+            return if model.meshes.is_empty() {
+                None
+            } else {
+                ray.intersect_aabb(&aabb)
+            };
+        }
+        intersect_model(model, &global, ray)
+    })?;
+
+    Some(RayHit {
+        entity: models[i].0,
+        distance,
+        position: ray.at(distance),
+    })
+}
+
+fn intersect_model(model: &Model, global: &Mat4, ray: &Ray) -> Option<f32> {
+    let mut nearest: Option<f32> = None;
+    for (mesh, transform) in model.cpu_meshes.iter().zip(&model.mesh_transforms) {
+        // 在网格的局部空间中求交，距离不受变换影响
+        let local_ray = ray.transform_by_matrix(&(*global * transform.matrix()).inverse());
+        for triangle in mesh.triangles() {
+            if let Some(distance) = local_ray.intersect_triangle(&triangle) {
+                if nearest.is_none_or(|nearest| distance < nearest) {
+                    nearest = Some(distance);
+                }
+            }
+        }
+    }
+    nearest
+}
@@ -0,0 +1,105 @@
+use crate::core::{EventWriter, Events};
+use std::any::{type_name, Any, TypeId};
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::HashMap;
+
+/// Global state shared by systems, one value per type. The engine stores its servers here
+/// (`Time`, `RenderContext`, `InputServer`, `Windows`, `FontServer`, `AssetServer`), plugins and
+/// apps can add their own.
+///
+/// Resources are borrowed like a `RefCell`, so several can be used at once:
+/// ```ignore
+/// let render_context = resources.resource::<RenderContext>();
+/// let mut asset_server = resources.resource_mut::<AssetServer>();
+/// ```
+#[derive(Default)]
+pub struct Resources {
+    resources: HashMap<TypeId, RefCell<Box<dyn Any>>>,
+    /// Updates the [`Events`] of each registered event type after a tick.
+    event_updaters: Vec<fn(&Resources)>,
+}
+
+impl Resources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a resource, replacing any previous one of the same type.
+    pub fn insert<T: 'static>(&mut self, value: T) {
+        self.resources
+            .insert(TypeId::of::<T>(), RefCell::new(Box::new(value)));
+    }
+
+    pub fn remove<T: 'static>(&mut self) -> Option<T> {
+        let value = self.resources.remove(&TypeId::of::<T>())?.into_inner();
+        value.downcast().ok().map(|value| *value)
+    }
+
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.resources.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Panics if the resource is mutably borrowed.
+    pub fn get<T: 'static>(&self) -> Option<Ref<'_, T>> {
+        let cell = self.resources.get(&TypeId::of::<T>())?;
+        Some(Ref::map(cell.borrow(), |value| {
+            value.downcast_ref().unwrap()
+        }))
+    }
+
+    /// Panics if the resource is already borrowed.
+    pub fn get_mut<T: 'static>(&self) -> Option<RefMut<'_, T>> {
+        let cell = self.resources.get(&TypeId::of::<T>())?;
+        Some(RefMut::map(cell.borrow_mut(), |value| {
+            value.downcast_mut().unwrap()
+        }))
+    }
+
+    /// Like [`Resources::get`], for resources that are known to exist.
+    pub fn resource<T: 'static>(&self) -> Ref<'_, T> {
+        self.get()
+            .unwrap_or_else(|| panic!("Resource {} not found", type_name::<T>()))
+    }
+
+    /// Like [`Resources::get_mut`], for resources that are known to exist.
+    pub fn resource_mut<T: 'static>(&self) -> RefMut<'_, T> {
+        self.get_mut()
+            .unwrap_or_else(|| panic!("Resource {} not found", type_name::<T>()))
+    }
+
+    /// Register an event type. Its [`Events`] queue is stored as a resource and updated after
+    /// every fixed tick. Registering a type again does nothing.
+    pub fn add_event<T: 'static>(&mut self) {
+        if self.contains::<Events<T>>() {
+            return;
+        }
+
+        self.insert(Events::<T>::new());
+        self.event_updaters.push(|resources| {
+            if let Some(mut events) = resources.get_mut::<Events<T>>() {
+                events.update();
+            }
+        });
+    }
+
+    /// The event queue of `T`, if the event type has been registered.
+    pub fn events<T: 'static>(&self) -> Option<Ref<'_, Events<T>>> {
+        self.get::<Events<T>>()
+    }
+
+    /// Writer for events of type `T`, registering the event type if needed.
+    pub fn event_writer<T: 'static>(&mut self) -> EventWriter<'_, T> {
+        self.add_event::<T>();
+        EventWriter::new(self.resource_mut::<Events<T>>())
+    }
+
+    pub fn send_event<T: 'static>(&mut self, event: T) {
+        self.event_writer().send(event);
+    }
+
+    pub(crate) fn update_events(&self) {
+        for update in &self.event_updaters {
+            update(self);
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use crate::core::{Time, Track};
+use crate::render::render_world::RenderWorld;
+use crate::scene::Resources;
+use hecs::World as EcsWorld;
+use std::collections::{BTreeSet, HashMap};
+use std::time::Instant;
+
+/// Logic system, called with the virtual tick length in seconds (real frame time for
+/// [`Stage::Extract`]), see [`crate::core::Time`].
+pub type System = Box<dyn FnMut(&mut EcsWorld, &mut Resources, &mut RenderWorld, f32)>;
+
+/// Labels of the systems registered by the [`crate::core::DefaultPlugins`], for ordering
+/// custom systems relative to them.
+pub mod system_labels {
+    /// Ticks `Timer` and `Stopwatch` components.
+    pub const TIMERS: &str = "timers";
+    /// Receives loaded assets and finalizes pending models, skies and sprites.
+    pub const ASSETS: &str = "assets";
+    /// Applies input to camera controllers.
+    pub const CAMERA_CONTROLLER_INPUT: &str = "camera_controller_input";
+    /// Syncs camera viewports with the render target.
+    pub const CAMERAS: &str = "cameras";
+    pub const ANIMATION: &str = "animation";
+    /// Moves cameras driven by a controller.
+    pub const CAMERA_CONTROLLER: &str = "camera_controller";
+    /// Steps the rigid-body simulation.
+    pub const PHYSICS: &str = "physics";
+    pub const PROPAGATE_TRANSFORMS: &str = "propagate_transforms";
+    /// Updates the `SpatialIndex` from the propagated transforms.
+    pub const SPATIAL_INDEX: &str = "spatial_index";
+    /// Reconciles fonts and lays out labels.
+    pub const TEXT: &str = "text";
+}
+
+/// Stages run in this order. The first three run every fixed tick; `Extract` runs once per
+/// rendered frame, right before the world is extracted for the render thread.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Stage {
+    PreUpdate,
+    Update,
+    PostUpdate,
+    Extract,
+}
+
+pub struct SystemEntry {
+    label: String,
+    system: System,
+    before: Vec<String>,
+    after: Vec<String>,
+}
+
+impl SystemEntry {
+    /// Run before the system with `label` in the same stage.
+    pub fn before(&mut self, label: impl Into<String>) -> &mut Self {
+        self.before.push(label.into());
+        self
+    }
+
+    /// Run after the system with `label` in the same stage.
+    pub fn after(&mut self, label: impl Into<String>) -> &mut Self {
+        self.after.push(label.into());
+        self
+    }
+}
+
+#[derive(Default)]
+struct StageSystems {
+    systems: Vec<SystemEntry>,
+    cached_execution_order: Option<Vec<usize>>,
+}
+
+/// Logic systems grouped by stage. Within a stage, systems are topologically ordered by their
+/// `before`/`after` constraints, otherwise they keep their registration order.
+#[derive(Default)]
+pub struct Schedule {
+    stages: HashMap<Stage, StageSystems>,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a system under a unique label. A system with the same label is replaced.
+    pub fn add_system<F>(
+        &mut self,
+        stage: Stage,
+        label: impl Into<String>,
+        system: F,
+    ) -> &mut SystemEntry
+    where
+        F: FnMut(&mut EcsWorld, &mut Resources, &mut RenderWorld, f32) + 'static,
+    {
+        let label = label.into();
+        if self.remove_system(&label) {
+            log::info!("System '{}' replaced", label);
+        }
+
+        let stage_systems = self.stages.entry(stage).or_default();
+        stage_systems.cached_execution_order = None;
+        stage_systems.systems.push(SystemEntry {
+            label,
+            system: Box::new(system),
+            before: Vec::new(),
+            after: Vec::new(),
+        });
+        stage_systems.systems.last_mut().unwrap()
+    }
+
+    /// Returns false if there is no system with this label.
+    pub fn remove_system(&mut self, label: &str) -> bool {
+        for stage_systems in self.stages.values_mut() {
+            if let Some(pos) = stage_systems.systems.iter().position(|s| s.label == label) {
+                stage_systems.systems.remove(pos);
+                stage_systems.cached_execution_order = None;
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn has_system(&self, label: &str) -> bool {
+        self.stages
+            .values()
+            .any(|stage_systems| stage_systems.systems.iter().any(|s| s.label == label))
+    }
+
+    /// Labels of a stage's systems in execution order.
+    pub fn execution_order(&mut self, stage: Stage) -> Vec<String> {
+        let Some(stage_systems) = self.stages.get_mut(&stage) else {
+            return Vec::new();
+        };
+
+        let order = stage_systems.execution_order().to_vec();
+        order
+            .into_iter()
+            .map(|i| stage_systems.systems[i].label.clone())
+            .collect()
+    }
+
+    pub fn run(
+        &mut self,
+        stage: Stage,
+        ecs: &mut EcsWorld,
+        resources: &mut Resources,
+        render_world: &mut RenderWorld,
+        dt: f32,
+    ) {
+        let Some(stage_systems) = self.stages.get_mut(&stage) else {
+            return;
+        };
+
+        let profiler = resources.get::<Time>().map(|time| time.profiler.clone());
+
+        let order = stage_systems.execution_order().to_vec();
+        for i in order {
+            let entry = &mut stage_systems.systems[i];
+            let start = Instant::now();
+            (entry.system)(ecs, resources, render_world, dt);
+            if let Some(profiler) = &profiler {
+                profiler.record(Track::Logic, &entry.label, start);
+            }
+        }
+    }
+}
+
+impl StageSystems {
+    fn execution_order(&mut self) -> &[usize] {
+        if self.cached_execution_order.is_none() {
+            self.cached_execution_order = Some(self.topological_sort());
+        }
+        self.cached_execution_order.as_ref().unwrap()
+    }
+
+    /// Kahn's algorithm, always picking the earliest registered ready system so that
+    /// unconstrained systems keep their registration order.
+    fn topological_sort(&self) -> Vec<usize> {
+        let index_of: HashMap<&str, usize> = self
+            .systems
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.label.as_str(), i))
+            .collect();
+
+        let mut in_degree = vec![0; self.systems.len()];
+        let mut enables = vec![Vec::new(); self.systems.len()];
+
+        for (i, system) in self.systems.iter().enumerate() {
+            let edges = system
+                .after
+                .iter()
+                .map(|label| (label, true))
+                .chain(system.before.iter().map(|label| (label, false)));
+
+            for (label, is_after) in edges {
+                let Some(&j) = index_of.get(label.as_str()) else {
+                    log::warn!(
+                        "System '{}' is ordered against '{}', which is not in the same stage",
+                        system.label,
+                        label
+                    );
+                    continue;
+                };
+
+                let (from, to) = if is_after { (j, i) } else { (i, j) };
+                enables[from].push(to);
+                in_degree[to] += 1;
+            }
+        }
+
+        let mut ready: BTreeSet<usize> = (0..self.systems.len())
+            .filter(|&i| in_degree[i] == 0)
+            .collect();
+
+        let mut result = Vec::with_capacity(self.systems.len());
+        while let Some(i) = ready.pop_first() {
+            result.push(i);
+            for &next in &enables[i] {
+                in_degree[next] -= 1;
+                if in_degree[next] == 0 {
+                    ready.insert(next);
+                }
+            }
+        }
+
+        // 存在环：剩余系统按注册顺序执行
+        if result.len() < self.systems.len() {
+            let cyclic: Vec<usize> = (0..self.systems.len())
+                .filter(|i| !result.contains(i))
+                .collect();
+            log::error!(
+                "Cycle in system ordering: {:?}",
+                cyclic
+                    .iter()
+                    .map(|&i| &self.systems[i].label)
+                    .collect::<Vec<_>>()
+            );
+            result.extend(cyclic);
+        }
+
+        result
+    }
+}
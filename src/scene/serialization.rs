@@ -0,0 +1,529 @@
+use crate::animation::AnimationPlayer;
+use crate::physics::{Collider, RigidBody, Velocity};
+use crate::scene::components::*;
+use crate::scene::d2::{Camera2dComponent, LabelComponent, SpriteAssetPending, SpriteComponent};
+use crate::scene::d3::{
+    AssetPending, Camera3dComponent, DirectionalLightComponent, Model, NodeHierarchy,
+    PointLightComponent, SkyAssetPending, SkyComponent,
+};
+use crate::scene::World;
+use crate::window::WindowId;
+use anyhow::{bail, Context, Result};
+use glam::Vec4;
+use hecs::{Component, Entity, EntityBuilder, EntityRef, Or, Query};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Scene ids of the saved or loaded entities, for remapping entity references such as
+/// [`Parent`].
+#[derive(Default)]
+pub struct SceneEntities {
+    ids: HashMap<Entity, u32>,
+    entities: HashMap<u32, Entity>,
+}
+
+impl SceneEntities {
+    /// None if the entity isn't part of the scene.
+    pub fn id(&self, entity: Entity) -> Option<u32> {
+        self.ids.get(&entity).copied()
+    }
+
+    pub fn entity(&self, id: u32) -> Option<Entity> {
+        self.entities.get(&id).copied()
+    }
+
+    fn insert(&mut self, id: u32, entity: Entity) {
+        self.ids.insert(entity, id);
+        self.entities.insert(id, entity);
+    }
+}
+
+type HasFn = Box<dyn Fn(&EntityRef) -> bool>;
+type SaveFn = Box<dyn Fn(&EntityRef, &SceneEntities) -> Option<Result<Value>>>;
+type LoadFn = Box<dyn Fn(&mut EntityBuilder, Value, &SceneEntities) -> Result<()>>;
+
+struct SceneComponent {
+    name: String,
+    has: HasFn,
+    save: SaveFn,
+    load: LoadFn,
+}
+
+/// Components saved to and loaded from scenes, stored as a resource by `ScenePlugin`.
+///
+/// The built-in components are registered up front, others opt in by name:
+/// ```ignore
+/// #[derive(Serialize, Deserialize)]
+/// struct Health(f32);
+///
+/// app.register_scene_component::<Health>("Health");
+/// ```
+/// Components that reference entities or hold runtime state are saved in a plain data form
+/// through [`SceneRegistry::register_with`].
+pub struct SceneRegistry {
+    components: Vec<SceneComponent>,
+}
+
+impl Default for SceneRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SceneRegistry {
+    /// A registry with the built-in components.
+    pub fn new() -> Self {
+        let mut registry = Self {
+            components: Vec::new(),
+        };
+        registry.register_builtins();
+        registry
+    }
+
+    /// Save `T` as it is. Registering a name again replaces it.
+    pub fn register<T: Component + Serialize + DeserializeOwned>(
+        &mut self,
+        name: &str,
+    ) -> &mut Self {
+        self.add(
+            name,
+            Box::new(|entity| entity.has::<T>()),
+            Box::new(|entity, _| {
+                let component = entity.get::<&T>()?;
+                Some(to_value(&*component))
+            }),
+            Box::new(|builder, value, _| {
+                builder.add(serde_json::from_value::<T>(value)?);
+                Ok(())
+            }),
+        )
+    }
+
+    /// Save `T` as `S`. Entity references are mapped through the [`SceneEntities`], when
+    /// `to_scene` returns None the component is left out.
+    pub fn register_with<T: Component, S: Serialize + DeserializeOwned + 'static>(
+        &mut self,
+        name: &str,
+        to_scene: fn(&T, &SceneEntities) -> Option<S>,
+        from_scene: fn(S, &SceneEntities) -> Result<T>,
+    ) -> &mut Self {
+        self.add(
+            name,
+            Box::new(|entity| entity.has::<T>()),
+            Box::new(move |entity, entities| {
+                let component = entity.get::<&T>()?;
+                let scene = to_scene(&component, entities)?;
+                Some(to_value(&scene))
+            }),
+            Box::new(move |builder, value, entities| {
+                let scene = serde_json::from_value::<S>(value)?;
+                builder.add(from_scene(scene, entities)?);
+                Ok(())
+            }),
+        )
+    }
+
+    /// Save the path of an asset, pending as `P` or already loaded into an entity matching `L`.
+    fn register_asset<P: Component, L: Query + 'static>(
+        &mut self,
+        name: &str,
+        path: fn(&P) -> &Path,
+        pending: fn(PathBuf) -> P,
+    ) -> &mut Self {
+        self.add(
+            name,
+            Box::new(|entity| {
+                entity.has::<P>() || (entity.satisfies::<L>() && entity.has::<AssetSource>())
+            }),
+            Box::new(move |entity, _| {
+                let path = match entity.get::<&P>() {
+                    Some(p) => path(&p).to_path_buf(),
+                    None if entity.satisfies::<L>() => entity.get::<&AssetSource>()?.0.clone(),
+                    None => return None,
+                };
+                Some(to_value(&path))
+            }),
+            Box::new(move |builder, value, _| {
+                builder.add(pending(serde_json::from_value(value)?));
+                Ok(())
+            }),
+        )
+    }
+
+    fn add(&mut self, name: &str, has: HasFn, save: SaveFn, load: LoadFn) -> &mut Self {
+        self.components.retain(|c| c.name != name);
+        self.components.push(SceneComponent {
+            name: name.to_string(),
+            has,
+            save,
+            load,
+        });
+        self
+    }
+
+    fn register_builtins(&mut self) {
+        self.register_with::<Name, String>(
+            "Name",
+            |name, _| Some(name.0.clone()),
+            |name, _| Ok(Name(name)),
+        )
+        .register_with::<Parent, u32>(
+            "Parent",
+            |parent, entities| entities.id(parent.0),
+            |id, entities| {
+                let parent = entities
+                    .entity(id)
+                    .with_context(|| format!("Parent is unknown entity {}", id))?;
+                Ok(Parent(parent))
+            },
+        )
+        .register_with::<CTransform3d, _>(
+            "CTransform3d",
+            |transform, _| Some(**transform),
+            |transform, _| Ok(CTransform3d::new(transform)),
+        )
+        .register_with::<CTransform2d, _>(
+            "CTransform2d",
+            |transform, _| Some(**transform),
+            |transform, _| Ok(CTransform2d::new(transform)),
+        )
+        .register_with::<ActiveCamera, ()>("ActiveCamera", |_, _| Some(()), |_, _| Ok(ActiveCamera))
+        .register_with::<Camera3dComponent, Camera3dScene>(
+            "Camera3dComponent",
+            |camera, _| Some(Camera3dScene::from(camera)),
+            |scene, _| Ok(scene.into()),
+        )
+        .register_with::<Camera2dComponent, Camera2dScene>(
+            "Camera2dComponent",
+            |camera, _| {
+                Some(Camera2dScene {
+                    target: camera.target,
+                })
+            },
+            |scene, _| {
+                let mut camera = Camera2dComponent::default();
+                camera.target = scene.target;
+                Ok(camera)
+            },
+        )
+        .register::<Visibility>("Visibility")
+        .register::<RenderLayers>("RenderLayers")
+        .register::<RigidBody>("RigidBody")
+        .register::<Velocity>("Velocity")
+        .register::<Collider>("Collider")
+        .register::<PointLightComponent>("PointLightComponent")
+        .register::<DirectionalLightComponent>("DirectionalLightComponent")
+        // 节点层级的根实体没有 Model，节点实体在加载时按名字复用
+        .register_asset::<AssetPending, Or<&Model, &NodeHierarchy>>(
+            "AssetPending",
+            |p| p.0.as_path(),
+            AssetPending,
+        )
+        .register_with::<NodeHierarchy, ()>(
+            "NodeHierarchy",
+            |_, _| Some(()),
+            |_, _| Ok(NodeHierarchy),
+        )
+        .register_asset::<SkyAssetPending, &SkyComponent>(
+            "SkyAssetPending",
+            |p| p.0.as_path(),
+            SkyAssetPending,
+        )
+        .register_asset::<SpriteAssetPending, &SpriteComponent>(
+            "SpriteAssetPending",
+            |p| p.0.as_path(),
+            SpriteAssetPending,
+        )
+        .register_with::<SpriteComponent, SpriteScene>(
+            "SpriteComponent",
+            |sprite, _| Some(SpriteScene::from(sprite)),
+            |scene, _| Ok(scene.into()),
+        )
+        .register_with::<LabelComponent, LabelScene>(
+            "LabelComponent",
+            |label, _| Some(LabelScene::from(label)),
+            |scene, _| Ok(scene.into()),
+        )
+        .register_with::<AnimationPlayer, _>(
+            "AnimationPlayer",
+            |player, entities| Some(player.to_scene(entities)),
+            AnimationPlayer::from_scene,
+        );
+    }
+}
+
+/// Goes through text so that f32 fields keep their short form, `serde_json::to_value` widens
+/// them to f64 (0.1 becomes 0.10000000149011612).
+fn to_value<T: Serialize>(value: &T) -> Result<Value> {
+    Ok(serde_json::from_str(&serde_json::to_string(value)?)?)
+}
+
+/// Camera settings, the viewport and history are runtime state.
+#[derive(Serialize, Deserialize)]
+struct Camera3dScene {
+    fov: f32,
+    near: f32,
+    far: f32,
+    ssao_enabled: bool,
+    fxaa_enabled: bool,
+    taa_enabled: bool,
+    volumetric_enabled: bool,
+    ssr_enabled: bool,
+    ssgi_enabled: bool,
+    target: WindowId,
+}
+
+impl From<&Camera3dComponent> for Camera3dScene {
+    fn from(camera: &Camera3dComponent) -> Self {
+        Self {
+            fov: camera.fov,
+            near: camera.near,
+            far: camera.far,
+            ssao_enabled: camera.ssao_enabled,
+            fxaa_enabled: camera.fxaa_enabled,
+            taa_enabled: camera.taa_enabled,
+            volumetric_enabled: camera.volumetric_enabled,
+            ssr_enabled: camera.ssr_enabled,
+            ssgi_enabled: camera.ssgi_enabled,
+            target: camera.target,
+        }
+    }
+}
+
+impl From<Camera3dScene> for Camera3dComponent {
+    fn from(scene: Camera3dScene) -> Self {
+        Self {
+            fov: scene.fov,
+            near: scene.near,
+            far: scene.far,
+            ssao_enabled: scene.ssao_enabled,
+            fxaa_enabled: scene.fxaa_enabled,
+            taa_enabled: scene.taa_enabled,
+            volumetric_enabled: scene.volumetric_enabled,
+            ssr_enabled: scene.ssr_enabled,
+            ssgi_enabled: scene.ssgi_enabled,
+            target: scene.target,
+            ..Camera3dComponent::new()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Camera2dScene {
+    target: WindowId,
+}
+
+/// Sprite settings, the texture is saved as `SpriteAssetPending`.
+#[derive(Serialize, Deserialize)]
+struct SpriteScene {
+    use_original_size: bool,
+    region: Vec4,
+    centered: bool,
+    flip_x: bool,
+    flip_y: bool,
+    color: [f32; 4],
+}
+
+impl From<&SpriteComponent> for SpriteScene {
+    fn from(sprite: &SpriteComponent) -> Self {
+        Self {
+            use_original_size: sprite.use_original_size,
+            region: sprite.region,
+            centered: sprite.centered,
+            flip_x: sprite.flip_x,
+            flip_y: sprite.flip_y,
+            color: sprite.color,
+        }
+    }
+}
+
+impl From<SpriteScene> for SpriteComponent {
+    fn from(scene: SpriteScene) -> Self {
+        Self {
+            use_original_size: scene.use_original_size,
+            region: scene.region,
+            centered: scene.centered,
+            flip_x: scene.flip_x,
+            flip_y: scene.flip_y,
+            color: scene.color,
+            ..SpriteComponent::empty()
+        }
+    }
+}
+
+/// Label text and layout settings, the glyph atlas is rebuilt after loading.
+#[derive(Serialize, Deserialize)]
+struct LabelScene {
+    text: String,
+    font_id: Option<String>,
+    single_line: bool,
+    leading: f32,
+    tracking: f32,
+}
+
+impl From<&LabelComponent> for LabelScene {
+    fn from(label: &LabelComponent) -> Self {
+        Self {
+            text: label.text.clone(),
+            font_id: label.font_id.clone(),
+            single_line: label.single_line,
+            leading: label.leading,
+            tracking: label.tracking,
+        }
+    }
+}
+
+impl From<LabelScene> for LabelComponent {
+    fn from(scene: LabelScene) -> Self {
+        let mut label = LabelComponent::new(&scene.text);
+        label.font_id = scene.font_id;
+        label.single_line = scene.single_line;
+        label.leading = scene.leading;
+        label.tracking = scene.tracking;
+        label
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct SceneFile {
+    entities: Vec<SceneEntity>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SceneEntity {
+    id: u32,
+    /// Keyed by registered name.
+    components: Map<String, Value>,
+}
+
+impl World {
+    /// The registered components of all entities as pretty-printed JSON. Entities without any
+    /// registered component are left out.
+    pub fn save_scene_to_string(&self) -> Result<String> {
+        let registry = self
+            .resources
+            .get::<SceneRegistry>()
+            .context("No SceneRegistry resource, is ScenePlugin added?")?;
+
+        let mut saved: Vec<EntityRef> = self
+            .ecs
+            .iter()
+            .filter(|entity| registry.components.iter().any(|c| (c.has)(entity)))
+            .collect();
+        saved.sort_by_key(|entity| entity.entity().id());
+
+        let mut entities = SceneEntities::default();
+        for entity in &saved {
+            entities.insert(entity.entity().id(), entity.entity());
+        }
+
+        let mut scene = SceneFile::default();
+        for entity in &saved {
+            let mut components = Map::new();
+            for component in &registry.components {
+                if let Some(value) = (component.save)(entity, &entities) {
+                    let value = value.with_context(|| {
+                        format!("Failed to save {} of {:?}", component.name, entity.entity())
+                    })?;
+                    components.insert(component.name.clone(), value);
+                }
+            }
+
+            scene.entities.push(SceneEntity {
+                id: entity.entity().id(),
+                components,
+            });
+        }
+
+        Ok(serde_json::to_string_pretty(&scene)?)
+    }
+
+    pub fn save_scene(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.save_scene_to_string()?)
+            .with_context(|| format!("Failed to write scene {}", path.display()))?;
+        log::info!("Scene saved to {}", path.display());
+        Ok(())
+    }
+
+    /// Spawn the entities of a scene, returned in scene order. Entity references are remapped to
+    /// the spawned entities, unknown components are skipped with a warning. Nothing is spawned
+    /// if loading fails.
+    pub fn load_scene_from_str(&mut self, scene: &str) -> Result<Vec<Entity>> {
+        let scene: SceneFile = serde_json::from_str(scene).context("Invalid scene")?;
+        let registry = self
+            .resources
+            .get::<SceneRegistry>()
+            .context("No SceneRegistry resource, is ScenePlugin added?")?;
+
+        let mut ids = HashSet::with_capacity(scene.entities.len());
+        if let Some(duplicate) = scene.entities.iter().find(|e| !ids.insert(e.id)) {
+            bail!("Duplicate scene entity id {}", duplicate.id);
+        }
+
+        // 先为所有实体预留句柄，组件中的实体引用才能重映射
+        let mut entities = SceneEntities::default();
+        let mut spawned = Vec::with_capacity(scene.entities.len());
+        for scene_entity in &scene.entities {
+            let entity = self.ecs.reserve_entity();
+            entities.insert(scene_entity.id, entity);
+            spawned.push(entity);
+        }
+
+        let mut builders = Vec::with_capacity(scene.entities.len());
+        let built = scene.entities.into_iter().try_for_each(|scene_entity| {
+            let mut builder = EntityBuilder::new();
+            for (name, value) in scene_entity.components {
+                let Some(component) = registry.components.iter().find(|c| c.name == name) else {
+                    log::warn!("Unknown scene component {}, skipped", name);
+                    continue;
+                };
+                (component.load)(&mut builder, value, &entities).with_context(|| {
+                    format!("Failed to load {} of entity {}", name, scene_entity.id)
+                })?;
+            }
+
+            // GlobalTransform 由 propagate_transforms 计算
+            if (builder.has::<CTransform3d>() || builder.has::<CTransform2d>())
+                && !builder.has::<GlobalTransform>()
+            {
+                builder.add(GlobalTransform::default());
+            }
+
+            builders.push(builder);
+            Ok::<_, anyhow::Error>(())
+        });
+        drop(registry);
+
+        if let Err(err) = built {
+            for entity in spawned {
+                let _ = self.ecs.despawn(entity);
+            }
+            return Err(err);
+        }
+
+        for (entity, mut builder) in spawned.iter().zip(builders) {
+            self.ecs.insert(*entity, builder.build())?;
+        }
+
+        Ok(spawned)
+    }
+
+    pub fn load_scene(&mut self, path: impl AsRef<Path>) -> Result<Vec<Entity>> {
+        let path = path.as_ref();
+        let scene = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read scene {}", path.display()))?;
+        let spawned = self
+            .load_scene_from_str(&scene)
+            .with_context(|| format!("Failed to load scene {}", path.display()))?;
+        log::info!(
+            "Scene loaded from {}, {} entities",
+            path.display(),
+            spawned.len()
+        );
+        Ok(spawned)
+    }
+}
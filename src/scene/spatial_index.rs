@@ -0,0 +1,175 @@
+use crate::math::aabb::Aabb;
+use crate::math::bvh::Bvh;
+use crate::math::frustum::Frustum;
+use crate::math::ray::Ray;
+use crate::math::sphere::Sphere;
+use crate::physics::Collider;
+use crate::scene::components::{GlobalTransform, TransformChanged};
+use crate::scene::d3::Model;
+use glam::Vec3;
+use hecs::{Entity, World};
+use std::collections::{HashMap, HashSet};
+
+/// Refits in a row before the tree is rebuilt to restore query speed.
+const MAX_REFITS: u32 = 64;
+
+/// Local space bounds of an entity in the [`SpatialIndex`], used instead of `Model::aabb` and
+/// the collider's bounds. Other entities need it to be indexed.
+#[derive(Clone, Copy, Debug)]
+pub struct SpatialBounds(pub Aabb);
+
+struct IndexedEntity {
+    local: Aabb,
+    /// [`TransformChanged`] tick `aabb` was computed at.
+    tick: u64,
+    aabb: Aabb,
+}
+
+/// World space AABBs of the entities with a `GlobalTransform` and a `Model`, `Collider` or
+/// [`SpatialBounds`], for gameplay queries. Kept as a resource and updated after transform propagation each tick,
+/// so systems see the positions as of the end of the previous tick.
+#[derive(Default)]
+pub struct SpatialIndex {
+    entities: HashMap<Entity, IndexedEntity>,
+    bvh: Bvh<Entity>,
+    refits: u32,
+}
+
+impl SpatialIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only entities whose transform or bounds changed are recomputed.
+    pub fn update(&mut self, ecs: &World) {
+        let mut seen = HashSet::with_capacity(self.entities.len());
+        let mut moved = false;
+        let mut added = false;
+
+        for (id, global, changed, bounds, model, collider) in ecs
+            .query::<(
+                Entity,
+                &GlobalTransform,
+                Option<&TransformChanged>,
+                Option<&SpatialBounds>,
+                Option<&Model>,
+                Option<&Collider>,
+            )>()
+            .iter()
+        {
+            let local = match (bounds, model, collider) {
+                (Some(bounds), ..) => bounds.0,
+                (None, Some(model), _) => model.aabb,
+                (None, None, Some(collider)) => collider.local_aabb(),
+                (None, None, None) => continue,
+            };
+            // 从未移动过的实体没有 TransformChanged
+            let tick = changed.map_or(0, |changed| changed.0);
+            seen.insert(id);
+
+            match self.entities.get_mut(&id) {
+                Some(entity) if entity.tick == tick && entity.local == local => {}
+                Some(entity) => {
+                    entity.local = local;
+                    entity.tick = tick;
+                    entity.aabb = local.transform_by_matrix(&global.0);
+                    moved = true;
+                }
+                None => {
+                    self.entities.insert(
+                        id,
+                        IndexedEntity {
+                            local,
+                            tick,
+                            aabb: local.transform_by_matrix(&global.0),
+                        },
+                    );
+                    added = true;
+                }
+            }
+        }
+
+        let removed = seen.len() != self.entities.len();
+        if removed {
+            self.entities.retain(|id, _| seen.contains(id));
+        }
+
+        if added || removed || (moved && self.refits >= MAX_REFITS) {
+            self.bvh = Bvh::build(
+                self.entities
+                    .iter()
+                    .map(|(id, entity)| (entity.aabb, *id))
+                    .collect(),
+            );
+            self.refits = 0;
+        } else if moved {
+            let entities = &self.entities;
+            self.bvh.refit(|id| entities[id].aabb);
+            self.refits += 1;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// World space AABB of an indexed entity.
+    pub fn aabb(&self, entity: Entity) -> Option<Aabb> {
+        self.entities.get(&entity).map(|entity| entity.aabb)
+    }
+
+    /// Entities whose AABB is within `radius` of `center`.
+    pub fn query_radius(&self, center: Vec3, radius: f32) -> Vec<Entity> {
+        let sphere = Sphere::new(center, radius);
+        self.query_with(|aabb| sphere.intersects_aabb(aabb))
+    }
+
+    /// Entities whose AABB overlaps `aabb`.
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<Entity> {
+        self.query_with(|other| other.intersects_aabb(aabb))
+    }
+
+    /// Entities whose AABB is (at least partly) inside the frustum.
+    pub fn query_frustum(&self, frustum: &Frustum) -> Vec<Entity> {
+        self.query_with(|aabb| frustum.intersects_aabb(aabb))
+    }
+
+    /// Entities whose AABB is hit by the ray within `max_distance`, nearest first.
+    pub fn query_ray(&self, ray: &Ray, max_distance: f32) -> Vec<(Entity, f32)> {
+        let mut candidates = Vec::new();
+        self.bvh.query_ray(ray, &mut candidates);
+
+        let mut hits: Vec<(Entity, f32)> = candidates
+            .into_iter()
+            .filter_map(|id| Some((id, ray.intersect_aabb(&self.entities.get(&id)?.aabb)?)))
+            .filter(|(_, distance)| *distance <= max_distance)
+            .collect();
+        hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+        hits
+    }
+
+    /// The entity whose AABB is hit first by the ray within `max_distance`. For hits against
+    /// the triangles of models see `cast_ray`.
+    pub fn ray_cast(&self, ray: &Ray, max_distance: f32) -> Option<(Entity, f32)> {
+        self.bvh.ray_cast(ray, max_distance, |id| {
+            ray.intersect_aabb(&self.entities.get(id)?.aabb)
+        })
+    }
+
+    fn query_with(&self, overlaps: impl Fn(&Aabb) -> bool) -> Vec<Entity> {
+        let mut candidates = Vec::new();
+        self.bvh.query_with(&overlaps, &mut candidates);
+
+        // 叶节点可能包含多个实体，逐个检查
+        candidates.retain(|id| {
+            self.entities
+                .get(id)
+                .is_some_and(|entity| overlaps(&entity.aabb))
+        });
+        candidates
+    }
+}
@@ -1,17 +1,30 @@
+use crate::animation::AnimationFinished;
+use crate::core::EventWriter;
 use crate::scene::components::*;
 use hecs::World;
 
-pub fn update_animations(ecs: &mut World, dt: f32) {
-    use crate::animation::player::AnimationPlayer;
+pub fn update_animations(ecs: &mut World, finished: &mut EventWriter<AnimationFinished>, dt: f32) {
+    use crate::animation::player::{AnimationPlayer, PlayState};
     use crate::animation::property::PropertyValue;
     use glam::FloatExt;
 
     let mut all_changes = Vec::new();
 
     // 收集所有动画播放器的变更
-    for player in ecs.query_mut::<&mut AnimationPlayer>() {
+    for (entity, player) in ecs.query_mut::<(hecs::Entity, &mut AnimationPlayer)>() {
+        let was_playing = player.play_state() == PlayState::Playing;
         player.update(dt);
         all_changes.extend(player.take_changes());
+
+        // 非循环片段播放到结尾时会自动停止
+        if was_playing && player.play_state() == PlayState::Stopped {
+            if let Some(clip) = player.active_clip() {
+                finished.send(AnimationFinished {
+                    entity,
+                    clip: clip.to_string(),
+                });
+            }
+        }
     }
 
     // 应用变更
@@ -22,22 +35,22 @@ pub fn update_animations(ecs: &mut World, dt: f32) {
         if let Ok(mut transform) = ecs.get::<&mut CTransform3d>(change.target_entity) {
             match (path, &change.value) {
                 ("transform.position", PropertyValue::Vec3(v)) => {
-                    transform.0.position = transform.0.position.lerp(*v, change.weight)
+                    transform.position = transform.position.lerp(*v, change.weight)
                 }
                 ("transform.position.x", PropertyValue::Float(v)) => {
-                    transform.0.position.x = transform.0.position.x.lerp(*v, change.weight)
+                    transform.position.x = transform.position.x.lerp(*v, change.weight)
                 }
                 ("transform.position.y", PropertyValue::Float(v)) => {
-                    transform.0.position.y = transform.0.position.y.lerp(*v, change.weight)
+                    transform.position.y = transform.position.y.lerp(*v, change.weight)
                 }
                 ("transform.position.z", PropertyValue::Float(v)) => {
-                    transform.0.position.z = transform.0.position.z.lerp(*v, change.weight)
+                    transform.position.z = transform.position.z.lerp(*v, change.weight)
                 }
                 ("transform.rotation", PropertyValue::Quat(q)) => {
-                    transform.0.rotation = transform.0.rotation.slerp(*q, change.weight)
+                    transform.rotation = transform.rotation.slerp(*q, change.weight)
                 }
                 ("transform.scale", PropertyValue::Vec3(v)) => {
-                    transform.0.scale = transform.0.scale.lerp(*v, change.weight)
+                    transform.scale = transform.scale.lerp(*v, change.weight)
                 }
                 _ => {}
             }
@@ -47,19 +60,19 @@ pub fn update_animations(ecs: &mut World, dt: f32) {
         if let Ok(mut transform) = ecs.get::<&mut CTransform2d>(change.target_entity) {
             match (path, &change.value) {
                 ("transform.position", PropertyValue::Vec2(v)) => {
-                    transform.0.position = transform.0.position.lerp(*v, change.weight)
+                    transform.position = transform.position.lerp(*v, change.weight)
                 }
                 ("transform.position.x", PropertyValue::Float(v)) => {
-                    transform.0.position.x = transform.0.position.x.lerp(*v, change.weight)
+                    transform.position.x = transform.position.x.lerp(*v, change.weight)
                 }
                 ("transform.position.y", PropertyValue::Float(v)) => {
-                    transform.0.position.y = transform.0.position.y.lerp(*v, change.weight)
+                    transform.position.y = transform.position.y.lerp(*v, change.weight)
                 }
                 ("transform.rotation", PropertyValue::Float(v)) => {
-                    transform.0.rotation = transform.0.rotation.lerp(*v, change.weight)
+                    transform.rotation = transform.rotation.lerp(*v, change.weight)
                 }
                 ("transform.scale", PropertyValue::Vec2(v)) => {
-                    transform.0.scale = transform.0.scale.lerp(*v, change.weight)
+                    transform.scale = transform.scale.lerp(*v, change.weight)
                 }
                 _ => {}
             }
@@ -1,34 +1,56 @@
-use crate::core::singleton::Singletons;
+use crate::asset::AssetServer;
 use crate::render::render_world::RenderWorld;
+use crate::render::RenderContext;
 use crate::scene::components::*;
 use crate::scene::d2::sprite2d::{SpriteAssetPending, SpriteComponent};
-use crate::scene::d3::model::{AssetPending, Model};
+use crate::scene::d3::model::{AssetPending, Model, NodeHierarchy, RawNodeData};
 use crate::scene::d3::sky::{SkyAssetPending, SkyComponent};
-use hecs::World;
+use hecs::{Entity, Or, World};
+use std::collections::HashMap;
 
-pub fn update_assets(ecs: &mut World, singletons: &mut Singletons, render_world: &mut RenderWorld) {
+pub fn update_assets(
+    ecs: &mut World,
+    asset_server: &mut AssetServer,
+    render_context: &RenderContext,
+    render_world: &mut RenderWorld,
+) {
     // 1. 模型加载 (模型通常包含多个子资源，暂不实现路径级缓存，但使用 take 避免内存泄漏)
     let mut model_to_finalize = Vec::new();
     for (id, pending) in ecs.query_mut::<(hecs::Entity, &AssetPending)>() {
-        singletons.asset_server.request_load(&pending.0);
+        asset_server.request_load(&pending.0);
 
-        if let Some(raw) = singletons.asset_server.take_model(&pending.0) {
-            model_to_finalize.push((id, raw));
+        if let Some(raw) = asset_server.take_model(&pending.0) {
+            model_to_finalize.push((id, pending.0.clone(), raw));
         }
     }
 
-    for (id, raw) in model_to_finalize {
+    for (id, path, raw) in model_to_finalize {
+        if ecs.satisfies::<&NodeHierarchy>(id) && !raw.nodes.is_empty() {
+            let nodes = Model::finalize_nodes(
+                raw,
+                render_context,
+                &mut render_world.imported_texture_cache.write().unwrap(),
+                &mut render_world.imported_material_cache.write().unwrap(),
+                &mut render_world.imported_mesh_cache.write().unwrap(),
+                &mut render_world.imported_mesh_allocator.write().unwrap(),
+            );
+            spawn_nodes(ecs, id, nodes);
+            let _ = ecs.remove_one::<AssetPending>(id);
+            let _ = ecs.insert_one(id, AssetSource(path));
+            continue;
+        }
+
         let mut model = Model::empty();
         model.finalize(
             raw,
-            &singletons.render_context,
+            render_context,
             &mut render_world.imported_texture_cache.write().unwrap(),
             &mut render_world.imported_material_cache.write().unwrap(),
             &mut render_world.imported_mesh_cache.write().unwrap(),
             &mut render_world.imported_mesh_allocator.write().unwrap(),
         );
         let _ = ecs.remove_one::<AssetPending>(id);
-        let _ = ecs.insert_one(id, model);
+        let _ = ecs.insert(id, (model, AssetSource(path)));
     }
 
     // 2. 天空盒加载
@@ -41,30 +63,30 @@ pub fn update_assets(ecs: &mut World, singletons: &mut Singletons, render_world:
             .unwrap()
             .get_by_path(&pending.0)
         {
-            sky_to_finalize.push((id, Some(texture_id), None));
+            sky_to_finalize.push((id, pending.0.clone(), Some(texture_id), None));
             continue;
         }
 
-        singletons.asset_server.request_texture(&pending.0);
-        if let Some(raw) = singletons.asset_server.take_texture(&pending.0) {
-            sky_to_finalize.push((id, None, Some((pending.0.clone(), raw))));
+        asset_server.request_texture(&pending.0);
+        if let Some(raw) = asset_server.take_texture(&pending.0) {
+            sky_to_finalize.push((id, pending.0.clone(), None, Some(raw)));
         }
     }
 
-    for (id, texture_id, raw_data) in sky_to_finalize {
+    for (id, path, texture_id, raw_data) in sky_to_finalize {
         let mut sky = SkyComponent::empty();
         if let Some(tid) = texture_id {
             sky.finalize_with_id(tid);
-        } else if let Some((path, raw)) = raw_data {
+        } else if let Some(raw) = raw_data {
             sky.finalize(
                 raw,
-                &singletons.render_context,
+                render_context,
                 &mut render_world.imported_texture_cache.write().unwrap(),
-                Some(path),
+                Some(path.clone()),
             );
         }
         let _ = ecs.remove_one::<SkyAssetPending>(id);
-        let _ = ecs.insert_one(id, sky);
+        let _ = ecs.insert(id, (sky, AssetSource(path)));
     }
 
     // 3. Sprite 加载
@@ -77,26 +99,26 @@ pub fn update_assets(ecs: &mut World, singletons: &mut Singletons, render_world:
             .unwrap()
             .get_by_path(&pending.0)
         {
-            sprite_to_finalize.push((id, Some(texture_id), None));
+            sprite_to_finalize.push((id, pending.0.clone(), Some(texture_id), None));
             continue;
         }
 
-        singletons.asset_server.request_texture(&pending.0);
-        if let Some(raw) = singletons.asset_server.take_texture(&pending.0) {
-            sprite_to_finalize.push((id, None, Some((pending.0.clone(), raw))));
+        asset_server.request_texture(&pending.0);
+        if let Some(raw) = asset_server.take_texture(&pending.0) {
+            sprite_to_finalize.push((id, pending.0.clone(), None, Some(raw)));
         }
     }
 
-    for (id, texture_id, raw_data) in sprite_to_finalize {
+    for (id, path, texture_id, raw_data) in sprite_to_finalize {
         if let Ok(mut sprite) = ecs.remove_one::<SpriteComponent>(id) {
             let size = if let Some(tid) = texture_id {
                 sprite.finalize_with_id(tid, &render_world.imported_texture_cache.read().unwrap())
-            } else if let Some((path, raw)) = raw_data {
+            } else if let Some(raw) = raw_data {
                 sprite.finalize(
                     raw,
-                    &singletons.render_context,
+                    render_context,
                     &mut render_world.imported_texture_cache.write().unwrap(),
-                    Some(path),
+                    Some(path.clone()),
                 )
             } else {
                 unreachable!()
@@ -104,7 +126,84 @@ pub fn update_assets(ecs: &mut World, singletons: &mut Singletons, render_world:
 
             let _ = ecs.insert_one(id, sprite);
             let _ = ecs.remove_one::<SpriteAssetPending>(id);
-            let _ = ecs.insert_one(id, Size(size));
+            let _ = ecs.insert(id, (Size(size), AssetSource(path)));
+        }
+    }
+}
+
+/// Request all loaded models, skies and sprites from their [`AssetSource`] again, e.g. after
+/// their GPU data was lost together with the device.
+pub(crate) fn reload_assets(ecs: &mut World) {
+    // 节点层级的节点实体没有 AssetSource，其模型随根实体重新加载
+    let roots: Vec<_> = ecs
+        .query_mut::<(Entity, &AssetSource)>()
+        .with::<Or<&Model, &NodeHierarchy>>()
+        .into_iter()
+        .map(|(id, source)| (id, source.0.clone()))
+        .collect();
+    let models: Vec<_> = ecs
+        .query_mut::<Entity>()
+        .with::<&Model>()
+        .into_iter()
+        .collect();
+    for id in models {
+        let _ = ecs.remove_one::<Model>(id);
+    }
+    for (id, path) in roots {
+        let _ = ecs.insert_one(id, AssetPending(path));
+    }
+
+    let skies: Vec<_> = ecs
+        .query_mut::<(Entity, &AssetSource)>()
+        .with::<&SkyComponent>()
+        .into_iter()
+        .map(|(id, source)| (id, source.0.clone()))
+        .collect();
+    for (id, path) in skies {
+        let _ = ecs.remove_one::<SkyComponent>(id);
+        let _ = ecs.insert_one(id, SkyAssetPending(path));
+    }
+
+    let mut sprites = Vec::new();
+    for (id, sprite, source) in ecs.query_mut::<(Entity, &mut SpriteComponent, &AssetSource)>() {
+        // 纹理已失效，重新加载前不绘制
+        sprite.texture = None;
+        sprites.push((id, source.0.clone()));
+    }
+    for (id, path) in sprites {
+        let _ = ecs.insert_one(id, SpriteAssetPending(path));
+    }
+}
+
+/// Spawn the nodes of a glTF model under `root`. Nodes that already exist under their parent
+/// with the same name, e.g. loaded from a scene, are reused and keep their transform.
+fn spawn_nodes(ecs: &mut World, root: Entity, nodes: Vec<(RawNodeData, Model)>) {
+    let mut existing: HashMap<(Entity, String), Vec<Entity>> = HashMap::new();
+    for (entity, parent, name) in ecs.query_mut::<(Entity, &Parent, &Name)>() {
+        existing
+            .entry((parent.0, name.0.clone()))
+            .or_default()
+            .push(entity);
+    }
+
+    let mut entities: Vec<Entity> = Vec::with_capacity(nodes.len());
+    for (node, model) in nodes {
+        // 父节点总在子节点之前
+        let parent = node.parent.map_or(root, |parent| entities[parent]);
+        let reused = existing
+            .get_mut(&(parent, node.name.clone()))
+            .and_then(|entities| entities.pop());
+        let entity = reused.unwrap_or_else(|| {
+            ecs.spawn((
+                Name(node.name),
+                CTransform3d::new(node.transform),
+                GlobalTransform::default(),
+                Parent(parent),
+            ))
+        });
+        if !model.meshes.is_empty() {
+            let _ = ecs.insert_one(entity, model);
         }
+        entities.push(entity);
     }
 }
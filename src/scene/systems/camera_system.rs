@@ -1,27 +1,30 @@
-use crate::core::singleton::Singletons;
 use crate::scene::components::*;
 use crate::scene::{Camera2dComponent, Camera3dComponent};
-use glam::UVec2;
+use crate::window::Windows;
 use hecs::World;
 
-pub fn update_cameras(ecs: &mut World, singletons: &Singletons) {
-    let width = singletons.render_context.surface_config.width as f32;
-    let height = singletons.render_context.surface_config.height as f32;
-
-    if width <= 0.0 || height <= 0.0 {
-        return;
-    }
+pub fn update_cameras(ecs: &mut World, windows: &Windows) {
+    // 目标窗口的物理尺寸，窗口不存在或最小化时返回 None
+    let target_size = |target| {
+        let size = windows.get(target)?.size;
+        (size.x > 0 && size.y > 0).then_some(size)
+    };
 
     // 同步 2D 摄像机投影
     for (_id, camera) in ecs.query_mut::<(hecs::Entity, &mut Camera2dComponent)>() {
-        camera.viewport_size = UVec2::new(width as u32, height as u32);
+        if let Some(size) = target_size(camera.target) {
+            camera.viewport_size = size;
+        }
     }
 
     // 同步 3D 摄像机视口
     for (_id, camera, _global) in
         ecs.query_mut::<(hecs::Entity, &mut Camera3dComponent, &GlobalTransform)>()
     {
-        camera.viewport_size = UVec2::new(width as u32, height as u32);
+        let Some(size) = target_size(camera.target) else {
+            continue;
+        };
+        camera.viewport_size = size;
         camera.frame_count = camera.frame_count.wrapping_add(1);
     }
 }
@@ -10,7 +10,14 @@ pub fn handle_input(ecs: &mut World, input_server: &mut InputServer) {
         if event.consumed {
             continue;
         }
-        for controller in ecs.query_mut::<&mut crate::scene::d3::camera3d::Camera3dController>() {
+        for (controller, camera) in ecs.query_mut::<(
+            &mut crate::scene::d3::camera3d::Camera3dController,
+            Option<&crate::scene::d3::camera3d::Camera3dComponent>,
+        )>() {
+            // 只响应摄像机所在窗口的输入
+            if camera.is_some_and(|camera| camera.target != event.window) {
+                continue;
+            }
             controller.handle_input(event, input_server);
         }
     }
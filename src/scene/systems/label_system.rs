@@ -1,17 +1,35 @@
-use crate::core::singleton::Singletons;
+use crate::asset::AssetServer;
 use crate::scene::components::*;
+use crate::text::FontServer;
 use hecs::World;
 
-pub fn update_labels(ecs: &mut World, singletons: &mut Singletons) {
+pub fn update_labels(
+    ecs: &mut World,
+    asset_server: &mut AssetServer,
+    font_server: &mut FontServer,
+) {
     use crate::math::transform::Transform2d;
     use crate::scene::d2::label::LabelComponent;
 
-    for (label, global) in ecs.query_mut::<(&mut LabelComponent, &GlobalTransform)>() {
+    for (label, global, changed) in ecs.query_mut::<(
+        &mut LabelComponent,
+        &GlobalTransform,
+        Option<&TransformChanged>,
+    )>() {
         // 确保字体被请求
         if let Some(font_id) = &label.font_id {
-            singletons.asset_server.request_font(font_id);
+            asset_server.request_font(font_id);
         }
 
+        let needs_layout =
+            label.text_is_dirty || label.atlas.as_ref().is_none_or(|a| a.texture.is_none());
+        let changed_tick = changed.map_or(0, |c| c.0);
+        let moved = changed_tick > label.transform_tick;
+        if !needs_layout && !moved {
+            continue;
+        }
+        label.transform_tick = changed_tick;
+
         let (_, rotation, translation) = global.0.to_scale_rotation_translation();
         let rotation_z = rotation.to_euler(glam::EulerRot::XYZ).2;
         let current_global_transform = Transform2d {
@@ -20,18 +38,16 @@ pub fn update_labels(ecs: &mut World, singletons: &mut Singletons) {
             scale: glam::Vec2::ONE,
         };
 
-        let transform_changed = (current_global_transform.position
-            - label.last_global_transform.position)
-            .length_squared()
-            > 0.0001
-            || (current_global_transform.rotation - label.last_global_transform.rotation).abs()
-                > 0.0001;
+        let transform_changed = moved
+            && ((current_global_transform.position - label.last_global_transform.position)
+                .length_squared()
+                > 0.0001
+                || (current_global_transform.rotation - label.last_global_transform.rotation)
+                    .abs()
+                    > 0.0001);
 
-        if label.text_is_dirty
-            || label.atlas.as_ref().map_or(true, |a| a.texture.is_none())
-            || transform_changed
-        {
-            let atlas = singletons.font_server.get_atlas(
+        if needs_layout || transform_changed {
+            let atlas = font_server.get_atlas(
                 label.text.as_str(),
                 label.font_id.clone(),
                 current_global_transform,
@@ -5,6 +5,7 @@ pub mod example_system;
 pub mod input_system;
 pub mod label_system;
 pub mod render_extract_system;
+pub mod timer_system;
 pub mod transform_system;
 
 pub use animation_system::*;
@@ -14,4 +15,5 @@ pub use example_system::*;
 pub use input_system::*;
 pub use label_system::*;
 pub use render_extract_system::*;
+pub use timer_system::*;
 pub use transform_system::*;
@@ -1,10 +1,40 @@
+use crate::math::transform::Transform3d;
 use crate::render::render_world::Extracted;
+use crate::render::ExtractedMesh;
 use crate::scene::components::*;
-use crate::scene::{ActiveCamera, Camera3dComponent, PointLightComponent};
-use glam::Vec3;
-use hecs::World;
+use crate::scene::hierarchy::is_visible;
+use crate::scene::{ActiveCamera, Camera3dComponent, Model, PointLightComponent};
+use glam::{Mat4, Vec3};
+use hecs::{Entity, World};
+use std::collections::HashSet;
 
-pub fn extract_render_objects(ecs: &mut World) -> Extracted {
+/// Extracted meshes of a still model as of its last [`TransformChanged`] tick, copied as is
+/// until the model moves again.
+pub(crate) struct ExtractedModel {
+    tick: u64,
+    layers: u32,
+    meshes: Vec<ExtractedMesh>,
+}
+
+impl ExtractedModel {
+    /// Whether the cache was built for this tick and layers, and for the same meshes.
+    fn matches(&self, tick: u64, layers: u32, model: &Model) -> bool {
+        self.tick == tick
+            && self.layers == layers
+            && self.meshes.len() == model.meshes.len()
+            && self
+                .meshes
+                .iter()
+                .zip(model.meshes.iter().zip(&model.materials))
+                .all(|(mesh, (mesh_id, material_id))| {
+                    mesh.mesh_id == *mesh_id && mesh.material_id == *material_id
+                })
+    }
+}
+
+/// `alpha` interpolates meshes, sprites and cameras between their previous (0) and current (1)
+/// tick transforms. Hidden meshes, sprites and labels (see [`Visibility`]) are skipped.
+pub fn extract_render_objects(ecs: &mut World, alpha: f32) -> Extracted {
     let mut extracted = Extracted::default();
 
     // 提取渲染设置 (从摄像机组件获取)
@@ -17,7 +47,7 @@ pub fn extract_render_objects(ecs: &mut World) -> Extracted {
     }
 
     // 1. 提取摄像机
-    extract_cameras(ecs, &mut extracted);
+    extract_cameras(ecs, &mut extracted, alpha);
 
     // 提取天空盒
     for sky in ecs.query::<&crate::scene::d3::SkyComponent>().iter() {
@@ -66,19 +96,27 @@ pub fn extract_render_objects(ecs: &mut World) -> Extracted {
     }
 
     // 提取 2D Sprite
-    for (sprite, global, size) in ecs
+    for (id, sprite, global, previous, size, layers) in ecs
         .query::<(
+            Entity,
             &crate::scene::d2::sprite2d::SpriteComponent,
             &GlobalTransform,
+            Option<&PreviousGlobalTransform>,
             &Size,
+            Option<&RenderLayers>,
         )>()
         .iter()
     {
+        if !is_visible(ecs, id) {
+            continue;
+        }
+
         if let Some(texture_id) = sprite.texture {
             use crate::math::transform::Transform2d;
             use crate::render::sprite::ExtractedSprite2d;
 
-            let (scale, rotation, translation) = global.0.to_scale_rotation_translation();
+            let (scale, rotation, translation) =
+                interpolate(global, previous, alpha).to_scale_rotation_translation();
             let rotation_z = rotation.to_euler(glam::EulerRot::XYZ).2;
 
             extracted.sprites.push(ExtractedSprite2d {
@@ -95,15 +133,24 @@ pub fn extract_render_objects(ecs: &mut World) -> Extracted {
                 flip_x: sprite.flip_x,
                 flip_y: sprite.flip_y,
                 mode: 0,
+                layers: layers.copied().unwrap_or_default().0,
             });
         }
     }
 
     // 提取 Label
-    for label in ecs
-        .query::<&crate::scene::d2::label::LabelComponent>()
+    for (id, label, layers) in ecs
+        .query::<(
+            Entity,
+            &crate::scene::d2::label::LabelComponent,
+            Option<&RenderLayers>,
+        )>()
         .iter()
     {
+        if !is_visible(ecs, id) {
+            continue;
+        }
+
         if let Some(atlas) = &label.atlas {
             if let Some(texture_id) = atlas.texture {
                 use crate::math::transform::Transform2d;
@@ -127,6 +174,7 @@ pub fn extract_render_objects(ecs: &mut World) -> Extracted {
                         flip_x: false,
                         flip_y: false,
                         mode: 1,
+                        layers: layers.copied().unwrap_or_default().0,
                     });
                 }
             }
@@ -134,56 +182,152 @@ pub fn extract_render_objects(ecs: &mut World) -> Extracted {
     }
 
     // 3. 提取 3D 模型
-    for (model, global) in ecs
-        .query::<(&crate::scene::d3::Model, &GlobalTransform)>()
+    let hidden: HashSet<Entity> = ecs
+        .query::<Entity>()
+        .with::<&crate::scene::d3::Model>()
         .iter()
-    {
-        use crate::math::transform::Transform3d;
-        use crate::render::ExtractedMesh;
-
-        let (scale, rotation, translation) = global.0.to_scale_rotation_translation();
-        let global_transform = Transform3d {
-            position: translation,
-            rotation,
-            scale,
-        };
-
-        for i in 0..model.meshes.len() {
-            let local_mesh_transform = model.mesh_transforms[i];
-            let combined = global_transform.combine(&local_mesh_transform);
-
-            extracted.meshes.push(ExtractedMesh {
-                transform: combined,
-                mesh_id: model.meshes[i],
-                material_id: model.materials[i],
-            });
+        .filter(|id| !is_visible(ecs, *id))
+        .collect();
+
+    let mut new_models = Vec::new();
+    for (id, model, global, previous, changed, cached, layers) in ecs.query_mut::<(
+        Entity,
+        &Model,
+        &GlobalTransform,
+        Option<&PreviousGlobalTransform>,
+        Option<&TransformChanged>,
+        Option<&mut ExtractedModel>,
+        Option<&RenderLayers>,
+    )>() {
+        if hidden.contains(&id) {
+            continue;
         }
+
+        // 静止 (无需插值) 的模型复用变化后第一次提取的网格
+        let layers = layers.copied().unwrap_or_default().0;
+        let still = previous.is_none_or(|previous| previous.0 == global.0);
+        match (still, changed, cached) {
+            (true, Some(changed), Some(cached)) if cached.matches(changed.0, layers, model) => {
+                extracted.meshes.extend_from_slice(&cached.meshes);
+            }
+            (true, Some(changed), cached) => {
+                let meshes: Vec<ExtractedMesh> =
+                    extract_meshes(model, decompose(global.0), layers).collect();
+                extracted.meshes.extend_from_slice(&meshes);
+
+                let model = ExtractedModel {
+                    tick: changed.0,
+                    layers,
+                    meshes,
+                };
+                match cached {
+                    Some(cached) => *cached = model,
+                    None => new_models.push((id, model)),
+                }
+            }
+            _ => {
+                let transform = decompose(interpolate(global, previous, alpha));
+                extracted
+                    .meshes
+                    .extend(extract_meshes(model, transform, layers));
+            }
+        }
+    }
+
+    for (id, model) in new_models {
+        let _ = ecs.insert_one(id, model);
     }
 
     extracted
 }
 
-fn extract_cameras(ecs: &mut World, extracted: &mut Extracted) {
+fn extract_meshes(
+    model: &Model,
+    global_transform: Transform3d,
+    layers: u32,
+) -> impl Iterator<Item = ExtractedMesh> + '_ {
+    model
+        .meshes
+        .iter()
+        .zip(&model.materials)
+        .zip(&model.mesh_transforms)
+        .map(
+            move |((mesh_id, material_id), local_mesh_transform)| ExtractedMesh {
+                transform: global_transform.combine(local_mesh_transform),
+                mesh_id: *mesh_id,
+                material_id: *material_id,
+                layers,
+            },
+        )
+}
+
+fn decompose(matrix: Mat4) -> Transform3d {
+    let (scale, rotation, position) = matrix.to_scale_rotation_translation();
+    Transform3d {
+        position,
+        rotation,
+        scale,
+    }
+}
+
+fn extract_cameras(ecs: &mut World, extracted: &mut Extracted, alpha: f32) {
     use crate::render::camera::CameraType;
 
     // 提取 3D 摄像机
-    for (camera, global, _) in
-        ecs.query_mut::<(&mut Camera3dComponent, &GlobalTransform, &ActiveCamera)>()
-    {
-        let uniform = camera.build_uniform(&global.0);
-        extracted.cameras.add(CameraType::D3, uniform);
+    for (camera, global, previous, layers, _) in ecs.query_mut::<(
+        &mut Camera3dComponent,
+        &GlobalTransform,
+        Option<&PreviousGlobalTransform>,
+        Option<&RenderLayers>,
+        &ActiveCamera,
+    )>() {
+        let transform = interpolate(global, previous, alpha);
+        let uniform = camera.build_uniform(&transform);
+        let layers = layers.copied().unwrap_or_default().0;
+        extracted
+            .cameras
+            .add(CameraType::D3, uniform, camera.target, layers);
 
         // 提取完成后，更新组件内的历史矩阵，供下一帧 build_uniform 使用
-        camera.update_after_extract(&global.0);
+        camera.update_after_extract(&transform);
     }
 
     // 提取 2D 摄像机
-    for (camera, global, _) in ecs.query_mut::<(
+    for (camera, global, previous, layers, _) in ecs.query_mut::<(
         &mut crate::scene::d2::Camera2dComponent,
         &GlobalTransform,
+        Option<&PreviousGlobalTransform>,
+        Option<&RenderLayers>,
         &ActiveCamera,
     )>() {
-        let uniform = camera.build_uniform(&global.0);
-        extracted.cameras.add(CameraType::D2, uniform);
+        let uniform = camera.build_uniform(&interpolate(global, previous, alpha));
+        let layers = layers.copied().unwrap_or_default().0;
+        extracted
+            .cameras
+            .add(CameraType::D2, uniform, camera.target, layers);
+    }
+}
+
+/// Blend between the previous and current tick's global transform.
+fn interpolate(
+    global: &GlobalTransform,
+    previous: Option<&PreviousGlobalTransform>,
+    alpha: f32,
+) -> Mat4 {
+    let Some(previous) = previous else {
+        return global.0;
+    };
+
+    if alpha >= 1.0 || previous.0 == global.0 {
+        return global.0;
     }
+
+    let (prev_scale, prev_rotation, prev_translation) = previous.0.to_scale_rotation_translation();
+    let (scale, rotation, translation) = global.0.to_scale_rotation_translation();
+
+    Mat4::from_scale_rotation_translation(
+        prev_scale.lerp(scale, alpha),
+        prev_rotation.slerp(rotation, alpha),
+        prev_translation.lerp(translation, alpha),
+    )
 }
@@ -0,0 +1,20 @@
+use crate::core::EventWriter;
+use crate::scene::components::*;
+use hecs::{Entity, World};
+
+pub fn update_timers(ecs: &mut World, finished: &mut EventWriter<TimerFinished>, dt: f32) {
+    for (entity, timer) in ecs.query_mut::<(Entity, &mut Timer)>() {
+        timer.tick(dt);
+
+        if timer.just_finished() {
+            finished.send(TimerFinished {
+                entity,
+                times: timer.times_finished_this_tick(),
+            });
+        }
+    }
+
+    for stopwatch in ecs.query_mut::<&mut Stopwatch>() {
+        stopwatch.tick(dt);
+    }
+}
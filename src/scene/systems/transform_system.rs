@@ -1,51 +1,105 @@
 use crate::scene::components::*;
+use crate::scene::hierarchy::{local_matrix, sync_children, world_matrix};
+use glam::Mat4;
 use hecs::{Entity, World};
+use std::collections::{HashSet, VecDeque};
 
-pub fn propagate_transforms(ecs: &mut World) {
-    // 1. 先更新所有 3D 根节点 (没有 Parent 的)
-    for (local, global) in ecs
-        .query_mut::<(&CTransform3d, &mut GlobalTransform)>()
-        .without::<&Parent>()
-    {
-        global.0 = local.0.matrix();
+/// Update `GlobalTransform` of entities whose local transform was mutably accessed or whose parent
+/// changed, and of their descendants. Other entities are not visited beyond checking their change
+/// flag. Changed entities get [`TransformChanged`] set to `tick`.
+pub fn propagate_transforms(ecs: &mut World, tick: u64) {
+    // 0. 记录上一个 tick 的全局变换，用于渲染插值
+    for (global, previous) in ecs.query_mut::<(&GlobalTransform, &mut PreviousGlobalTransform)>() {
+        previous.0 = global.0;
     }
 
-    // 2. 先更新所有 2D 根节点
-    for (local, global) in ecs
-        .query_mut::<(&CTransform2d, &mut GlobalTransform)>()
-        .without::<&Parent>()
-    {
-        global.0 = local.0.to_mat4();
+    // 1. 根据 Parent 的变化更新 Children，父节点变化的实体需要重新计算
+    let mut dirty = sync_children(ecs);
+
+    // 2. 找出局部变换被修改过的实体
+    for (id, t) in ecs.query_mut::<(Entity, &mut CTransform3d)>() {
+        if t.take_changed() {
+            dirty.push(id);
+        }
+    }
+    for (id, t) in ecs.query_mut::<(Entity, &mut CTransform2d)>() {
+        if t.take_changed() {
+            dirty.push(id);
+        }
     }
 
-    // 3. 处理子节点
-    // 为了避免借用冲突，我们先收集所有带有 Parent 组件的实体 ID
-    let child_entities: Vec<(Entity, Entity)> = ecs
-        .query::<(hecs::Entity, &Parent)>()
-        .iter()
-        .map(|(id, p)| (id, p.0))
-        .collect();
+    // 3. 祖先先于后代更新，随祖先更新过的子树不再重复
+    dirty.sort_by_cached_key(|id| depth(ecs, *id));
 
-    for (child_id, parent_id) in child_entities {
-        // 获取父节点的全局矩阵
-        let parent_mat = if let Ok(parent_global) = ecs.get::<&GlobalTransform>(parent_id) {
-            parent_global.0
-        } else {
+    let mut updated = HashSet::new();
+    for root in dirty {
+        if updated.contains(&root) {
             continue;
-        };
+        }
 
-        // 获取子节点的局部矩阵
-        let local_mat = if let Ok(t) = ecs.get::<&CTransform3d>(child_id) {
-            t.0.matrix()
-        } else if let Ok(t2d) = ecs.get::<&CTransform2d>(child_id) {
-            t2d.0.to_mat4()
-        } else {
-            continue;
+        let parent_mat = match ecs.get::<&Parent>(root).map(|p| p.0) {
+            Ok(parent) if ecs.contains(parent) => match ecs.get::<&GlobalTransform>(parent) {
+                Ok(global) => global.0,
+                Err(_) => world_matrix(ecs, parent),
+            },
+            _ => Mat4::IDENTITY,
         };
 
-        // 更新子节点的全局矩阵
-        if let Ok(mut global) = ecs.get::<&mut GlobalTransform>(child_id) {
-            global.0 = parent_mat * local_mat;
+        let mut queue = VecDeque::from([(root, parent_mat)]);
+        while let Some((id, parent_mat)) = queue.pop_front() {
+            if !updated.insert(id) {
+                continue;
+            }
+
+            // 没有局部变换的实体保留自身的 GlobalTransform
+            let global_mat = match local_matrix(ecs, id) {
+                Some(local_mat) => {
+                    let global_mat = parent_mat * local_mat;
+                    set_global(ecs, id, global_mat, tick);
+                    global_mat
+                }
+                None => ecs
+                    .get::<&GlobalTransform>(id)
+                    .map_or(parent_mat, |global| global.0),
+            };
+
+            if let Ok(children) = ecs.get::<&Children>(id) {
+                queue.extend(children.0.iter().map(|child| (*child, global_mat)));
+            }
+        }
+    }
+
+    // 4. 新实体没有历史变换，使用当前值（首帧不插值）
+    let new_entities: Vec<(Entity, Mat4)> = ecs
+        .query::<(hecs::Entity, &GlobalTransform)>()
+        .without::<&PreviousGlobalTransform>()
+        .iter()
+        .map(|(id, global)| (id, global.0))
+        .collect();
+
+    for (id, global) in new_entities {
+        let _ = ecs.insert_one(id, PreviousGlobalTransform(global));
+    }
+}
+
+fn set_global(ecs: &mut World, id: Entity, global_mat: Mat4, tick: u64) {
+    match ecs.get::<&mut GlobalTransform>(id) {
+        Ok(mut global) if global.0 != global_mat => global.0 = global_mat,
+        _ => return,
+    }
+    let _ = ecs.insert_one(id, TransformChanged(tick));
+}
+
+fn depth(ecs: &World, id: Entity) -> usize {
+    let mut depth = 0;
+    let mut current = id;
+    while let Ok(parent) = ecs.get::<&Parent>(current).map(|p| p.0) {
+        depth += 1;
+        // 防止环状引用导致死循环
+        if depth > ecs.len() as usize {
+            break;
         }
+        current = parent;
     }
+    depth
 }
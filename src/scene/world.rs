@@ -1,54 +1,50 @@
-use crate::core::singleton::Singletons;
-use crate::render::render_world::Extracted;
-use crate::window::InputServer;
+use crate::render::render_world::{Extracted, RenderWorld};
+use crate::scene::resources::Resources;
+use crate::scene::schedule::{Schedule, Stage};
 use hecs::World as EcsWorld;
 
+/// Logic ticks run so far, stored as a resource and advanced by [`World::update`]. Change ticks
+/// such as [`TransformChanged`](crate::scene::TransformChanged) hold values of it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ChangeTick(pub u64);
+
 pub struct World {
     pub ecs: EcsWorld,
+    pub schedule: Schedule,
+    /// Global state shared by systems, see [`Resources`].
+    pub resources: Resources,
 }
 
 impl World {
     pub fn new() -> Self {
+        let mut resources = Resources::new();
+        resources.insert(ChangeTick::default());
+
         Self {
             ecs: EcsWorld::new(),
+            schedule: Schedule::new(),
+            resources,
         }
     }
 
-    /// 核心更新逻辑：它是“系统”的集合
-    pub fn update(
-        &mut self,
-        dt: f32,
-        singletons: &mut Singletons,
-        render_world: &mut crate::render::render_world::RenderWorld,
-    ) {
-        // 0. 先更新资产服务器，从后台线程接收已加载的资产
-        singletons.asset_server.update();
-
-        // 1. 资产加载系统
-        crate::scene::systems::update_assets(&mut self.ecs, singletons, render_world);
-
-        // 2. 摄像机同步系统
-        crate::scene::systems::update_cameras(&mut self.ecs, singletons);
-
-        // 3. 动画系统
-        crate::scene::systems::update_animations(&mut self.ecs, dt);
+    /// 核心更新逻辑：依次执行各阶段的系统
+    pub fn update(&mut self, dt: f32, render_world: &mut RenderWorld) {
+        self.resources.resource_mut::<ChangeTick>().0 += 1;
 
-        // 4. 示例中的自定义逻辑系统
-        crate::scene::systems::update_example_logic(&mut self.ecs, dt);
-
-        // 5. 变换传播系统
-        crate::scene::systems::propagate_transforms(&mut self.ecs);
-
-        // 6. Label 系统
-        crate::scene::systems::update_labels(&mut self.ecs, singletons);
+        for stage in [Stage::PreUpdate, Stage::Update, Stage::PostUpdate] {
+            self.run_stage(stage, dt, render_world);
+        }
     }
 
-    /// 渲染提取系统：从 ECS 中提取渲染命令
-    pub fn extract_render_objects(&mut self) -> Extracted {
-        crate::scene::systems::extract_render_objects(&mut self.ecs)
+    pub fn run_stage(&mut self, stage: Stage, dt: f32, render_world: &mut RenderWorld) {
+        self.schedule
+            .run(stage, &mut self.ecs, &mut self.resources, render_world, dt);
     }
 
-    pub fn input(&mut self, input_server: &mut InputServer) {
-        crate::scene::systems::handle_input(&mut self.ecs, input_server);
+    /// 渲染提取系统：从 ECS 中提取渲染命令
+    ///
+    /// `alpha` interpolates transforms between the previous (0) and the current (1) tick.
+    pub fn extract_render_objects(&mut self, alpha: f32) -> Extracted {
+        crate::scene::systems::extract_render_objects(&mut self.ecs, alpha)
     }
 }
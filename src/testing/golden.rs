@@ -0,0 +1,382 @@
+use crate::asset::AssetServer;
+use crate::core::App;
+use crate::render::RenderContext;
+use crate::scene::{AssetPending, Camera3dComponent, SkyAssetPending, SpriteAssetPending};
+use crate::text::FontServer;
+use anyhow::*;
+use image::{Rgba, RgbaImage};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Set to re-record reference images instead of comparing against them.
+const UPDATE_ENV_VAR: &str = "EUREKA_UPDATE_GOLDEN";
+
+/// Give up waiting for scene assets after this long.
+const ASSET_TIMEOUT: Duration = Duration::from_secs(60);
+
+type SetupCallback = Box<dyn FnOnce(&mut App)>;
+
+/// Result of comparing a rendered frame against a reference image.
+pub struct ImageComparison {
+    /// Number of pixels whose difference exceeds the tolerance in any channel.
+    pub mismatched_pixels: u32,
+    /// Largest per-channel difference found.
+    pub max_difference: u8,
+    /// Mismatched pixels in red, matching ones as a dimmed grayscale of the reference.
+    pub diff: RgbaImage,
+}
+
+impl ImageComparison {
+    pub fn passed(&self) -> bool {
+        self.mismatched_pixels == 0
+    }
+}
+
+/// Per-pixel comparison of two images. If the sizes differ, the diff covers both and pixels
+/// that only one of the images has count as mismatched.
+pub fn compare_images(actual: &RgbaImage, expected: &RgbaImage, tolerance: u8) -> ImageComparison {
+    let width = actual.width().max(expected.width());
+    let height = actual.height().max(expected.height());
+    let mut diff = RgbaImage::new(width, height);
+    let mut mismatched_pixels = 0;
+    let mut max_difference = 0;
+
+    for (x, y, pixel) in diff.enumerate_pixels_mut() {
+        let (Some(actual_pixel), Some(expected_pixel)) = (
+            actual.get_pixel_checked(x, y),
+            expected.get_pixel_checked(x, y),
+        ) else {
+            mismatched_pixels += 1;
+            max_difference = u8::MAX;
+            *pixel = Rgba([255, 0, 0, 255]);
+            continue;
+        };
+
+        let difference = (0..4)
+            .map(|i| actual_pixel[i].abs_diff(expected_pixel[i]))
+            .max()
+            .unwrap_or(0);
+        max_difference = max_difference.max(difference);
+
+        if difference > tolerance {
+            mismatched_pixels += 1;
+            *pixel = Rgba([255, 0, 0, 255]);
+        } else {
+            let luma =
+                (expected_pixel[0] as u32 + expected_pixel[1] as u32 + expected_pixel[2] as u32)
+                    / 3
+                    / 4;
+            *pixel = Rgba([luma as u8, luma as u8, luma as u8, 255]);
+        }
+    }
+
+    ImageComparison {
+        mismatched_pixels,
+        max_difference,
+        diff,
+    }
+}
+
+/// Golden-image regression test: renders a scene offscreen on the fallback adapter and
+/// compares the final frame against `<reference_dir>/<name>.png`.
+///
+/// ```ignore
+/// GoldenTest::new("ssao_only")
+///     .reference_dir("tests/golden")
+///     .disable_node("ssr")
+///     .disable_node("ssgi")
+///     .ticks(16)
+///     .setup(|app| { /* spawn the scene */ })
+///     .run()
+///     .unwrap();
+/// ```
+///
+/// On failure the actual frame and a diff image are written to the output directory.
+/// Run with `EUREKA_UPDATE_GOLDEN=1` to (re)record the reference images.
+pub struct GoldenTest {
+    name: String,
+    width: u32,
+    height: u32,
+    ticks: u32,
+    jitter_seed: u64,
+    tolerance: u8,
+    disabled_nodes: Vec<String>,
+    reference_dir: PathBuf,
+    output_dir: PathBuf,
+    setup: Option<SetupCallback>,
+}
+
+impl GoldenTest {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            width: 256,
+            height: 256,
+            ticks: 8,
+            jitter_seed: 0,
+            tolerance: 2,
+            disabled_nodes: Vec::new(),
+            reference_dir: PathBuf::from("tests/golden"),
+            output_dir: std::env::temp_dir().join("eureka_golden"),
+            setup: None,
+        }
+    }
+
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Number of fixed logic ticks to run. A frame is rendered after each tick and the last
+    /// one is compared, so TAA has accumulated `ticks` frames of history.
+    pub fn ticks(mut self, ticks: u32) -> Self {
+        self.ticks = ticks.max(1);
+        self
+    }
+
+    /// Starting index into the TAA jitter sequence of every 3D camera.
+    pub fn jitter_seed(mut self, seed: u64) -> Self {
+        self.jitter_seed = seed;
+        self
+    }
+
+    /// Maximum allowed per-channel difference (0-255).
+    pub fn tolerance(mut self, tolerance: u8) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Remove a node from the standard render graph, e.g. "ssr", "ssgi", or "volumetric"
+    /// together with "volumetric_apply".
+    pub fn disable_node(mut self, name: &str) -> Self {
+        self.disabled_nodes.push(name.to_string());
+        self
+    }
+
+    pub fn reference_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.reference_dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Where the actual frame and the diff image are written on failure.
+    pub fn output_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.output_dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Scene setup, called once the headless app is initialized.
+    pub fn setup<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut App) + 'static,
+    {
+        self.setup = Some(Box::new(f));
+        self
+    }
+
+    /// Render the scene and return the final frame without comparing it.
+    pub fn render(mut self) -> Result<RgbaImage> {
+        let mut app = App::new_headless(self.width, self.height);
+        app.set_force_fallback_adapter(true);
+
+        let disabled_nodes = std::mem::take(&mut self.disabled_nodes);
+        app.configure_render_graph(move |graph| {
+            for name in &disabled_nodes {
+                if !graph.remove_node(name) {
+                    log::warn!("Golden test: no render graph node named '{}'", name);
+                }
+            }
+        });
+
+        if let Some(setup) = self.setup.take() {
+            app.setup(setup);
+        }
+
+        // 初始化 (不推进逻辑)，然后等待资产加载完成
+        app.try_step(0)?;
+        wait_for_assets(&mut app)?;
+
+        for camera in app.world.ecs.query_mut::<&mut Camera3dComponent>() {
+            camera.frame_count = self.jitter_seed;
+        }
+
+        app.step(self.ticks - 1);
+
+        let receiver = app
+            .render_world
+            .as_ref()
+            .context("Render world not initialized")?
+            .request_capture();
+        app.step(1);
+
+        receiver
+            .recv()
+            .map_err(|_| anyhow!("Failed to capture frame for golden test '{}'", self.name))
+    }
+
+    /// Render the scene and compare it against the reference image.
+    pub fn run(self) -> Result<()> {
+        let name = self.name.clone();
+        let tolerance = self.tolerance;
+        let reference_path = self.reference_dir.join(format!("{}.png", name));
+        let output_dir = self.output_dir.clone();
+
+        let actual = self.render()?;
+
+        if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+            if let Some(parent) = reference_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            actual.save(&reference_path)?;
+            log::info!("Golden image '{}' recorded to {:?}", name, reference_path);
+            return Ok(());
+        }
+
+        let expected = image::open(&reference_path)
+            .with_context(|| {
+                format!(
+                    "Missing reference image {:?}, run with {}=1 to record it",
+                    reference_path, UPDATE_ENV_VAR
+                )
+            })?
+            .to_rgba8();
+
+        if expected.dimensions() != actual.dimensions() {
+            bail!(
+                "Golden image '{}' has size {:?}, but the rendered frame is {:?}",
+                name,
+                expected.dimensions(),
+                actual.dimensions()
+            );
+        }
+
+        let comparison = compare_images(&actual, &expected, tolerance);
+        if comparison.passed() {
+            return Ok(());
+        }
+
+        std::fs::create_dir_all(&output_dir)?;
+        let actual_path = output_dir.join(format!("{}.actual.png", name));
+        let diff_path = output_dir.join(format!("{}.diff.png", name));
+        actual.save(&actual_path)?;
+        comparison.diff.save(&diff_path)?;
+
+        bail!(
+            "Golden image '{}' differs: {} pixels over tolerance {} (max difference {}), see {:?} and {:?}",
+            name,
+            comparison.mismatched_pixels,
+            tolerance,
+            comparison.max_difference,
+            actual_path,
+            diff_path
+        )
+    }
+}
+
+/// Finalize all pending assets without advancing the simulation, so that the number of logic
+/// ticks doesn't depend on how fast assets load.
+fn wait_for_assets(app: &mut App) -> Result<()> {
+    let start = Instant::now();
+
+    loop {
+        let Some(render_world) = &mut app.render_world else {
+            bail!("App not initialized");
+        };
+        let resources = &app.world.resources;
+        let (Some(mut asset_server), Some(render_context)) = (
+            resources.get_mut::<AssetServer>(),
+            resources.get::<RenderContext>(),
+        ) else {
+            bail!("App not initialized");
+        };
+
+        asset_server.update();
+        resources.resource_mut::<FontServer>().update(
+            &render_context,
+            &mut render_world.imported_texture_cache.write().unwrap(),
+            &mut asset_server,
+        );
+        crate::scene::systems::update_assets(
+            &mut app.world.ecs,
+            &mut asset_server,
+            &render_context,
+            render_world,
+        );
+
+        let ecs = &app.world.ecs;
+        let mut pending_paths: Vec<PathBuf> = Vec::new();
+        pending_paths.extend(ecs.query::<&AssetPending>().iter().map(|p| p.0.clone()));
+        pending_paths.extend(ecs.query::<&SkyAssetPending>().iter().map(|p| p.0.clone()));
+        pending_paths.extend(
+            ecs.query::<&SpriteAssetPending>()
+                .iter()
+                .map(|p| p.0.clone()),
+        );
+
+        if pending_paths.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(error) = pending_paths
+            .iter()
+            .find_map(|path| asset_server.has_failed(path))
+        {
+            bail!("Golden test asset failed to load: {}", error);
+        }
+
+        if start.elapsed() > ASSET_TIMEOUT {
+            bail!("Timed out waiting for assets: {:?}", pending_paths);
+        }
+
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: u32, height: u32, color: [u8; 4]) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, Rgba(color))
+    }
+
+    #[test]
+    fn differences_within_tolerance_pass() {
+        let expected = solid(4, 4, [100, 100, 100, 255]);
+        let actual = solid(4, 4, [102, 98, 100, 255]);
+
+        let comparison = compare_images(&actual, &expected, 2);
+        assert!(comparison.passed());
+        assert_eq!(comparison.max_difference, 2);
+        // 匹配的像素是参考图暗化后的灰度
+        assert_eq!(*comparison.diff.get_pixel(0, 0), Rgba([25, 25, 25, 255]));
+    }
+
+    #[test]
+    fn differences_over_tolerance_are_red_in_the_diff() {
+        let expected = solid(4, 4, [100, 100, 100, 255]);
+        let mut actual = expected.clone();
+        actual.put_pixel(1, 2, Rgba([110, 100, 100, 255]));
+
+        let comparison = compare_images(&actual, &expected, 2);
+        assert!(!comparison.passed());
+        assert_eq!(comparison.mismatched_pixels, 1);
+        assert_eq!(comparison.max_difference, 10);
+        assert_eq!(*comparison.diff.get_pixel(1, 2), Rgba([255, 0, 0, 255]));
+        assert_ne!(*comparison.diff.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn size_mismatch_is_reported_instead_of_panicking() {
+        let expected = solid(4, 4, [0, 0, 0, 255]);
+        let actual = solid(2, 4, [0, 0, 0, 255]);
+
+        let comparison = compare_images(&actual, &expected, 0);
+        assert_eq!(comparison.diff.dimensions(), (4, 4));
+        assert_eq!(comparison.mismatched_pixels, 8);
+        assert_eq!(comparison.max_difference, u8::MAX);
+
+        let comparison = compare_images(&expected, &actual, 0);
+        assert_eq!(comparison.mismatched_pixels, 8);
+    }
+}
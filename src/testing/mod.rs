@@ -0,0 +1,3 @@
+pub(crate) mod golden;
+
+pub use golden::*;
@@ -172,12 +172,24 @@ impl DynamicFont {
         return metrics.ascent;
     }
 
-    /// Upload atlas data to the atlas texture.
+    /// Upload atlas data to the atlas texture, creating it again if it was lost with the device.
     pub(crate) fn upload(
         &mut self,
         render_server: &RenderContext,
-        imported_texture_cache: &TextureCache,
+        imported_texture_cache: &mut TextureCache,
     ) {
+        if imported_texture_cache.get(self.atlas_texture).is_none() {
+            self.atlas_texture = Texture::from_image(
+                &render_server.device,
+                &render_server.queue,
+                imported_texture_cache,
+                &self.atlas_image,
+                "default font atlas".into(),
+            )
+            .unwrap();
+            self.updated_atlas_region = None;
+            return;
+        }
         let texture = imported_texture_cache.get(self.atlas_texture).unwrap();
 
         if let Some(region) = self.updated_atlas_region {
@@ -221,6 +233,15 @@ impl DynamicFont {
         }
     }
 
+    /// Upload the whole atlas again with the next [`DynamicFont::upload`], e.g. after its texture
+    /// was recreated.
+    pub(crate) fn invalidate_atlas(&mut self) {
+        self.updated_atlas_region = Some(RectI::new(
+            Vector2I::new(0, 0),
+            Vector2I::new(FONT_ATLAS_SIZE as i32, FONT_ATLAS_SIZE as i32),
+        ));
+    }
+
     pub(crate) fn reset_atlas(&mut self) {
         log::info!("Font atlas full. Resetting...");
         self.atlas_image =
@@ -70,6 +70,13 @@ impl FontServer {
         }
     }
 
+    /// Re-upload all atlases, their textures have been recreated.
+    pub(crate) fn invalidate_atlases(&mut self) {
+        for font in self.fonts.values_mut() {
+            font.invalidate_atlas();
+        }
+    }
+
     pub(crate) fn get_default_font(&self) -> Option<&DynamicFont> {
         self.fonts.get("default")
     }
@@ -0,0 +1,168 @@
+use crate::window::InputEvent;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Input events seen by each fixed logic tick, recorded by [`crate::window::InputServer`] and fed
+/// back in place of window events on replay. Since logic runs at a fixed tick, replaying a
+/// recording from startup reproduces a session exactly.
+///
+/// Files hold one JSON line per tick with input, after a header with the tick rate and before a
+/// footer with the length, see [`RecordingWriter`].
+#[derive(Clone, Debug, Default)]
+pub struct InputRecording {
+    /// Fixed ticks per second the recording was made at.
+    pub tick_rate: f64,
+    /// Number of recorded ticks.
+    length: u64,
+    /// Only ticks with input are stored.
+    ticks: Vec<RecordedTick>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct RecordedTick {
+    /// Ticks since the recording started.
+    tick: u64,
+    events: Vec<InputEvent>,
+}
+
+/// A line of a recording file.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RecordingLine {
+    Header { tick_rate: f64 },
+    Tick(RecordedTick),
+    Footer { length: u64 },
+}
+
+impl InputRecording {
+    pub fn new(tick_rate: f64) -> Self {
+        Self {
+            tick_rate,
+            ..Default::default()
+        }
+    }
+
+    /// Number of recorded ticks.
+    pub fn len(&self) -> u64 {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    pub(crate) fn push_tick(&mut self, events: &[InputEvent]) {
+        if !events.is_empty() {
+            self.ticks.push(RecordedTick {
+                tick: self.length,
+                events: events.to_vec(),
+            });
+        }
+        self.length += 1;
+    }
+
+    /// Events of a tick, ticks are looked up in increasing order starting at `cursor`.
+    pub(crate) fn tick_events(&self, tick: u64, cursor: &mut usize) -> &[InputEvent] {
+        while self.ticks.get(*cursor).is_some_and(|t| t.tick < tick) {
+            *cursor += 1;
+        }
+        match self.ticks.get(*cursor) {
+            Some(t) if t.tick == tick => &t.events,
+            _ => &[],
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut writer = RecordingWriter::create(path, self.tick_rate)?;
+        for tick in &self.ticks {
+            writer.write_tick(tick.tick, &tick.events)?;
+        }
+        writer.finish(self.length)
+    }
+
+    /// Load a recording. Without a footer, e.g. after a crash, it ends after the last tick with
+    /// input.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .with_context(|| format!("Failed to read input recording {}", path.display()))?;
+
+        let mut lines = BufReader::new(file).lines();
+        let Some(RecordingLine::Header { tick_rate }) = lines
+            .next()
+            .transpose()?
+            .map(|line| serde_json::from_str(&line))
+            .transpose()?
+        else {
+            bail!("Input recording {} has no header", path.display());
+        };
+
+        let mut recording = Self::new(tick_rate);
+        let mut length = None;
+        for line in lines {
+            match serde_json::from_str(&line?)? {
+                RecordingLine::Tick(tick) => {
+                    recording.length = tick.tick + 1;
+                    recording.ticks.push(tick);
+                }
+                RecordingLine::Footer { length: footer } => length = Some(footer),
+                RecordingLine::Header { .. } => {
+                    bail!("Input recording {} has two headers", path.display())
+                }
+            }
+        }
+        match length {
+            Some(length) => recording.length = length,
+            None => log::warn!(
+                "Input recording {} wasn't finished, replaying {} ticks",
+                path.display(),
+                recording.length
+            ),
+        }
+        Ok(recording)
+    }
+}
+
+/// Writes a recording to its file while it is being made, so that ticks recorded before a
+/// crash aren't lost. Used by [`crate::window::InputServer::start_recording_to`].
+pub(crate) struct RecordingWriter {
+    file: BufWriter<File>,
+}
+
+impl RecordingWriter {
+    pub(crate) fn create(path: impl AsRef<Path>, tick_rate: f64) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("Failed to write input recording {}", path.display()))?;
+        let mut writer = Self {
+            file: BufWriter::new(file),
+        };
+        writer.write_line(&RecordingLine::Header { tick_rate })?;
+        Ok(writer)
+    }
+
+    /// Ticks without events are skipped.
+    pub(crate) fn write_tick(&mut self, tick: u64, events: &[InputEvent]) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.write_line(&RecordingLine::Tick(RecordedTick {
+            tick,
+            events: events.to_vec(),
+        }))
+    }
+
+    pub(crate) fn finish(mut self, length: u64) -> Result<()> {
+        self.write_line(&RecordingLine::Footer { length })
+    }
+
+    fn write_line(&mut self, line: &RecordingLine) -> Result<()> {
+        serde_json::to_writer(&mut self.file, line)?;
+        self.file.write_all(b"\n")?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
@@ -1,16 +1,23 @@
+use crate::window::input_recording::RecordingWriter;
+use crate::window::{InputRecording, WindowId};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::path::Path;
 use winit::dpi::PhysicalPosition;
 use winit::event::*;
 use winit::keyboard::{KeyCode, PhysicalKey};
 use winit::window::{CursorGrabMode, Window};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InputEvent {
+    /// Window the event was received by.
+    pub window: WindowId,
     pub content: InputContent,
     pub consumed: bool,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum InputContent {
     MouseButton(MouseButton),
     MouseMotion(MouseMotion),
@@ -18,25 +25,25 @@ pub enum InputContent {
     Key(Key),
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct Key {
     pub key_code: KeyCode,
     pub pressed: bool,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct MouseButton {
     pub button: winit::event::MouseButton,
     pub pressed: bool,
     pub position: (f32, f32),
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct MouseScroll {
     pub delta: f32,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct MouseMotion {
     pub delta: (f32, f32),
     pub position: (f32, f32),
@@ -44,6 +51,10 @@ pub struct MouseMotion {
 
 pub struct InputServer {
     pub(crate) mouse_position: (f32, f32),
+    /// Window the cursor was last moved in, `mouse_position` is relative to it.
+    mouse_window: WindowId,
+    /// Window with keyboard focus, raw device events are attributed to it.
+    focused_window: WindowId,
     pub(crate) input_events: Vec<InputEvent>,
     pub(crate) cursor_captured: bool,
     cursor_state_changed: bool,
@@ -51,17 +62,36 @@ pub struct InputServer {
     // 状态查询缓存
     pressed_keys: HashSet<KeyCode>,
     pressed_mouse_buttons: HashSet<winit::event::MouseButton>,
+
+    /// Fixed ticks since the recording or replay started.
+    tick: u64,
+    recording: Option<InputRecording>,
+    /// Streams the recording to its file, see [`InputServer::start_recording_to`].
+    recording_writer: Option<RecordingWriter>,
+    replay: Option<Replay>,
+}
+
+struct Replay {
+    recording: InputRecording,
+    /// Next recorded tick to look at.
+    cursor: usize,
 }
 
 impl InputServer {
     pub fn new() -> Self {
         Self {
             mouse_position: (0.0f32, 0.0),
+            mouse_window: WindowId::PRIMARY,
+            focused_window: WindowId::PRIMARY,
             input_events: Vec::new(),
             cursor_captured: false,
             cursor_state_changed: false,
             pressed_keys: HashSet::new(),
             pressed_mouse_buttons: HashSet::new(),
+            tick: 0,
+            recording: None,
+            recording_writer: None,
+            replay: None,
         }
     }
 
@@ -77,6 +107,15 @@ impl InputServer {
         self.mouse_position
     }
 
+    /// Window the mouse position refers to.
+    pub fn get_mouse_window(&self) -> WindowId {
+        self.mouse_window
+    }
+
+    pub fn get_focused_window(&self) -> WindowId {
+        self.focused_window
+    }
+
     pub fn set_cursor_capture(&mut self, capture: bool) {
         if self.cursor_captured != capture {
             self.cursor_captured = capture;
@@ -103,11 +142,129 @@ impl InputServer {
         self.input_events.clear();
     }
 
+    /// Record the events seen by each fixed tick from now on, replacing any recording in
+    /// progress.
+    pub fn start_recording(&mut self, tick_rate: f64) {
+        self.tick = 0;
+        self.recording = Some(InputRecording::new(tick_rate));
+        self.recording_writer = None;
+    }
+
+    /// Like [`InputServer::start_recording`], and also write each tick to a file as it is
+    /// recorded, so the recording survives a crash.
+    pub fn start_recording_to(&mut self, tick_rate: f64, path: impl AsRef<Path>) -> Result<()> {
+        let writer = RecordingWriter::create(path, tick_rate)?;
+        self.start_recording(tick_rate);
+        self.recording_writer = Some(writer);
+        Ok(())
+    }
+
+    /// Stop recording, and finish the file of [`InputServer::start_recording_to`].
+    pub fn stop_recording(&mut self) -> Option<InputRecording> {
+        let recording = self.recording.take()?;
+        if let Some(writer) = self.recording_writer.take() {
+            if let Err(err) = writer.finish(recording.len()) {
+                log::error!("{:#}", err);
+            }
+        }
+        Some(recording)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Feed the events of a recording to the following fixed ticks, ignoring window input until
+    /// it ends. The recording has to be made at the app's tick rate.
+    pub fn start_replay(&mut self, recording: InputRecording) {
+        self.tick = 0;
+        self.input_events.clear();
+        self.pressed_keys.clear();
+        self.pressed_mouse_buttons.clear();
+        self.replay = Some(Replay {
+            recording,
+            cursor: 0,
+        });
+    }
+
+    pub fn stop_replay(&mut self) {
+        self.replay = None;
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Called before each fixed tick, after window input has been collected.
+    pub(crate) fn begin_tick(&mut self) {
+        if let Some(replay) = &mut self.replay {
+            if self.tick >= replay.recording.len() {
+                log::info!("Input replay finished after {} ticks", self.tick);
+                self.replay = None;
+                // 交还给真实输入，回放中按下的键不再视为按下
+                self.pressed_keys.clear();
+                self.pressed_mouse_buttons.clear();
+            } else {
+                let events = replay
+                    .recording
+                    .tick_events(self.tick, &mut replay.cursor)
+                    .to_vec();
+                for event in &events {
+                    self.apply_replayed_event(event);
+                }
+                self.input_events = events;
+            }
+        }
+
+        if let Some(recording) = &mut self.recording {
+            recording.push_tick(&self.input_events);
+        }
+        if let Some(writer) = &mut self.recording_writer {
+            if let Err(err) = writer.write_tick(self.tick, &self.input_events) {
+                log::error!("{:#}", err);
+                self.recording_writer = None;
+            }
+        }
+
+        self.tick += 1;
+    }
+
+    /// Update the input state like the window event the replayed event was made from.
+    fn apply_replayed_event(&mut self, event: &InputEvent) {
+        match event.content {
+            InputContent::Key(key) => {
+                if key.pressed {
+                    self.pressed_keys.insert(key.key_code);
+                } else {
+                    self.pressed_keys.remove(&key.key_code);
+                }
+            }
+            InputContent::MouseButton(button) => {
+                if button.pressed {
+                    self.pressed_mouse_buttons.insert(button.button);
+                } else {
+                    self.pressed_mouse_buttons.remove(&button.button);
+                }
+                self.mouse_position = button.position;
+            }
+            InputContent::MouseMotion(motion) => {
+                self.mouse_window = event.window;
+                self.mouse_position = motion.position;
+            }
+            InputContent::MouseScroll(_) => {}
+        }
+    }
+
     /// 处理来自 DeviceEvent 的原始鼠标移动（不受窗口边界限制，无反馈环）
     pub fn handle_device_event(&mut self, event: &DeviceEvent) {
+        if self.replay.is_some() {
+            return;
+        }
+
         if let DeviceEvent::MouseMotion { delta } = event {
             if self.cursor_captured {
                 self.input_events.push(InputEvent {
+                    window: self.focused_window,
                     content: InputContent::MouseMotion(MouseMotion {
                         delta: (delta.0 as f32, delta.1 as f32),
                         position: self.mouse_position,
@@ -118,8 +275,19 @@ impl InputServer {
         }
     }
 
-    pub fn prepare_input_event(&mut self, _window: &Window, event: &WindowEvent) {
+    pub fn prepare_input_event(&mut self, window: WindowId, event: &WindowEvent) {
+        // 回放时忽略真实输入
+        if self.replay.is_some() {
+            return;
+        }
+
         let content = match event {
+            WindowEvent::Focused(focused) => {
+                if *focused {
+                    self.focused_window = window;
+                }
+                None
+            }
             WindowEvent::KeyboardInput { event, .. } => {
                 if let PhysicalKey::Code(code) = event.physical_key {
                     if event.state == ElementState::Pressed {
@@ -157,7 +325,13 @@ impl InputServer {
                 }))
             }
             WindowEvent::CursorMoved { position, .. } => {
-                let last_pos = self.mouse_position;
+                // 切换窗口时坐标系不同，不产生增量
+                let last_pos = if self.mouse_window == window {
+                    self.mouse_position
+                } else {
+                    (position.x as f32, position.y as f32)
+                };
+                self.mouse_window = window;
                 self.mouse_position = (position.x as f32, position.y as f32);
 
                 if !self.cursor_captured {
@@ -177,6 +351,7 @@ impl InputServer {
 
         if let Some(content) = content {
             self.input_events.push(InputEvent {
+                window,
                 content,
                 consumed: false,
             });
@@ -1,3 +1,7 @@
+pub(crate) mod input_recording;
 pub(crate) mod input_server;
+pub(crate) mod windows;
 
+pub use input_recording::*;
 pub use input_server::*;
+pub use windows::*;
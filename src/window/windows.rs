@@ -0,0 +1,127 @@
+use glam::UVec2;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Engine-side window handle. The window created at startup (or the offscreen target of a
+/// headless app) is [`WindowId::PRIMARY`].
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct WindowId(pub(crate) u32);
+
+impl WindowId {
+    pub const PRIMARY: WindowId = WindowId(0);
+}
+
+/// Settings of an additional window, see [`Windows::spawn`].
+#[derive(Clone, Debug)]
+pub struct WindowDescriptor {
+    pub title: String,
+    /// Initial size in logical pixels.
+    pub width: u32,
+    pub height: u32,
+    pub resizable: bool,
+}
+
+impl Default for WindowDescriptor {
+    fn default() -> Self {
+        Self {
+            title: env!("CARGO_PKG_NAME").to_string(),
+            width: 800,
+            height: 600,
+            resizable: true,
+        }
+    }
+}
+
+/// Logic-side state of an open window.
+#[derive(Clone, Copy, Debug)]
+pub struct WindowInfo {
+    /// Surface size in physical pixels.
+    pub size: UVec2,
+    pub scale_factor: f64,
+    pub focused: bool,
+}
+
+/// Sent when a window's surface is resized, also when it is minimized (zero size).
+#[derive(Clone, Copy, Debug)]
+pub struct WindowResized {
+    pub window: WindowId,
+    /// New size in physical pixels.
+    pub size: UVec2,
+}
+
+/// Open windows. Windows spawned or closed here are created/destroyed by the app before the
+/// next frame.
+pub struct Windows {
+    windows: HashMap<WindowId, WindowInfo>,
+    next_id: u32,
+    pub(crate) pending_spawns: Vec<(WindowId, WindowDescriptor)>,
+    pub(crate) pending_closes: Vec<WindowId>,
+}
+
+impl Windows {
+    pub(crate) fn new(primary_size: UVec2, scale_factor: f64) -> Self {
+        let mut windows = HashMap::new();
+        windows.insert(
+            WindowId::PRIMARY,
+            WindowInfo {
+                size: primary_size,
+                scale_factor,
+                focused: true,
+            },
+        );
+
+        Self {
+            windows,
+            next_id: 1,
+            pending_spawns: Vec::new(),
+            pending_closes: Vec::new(),
+        }
+    }
+
+    /// Request a new window. The returned id can be used as a camera target right away.
+    pub fn spawn(&mut self, descriptor: WindowDescriptor) -> WindowId {
+        let id = WindowId(self.next_id);
+        self.next_id += 1;
+        self.pending_spawns.push((id, descriptor));
+        id
+    }
+
+    /// Request closing a window. Closing the primary window exits the app.
+    pub fn close(&mut self, id: WindowId) {
+        self.pending_spawns.retain(|(pending, _)| *pending != id);
+        if self.windows.contains_key(&id) {
+            self.pending_closes.push(id);
+        }
+    }
+
+    pub fn get(&self, id: WindowId) -> Option<&WindowInfo> {
+        self.windows.get(&id)
+    }
+
+    pub fn is_open(&self, id: WindowId) -> bool {
+        self.windows.contains_key(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (WindowId, &WindowInfo)> {
+        self.windows.iter().map(|(id, info)| (*id, info))
+    }
+
+    /// The window with keyboard focus, if any.
+    pub fn focused(&self) -> Option<WindowId> {
+        self.iter().find(|(_, info)| info.focused).map(|(id, _)| id)
+    }
+
+    pub(crate) fn insert(&mut self, id: WindowId, info: WindowInfo) {
+        self.windows.insert(id, info);
+    }
+
+    pub(crate) fn remove(&mut self, id: WindowId) {
+        self.windows.remove(&id);
+    }
+
+    pub(crate) fn get_mut(&mut self, id: WindowId) -> Option<&mut WindowInfo> {
+        self.windows.get_mut(&id)
+    }
+}